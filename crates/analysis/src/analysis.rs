@@ -0,0 +1,513 @@
+use super::analysis_error::AnalysisError;
+use super::analysis_report::AnalysisReport;
+use codebase_files::{ChangedLines, CodebaseFiles, DiffBase, GitTree, WalkOptions};
+use project_configuration::{ProjectConfiguration, ProjectConfigurations};
+use read_ctags::TagsReader;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use token_analysis::{AnalysisFilter, TokenUsageResults};
+use token_search::{
+    FileRestriction, LanguageRestriction, ProgressSink, SilentProgress, Token, TokenSearchConfig,
+    TokenSearchResults,
+};
+
+/// Where tokens are loaded from
+pub enum TagsSource {
+    /// Read and parse a tags file
+    Reader(TagsReader),
+    /// Use tokens which have already been loaded
+    Tokens(Vec<Token>),
+}
+
+/// How the project configuration is chosen
+#[allow(clippy::large_enum_variant)]
+pub enum ProjectConfigurationSource {
+    /// Pick the best match from a set of configurations, falling back to the default
+    Detect(ProjectConfigurations),
+    /// Always use the provided configuration
+    Fixed(ProjectConfiguration),
+}
+
+/// Analysis collects everything needed to identify unused tokens in a codebase
+///
+/// Each option has a sensible default, mirroring the behavior of the `unused` binary without any
+/// flags provided (aside from progress, which is silent by default).
+pub struct Analysis {
+    tags: Option<TagsSource>,
+    files: Option<Vec<PathBuf>>,
+    walk_options: WalkOptions,
+    project_configuration: ProjectConfigurationSource,
+    language_restriction: Option<LanguageRestriction>,
+    file_restriction: FileRestriction,
+    project_file_restriction: bool,
+    git_refs: Vec<String>,
+    consumers: Vec<PathBuf>,
+    changes: Option<DiffBase>,
+    blame: bool,
+    transitive: bool,
+    filter: AnalysisFilter,
+    progress: Arc<dyn ProgressSink>,
+}
+
+impl Default for Analysis {
+    fn default() -> Self {
+        Analysis {
+            tags: None,
+            files: None,
+            walk_options: WalkOptions::default(),
+            project_configuration: ProjectConfigurationSource::Detect(
+                ProjectConfigurations::parse(&ProjectConfigurations::default_yaml())
+                    .expect("the default configuration is valid"),
+            ),
+            language_restriction: None,
+            file_restriction: FileRestriction::default(),
+            project_file_restriction: true,
+            git_refs: vec![],
+            consumers: vec![],
+            changes: None,
+            blame: false,
+            transitive: false,
+            filter: AnalysisFilter::default(),
+            progress: Arc::new(SilentProgress),
+        }
+    }
+}
+
+impl Analysis {
+    /// Load tokens by reading a tags file with the provided reader
+    pub fn tags_reader(mut self, tags_reader: TagsReader) -> Self {
+        self.tags = Some(TagsSource::Reader(tags_reader));
+        self
+    }
+
+    /// Use tokens which have already been loaded
+    pub fn tokens(mut self, tokens: Vec<Token>) -> Self {
+        self.tags = Some(TagsSource::Tokens(tokens));
+        self
+    }
+
+    /// Search the provided files (relative to the root) instead of walking the codebase
+    pub fn files(mut self, files: Vec<PathBuf>) -> Self {
+        self.files = Some(files);
+        self
+    }
+
+    /// Control how the codebase is walked, including its root directory
+    ///
+    /// Unless a tags reader is provided, tags files are also looked for relative to the root.
+    pub fn walk_options(mut self, walk_options: WalkOptions) -> Self {
+        self.walk_options = walk_options;
+        self
+    }
+
+    /// Choose the best matching project configuration from the provided set
+    pub fn project_configurations(mut self, configurations: ProjectConfigurations) -> Self {
+        self.project_configuration = ProjectConfigurationSource::Detect(configurations);
+        self
+    }
+
+    /// Always use the provided project configuration
+    pub fn project_configuration(mut self, configuration: ProjectConfiguration) -> Self {
+        self.project_configuration = ProjectConfigurationSource::Fixed(configuration);
+        self
+    }
+
+    /// Restrict which tokens are searched for based on the language they're defined in
+    pub fn language_restriction(mut self, language_restriction: LanguageRestriction) -> Self {
+        self.language_restriction = Some(language_restriction);
+        self
+    }
+
+    /// Restrict which files are searched for token occurrences
+    ///
+    /// This is combined with the project configuration's own restriction (which, by default,
+    /// excludes documentation) unless `ignore_project_file_restriction` is used.
+    pub fn file_restriction(mut self, file_restriction: FileRestriction) -> Self {
+        self.file_restriction = file_restriction;
+        self
+    }
+
+    /// Search files the project configuration would otherwise exclude
+    pub fn ignore_project_file_restriction(mut self) -> Self {
+        self.project_file_restriction = false;
+        self
+    }
+
+    /// Also search the contents of other git references (branches, tags, or commits)
+    ///
+    /// Files are read from the local repository's object store; tokens used only within these
+    /// references are reported separately from tokens used in the working tree.
+    pub fn git_refs(mut self, git_refs: Vec<String>) -> Self {
+        self.git_refs = git_refs;
+        self
+    }
+
+    /// Also search other codebases on disk which consume the tokens defined in this one
+    ///
+    /// Relative paths are resolved from the root. Tokens referenced by any consumer are considered
+    /// used, and the report includes which consumers reference each token.
+    pub fn consumers(mut self, consumers: Vec<PathBuf>) -> Self {
+        self.consumers = consumers;
+        self
+    }
+
+    /// Only report tokens defined within lines added or modified in a git diff
+    ///
+    /// Occurrences are still searched across the entire codebase.
+    pub fn changes(mut self, base: DiffBase) -> Self {
+        self.changes = Some(base);
+        self
+    }
+
+    /// Attach the commit, author, and date which last modified each finding's definitions
+    ///
+    /// Lines are blamed within the local repository and cached in its git directory. This is
+    /// enabled regardless when the filter limits or sorts findings by age.
+    pub fn blame(mut self) -> Self {
+        self.blame = true;
+        self
+    }
+
+    /// Report tokens only referenced from the definitions of unused tokens as high likelihood
+    ///
+    /// References are attributed to the definition enclosing each occurrence, which relies on
+    /// tags with accurate line numbers; groups of such tokens are reported as dead clusters
+    /// either way.
+    pub fn transitive(mut self) -> Self {
+        self.transitive = true;
+        self
+    }
+
+    /// Filter and sort the findings in the resulting report
+    pub fn filter(mut self, filter: AnalysisFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Report progress while searching and analyzing
+    pub fn progress(mut self, progress: Arc<dyn ProgressSink>) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Run the analysis
+    pub fn run(self) -> Result<AnalysisReport, AnalysisError> {
+        let root = self.walk_options.root.to_path_buf();
+        let tags = self
+            .tags
+            .unwrap_or_else(|| TagsSource::Reader(TagsReader::within(&root)));
+        let (tags_path, tokens) = match tags {
+            TagsSource::Reader(tags_reader) => {
+                let (path, tokens) = Token::all(&tags_reader)?;
+                (Some(path), tokens)
+            }
+            TagsSource::Tokens(tokens) => (None, tokens),
+        };
+        let git_trees = self
+            .git_refs
+            .iter()
+            .map(|reference| GitTree::within(&root, reference))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(AnalysisError::GitRef)?;
+
+        let mut token_search_config = TokenSearchConfig {
+            filter_tokens: TokenSearchConfig::is_searchable,
+            tokens,
+            files: vec![],
+            root: root.to_path_buf(),
+            progress: self.progress,
+            language_restriction: self
+                .language_restriction
+                .unwrap_or_else(LanguageRestriction::except_data_and_docs),
+            file_restriction: FileRestriction::default(),
+            git_trees,
+            consumers: self.consumers,
+        };
+
+        let project_configuration = match self.project_configuration {
+            ProjectConfigurationSource::Detect(configurations) => configurations
+                .best_match_for_tokens(&token_search_config.searchable_tokens())
+                .unwrap_or_default(),
+            ProjectConfigurationSource::Fixed(configuration) => configuration,
+        };
+
+        for token in token_search_config.tokens.iter_mut() {
+            token.classify_test_paths(|path| project_configuration.is_test_file(path));
+        }
+
+        let mut walk_options = self.walk_options;
+        walk_options.source = walk_options.source.or(project_configuration.file_source);
+
+        token_search_config.files = Self::without_consumers(
+            &root,
+            &token_search_config.consumers,
+            match self.files {
+                Some(files) => files,
+                None => CodebaseFiles::with_options(&walk_options)?.paths,
+            },
+        );
+        for consumer in token_search_config.consumers.iter() {
+            let consumer_options = walk_options.with_root(&root.join(consumer));
+            token_search_config.files.extend(
+                CodebaseFiles::with_options(&consumer_options)?
+                    .paths
+                    .into_iter()
+                    .map(|path| consumer.join(path)),
+            );
+        }
+
+        token_search_config.file_restriction = if self.project_file_restriction {
+            project_configuration
+                .searched_files
+                .clone()
+                .combine(self.file_restriction)
+        } else {
+            self.file_restriction
+        };
+
+        let mut filter = self.filter;
+        if let Some(base) = &self.changes {
+            let changed_lines =
+                ChangedLines::from_git(&root, base).map_err(AnalysisError::GitRef)?;
+            filter.set_limited_to_tokens(Self::changed_tokens(
+                &root,
+                &token_search_config.searchable_tokens(),
+                &changed_lines,
+            ));
+        }
+
+        let results = TokenSearchResults::generate_with_config(&token_search_config)
+            .map_err(AnalysisError::GitRef)?;
+        let mut results = TokenUsageResults::calculate(
+            &token_search_config,
+            results,
+            &project_configuration,
+            &filter,
+            self.transitive,
+        );
+
+        if self.blame || filter.requires_blame() {
+            results
+                .blame_definitions(&root, &filter)
+                .map_err(AnalysisError::Blame)?;
+        }
+
+        Ok(AnalysisReport {
+            tags_path,
+            project_configuration,
+            language_restriction: token_search_config.language_restriction,
+            file_restriction: token_search_config.file_restriction,
+            file_source: walk_options.source.unwrap_or_default(),
+            changes: self.changes,
+            filter,
+            results,
+        })
+    }
+
+    /// Remove project files within consumer roots nested in the project; consumer files are
+    /// searched separately, so they would otherwise be counted twice
+    fn without_consumers(root: &Path, consumers: &[PathBuf], files: Vec<PathBuf>) -> Vec<PathBuf> {
+        let nested: Vec<PathBuf> = consumers
+            .iter()
+            .filter_map(|consumer| {
+                let relative = if consumer.is_absolute() {
+                    match consumer.strip_prefix(root) {
+                        Ok(relative) => relative.to_path_buf(),
+                        Err(_) => {
+                            let root = fs::canonicalize(root).ok()?;
+                            fs::canonicalize(consumer)
+                                .ok()?
+                                .strip_prefix(root)
+                                .ok()?
+                                .to_path_buf()
+                        }
+                    }
+                } else {
+                    consumer.to_path_buf()
+                };
+
+                Some(
+                    relative
+                        .components()
+                        .filter(|c| *c != Component::CurDir)
+                        .collect(),
+                )
+            })
+            .collect();
+
+        files
+            .into_iter()
+            .filter(|file| !nested.iter().any(|consumer| file.starts_with(consumer)))
+            .collect()
+    }
+
+    fn changed_tokens(
+        root: &Path,
+        tokens: &[&Token],
+        changed_lines: &ChangedLines,
+    ) -> HashSet<String> {
+        let mut contents: HashMap<&Path, Option<String>> = HashMap::new();
+
+        tokens
+            .iter()
+            .filter(|token| {
+                token.definitions.iter().any(|definition| {
+                    let path = definition.file_path.as_path();
+                    if !changed_lines.includes_file(path) {
+                        return false;
+                    }
+
+                    let line = match definition.line() {
+                        Some(line) => Some(line),
+                        None => contents
+                            .entry(path)
+                            .or_insert_with(|| fs::read_to_string(root.join(path)).ok())
+                            .as_deref()
+                            .and_then(|c| definition.resolve_line(c)),
+                    };
+
+                    line.map(|l| changed_lines.contains(path, l))
+                        .unwrap_or(false)
+                })
+            })
+            .map(|token| token.token.to_string())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use globset::Glob;
+    use read_ctags::{CtagItem, Language, TokenKind};
+    use std::collections::BTreeMap;
+    use token_analysis::UsageLikelihoodStatus;
+
+    fn build_token(name: &str, path: &str) -> Token {
+        Token::new(
+            name.to_string(),
+            vec![CtagItem {
+                name: name.to_string(),
+                file_path: PathBuf::from(path),
+                address: String::from("1"),
+                language: Some(Language::Rust),
+                tags: BTreeMap::new(),
+                kind: TokenKind::Struct,
+            }]
+            .into_iter()
+            .collect(),
+        )
+    }
+
+    #[test]
+    fn excludes_files_based_on_project_configuration() {
+        let all_likelihoods = || {
+            let mut filter = AnalysisFilter::default();
+            filter.usage_likelihood_filter = UsageLikelihoodStatus::all();
+            filter
+        };
+        let tokens = vec![build_token("tags_path", "src/analysis_report.rs")];
+        let files = vec![
+            PathBuf::from("src/analysis_report.rs"),
+            PathBuf::from("src/analysis.rs"),
+        ];
+
+        let configuration = ProjectConfiguration {
+            searched_files: FileRestriction {
+                excluded_globs: vec![Glob::new("**/analysis.rs").unwrap()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let report = Analysis::default()
+            .tokens(tokens.clone())
+            .files(files.clone())
+            .project_configuration(configuration.clone())
+            .filter(all_likelihoods())
+            .run()
+            .unwrap();
+
+        assert_eq!(
+            report.file_restriction.to_string(),
+            "excluding **/analysis.rs"
+        );
+        assert_eq!(
+            report.findings()[0].usage_likelihood.status,
+            UsageLikelihoodStatus::High
+        );
+
+        let report = Analysis::default()
+            .tokens(tokens)
+            .files(files)
+            .project_configuration(configuration)
+            .ignore_project_file_restriction()
+            .filter(all_likelihoods())
+            .run()
+            .unwrap();
+
+        assert_eq!(report.file_restriction.to_string(), "all files");
+        assert_eq!(
+            report.findings()[0].usage_likelihood.status,
+            UsageLikelihoodStatus::Low
+        );
+    }
+
+    #[test]
+    fn excludes_nested_consumers_from_project_files() {
+        let files = vec![
+            PathBuf::from("src/lib.rs"),
+            PathBuf::from("apps/web/src/main.rs"),
+            PathBuf::from("apps/website/src/main.rs"),
+            PathBuf::from("apps/admin/src/main.rs"),
+        ];
+        let consumers = vec![
+            PathBuf::from("./apps/web"),
+            PathBuf::from("/project/apps/admin"),
+            PathBuf::from("../other"),
+        ];
+
+        assert_eq!(
+            Analysis::without_consumers(Path::new("/project"), &consumers, files),
+            vec![
+                PathBuf::from("src/lib.rs"),
+                PathBuf::from("apps/website/src/main.rs"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_findings_without_reading_tags() {
+        let mut filter = AnalysisFilter::default();
+        filter.usage_likelihood_filter = UsageLikelihoodStatus::all();
+
+        let report = Analysis::default()
+            .tokens(vec![
+                build_token("AnalysisReport", "src/analysis_report.rs"),
+                build_token("configuration_name", "src/analysis_report.rs"),
+            ])
+            .files(vec![PathBuf::from("src/analysis_report.rs")])
+            .project_configuration(ProjectConfiguration::default())
+            .filter(filter)
+            .run()
+            .unwrap();
+
+        let findings: Vec<_> = report
+            .findings()
+            .iter()
+            .map(|u| (u.result.token.token.as_str(), u.usage_likelihood.status))
+            .collect();
+
+        assert_eq!(report.tags_path, None);
+        assert_eq!(report.configuration_name(), "Default");
+        assert_eq!(
+            findings,
+            vec![
+                ("AnalysisReport", UsageLikelihoodStatus::Low),
+                ("configuration_name", UsageLikelihoodStatus::High),
+            ]
+        );
+    }
+}
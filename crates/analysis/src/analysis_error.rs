@@ -0,0 +1,40 @@
+use codebase_files::WalkError;
+use read_ctags::ReadCtagsError;
+use std::fmt::{Display, Formatter};
+use std::io;
+
+/// A struct capturing possible failures when running an `Analysis`
+#[derive(Debug)]
+pub enum AnalysisError {
+    /// Tags could not be found or parsed
+    Tags(ReadCtagsError),
+    /// A git reference could not be read from the local repository
+    GitRef(io::Error),
+    /// The codebase could not be walked with the provided options
+    Files(WalkError),
+    /// Definitions could not be blamed within the local repository
+    Blame(io::Error),
+}
+
+impl From<ReadCtagsError> for AnalysisError {
+    fn from(err: ReadCtagsError) -> Self {
+        AnalysisError::Tags(err)
+    }
+}
+
+impl From<WalkError> for AnalysisError {
+    fn from(err: WalkError) -> Self {
+        AnalysisError::Files(err)
+    }
+}
+
+impl Display for AnalysisError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            AnalysisError::Tags(err) => write!(f, "{}", err),
+            AnalysisError::GitRef(err) => write!(f, "{}", err),
+            AnalysisError::Files(err) => write!(f, "{}", err),
+            AnalysisError::Blame(err) => write!(f, "{}", err),
+        }
+    }
+}
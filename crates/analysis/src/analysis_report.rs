@@ -0,0 +1,99 @@
+use codebase_files::{DiffBase, FileSource};
+use project_configuration::{AssertionConflict, ProjectConfiguration};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use token_analysis::{
+    AnalysisFilter, BaselineEntry, DeadCluster, DefinitionSpan, TokenUsage, TokenUsageResults,
+};
+use token_search::{FileRestriction, LanguageRestriction};
+
+/// The outcome of running an `Analysis`
+pub struct AnalysisReport {
+    /// Path to the tags file read, if tokens were loaded from one
+    pub tags_path: Option<PathBuf>,
+    /// The project configuration used to analyze results
+    pub project_configuration: ProjectConfiguration,
+    /// Languages whose tokens were searched for
+    pub language_restriction: LanguageRestriction,
+    /// Restrictions on which files were searched
+    pub file_restriction: FileRestriction,
+    /// Where the list of files searched came from
+    pub file_source: FileSource,
+    /// When set, findings are limited to tokens defined within these changes
+    pub changes: Option<DiffBase>,
+    /// Filter and sort order applied to findings
+    pub filter: AnalysisFilter,
+    pub(crate) results: TokenUsageResults,
+}
+
+impl AnalysisReport {
+    /// Token usages, filtered and sorted based on the analysis filter
+    pub fn findings(&self) -> Vec<&TokenUsage> {
+        self.results.filter(&self.filter)
+    }
+
+    /// Findings the filter matches, including those recorded in its baseline
+    pub fn findings_ignoring_baseline(&self) -> Vec<&TokenUsage> {
+        self.results.filter_ignoring_baseline(&self.filter)
+    }
+
+    /// Estimated lines removed along with every finding, counting overlapping definitions once
+    pub fn removable_lines(&self) -> usize {
+        DefinitionSpan::line_count(self.findings().iter().flat_map(|f| f.spans.iter()))
+    }
+
+    /// Findings hidden by the filter's allowlist
+    pub fn allowlisted(&self) -> Vec<&TokenUsage> {
+        self.results.allowlisted(&self.filter)
+    }
+
+    /// Findings hidden by suppression comments; these are reported when the filter includes them
+    pub fn suppressed(&self) -> Vec<&TokenUsage> {
+        self.results.suppressed(&self.filter)
+    }
+
+    /// Entries in the filter's baseline which no longer match any finding
+    pub fn stale_baseline_entries(&self) -> Vec<&BaselineEntry> {
+        self.results.stale_baseline_entries(&self.filter)
+    }
+
+    /// Every analyzed token usage, regardless of the analysis filter
+    pub fn usages(&self) -> &[TokenUsage] {
+        self.results.usages()
+    }
+
+    /// Groups of tokens only referenced by one another, limited to those including a finding
+    pub fn dead_clusters(&self) -> Vec<&DeadCluster> {
+        let findings = self.findings();
+        let tokens: HashSet<&str> = findings
+            .iter()
+            .map(|f| f.result.token.token.as_str())
+            .collect();
+
+        self.results
+            .dead_clusters()
+            .iter()
+            .filter(|c| c.tokens.iter().any(|t| tokens.contains(t.as_str())))
+            .collect()
+    }
+
+    /// Name of the project configuration used
+    pub fn configuration_name(&self) -> &str {
+        &self.project_configuration.name
+    }
+
+    /// Low-likelihood configurations whose assertions conflict with one another, keyed by name
+    pub fn low_likelihood_conflicts(&self) -> HashMap<String, Vec<AssertionConflict>> {
+        let mut conflict_results = HashMap::new();
+
+        for ll in self.project_configuration.low_likelihood.iter() {
+            let conflicts = ll.conflicts();
+
+            if !conflicts.is_empty() {
+                conflict_results.insert(ll.name.to_string(), conflicts);
+            }
+        }
+
+        conflict_results
+    }
+}
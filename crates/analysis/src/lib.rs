@@ -0,0 +1,48 @@
+#![deny(missing_docs)]
+
+//! `analysis` is a high-level interface for running unused from other Rust tooling.
+//!
+//! It ties together reading tags, searching the codebase for tokens, selecting a project
+//! configuration, and calculating usage likelihood. Nothing is printed and the process is never
+//! exited; callers receive an `AnalysisReport` (or an `AnalysisError`) to handle as they see fit.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use analysis::Analysis;
+//!
+//! match Analysis::default().run() {
+//!     Ok(report) => {
+//!         for usage in report.findings() {
+//!             println!("{}", usage.result.token.token);
+//!         }
+//!     }
+//!     Err(e) => eprintln!("{}", e),
+//! }
+//! ```
+
+mod analysis;
+mod analysis_error;
+mod analysis_report;
+
+pub use self::analysis::*;
+pub use self::analysis_error::*;
+pub use self::analysis_report::*;
+
+pub use codebase_files::{Blame, DiffBase, FileSource, WalkOptions};
+pub use globset::Glob;
+pub use project_configuration::{
+    Allowlist, AllowlistEntry, AllowlistError, ProjectConfiguration, ProjectConfigurations,
+    ALLOWLIST_FILE_NAME,
+};
+pub use read_ctags::{Language, TagsReader};
+pub use token_analysis::{
+    Age, AnalysisFilter, Baseline, BaselineEntry, BaselineError, DeadCluster, DefinitionSpan,
+    FileRemoval, OrderField, RemovalPlan, SkippedDefinition, SortOrder, Suppression, TokenUsage,
+    Triage, TriageDecision, TriageError, UsageLikelihood, UsageLikelihoodReason,
+    UsageLikelihoodStatus, UsageScope, TRIAGE_FILE_NAME,
+};
+pub use token_search::{
+    FileCategory, FileRestriction, JsonProgress, LanguageRestriction, ProgressPhase, ProgressSink,
+    SilentProgress, TerminalProgress, Token,
+};
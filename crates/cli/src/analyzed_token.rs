@@ -1,4 +1,5 @@
-use std::collections::HashSet;
+use analysis::{Blame, DefinitionSpan};
+use std::collections::{BTreeMap, HashSet};
 use std::path::PathBuf;
 use token_analysis::{TokenUsage, UsageLikelihoodStatus};
 
@@ -7,9 +8,17 @@ pub struct AnalyzedToken {
     pub first_path: PathBuf,
     pub likelihood_status: UsageLikelihoodStatus,
     pub likelihood_reason: String,
+    pub likelihood_score: u32,
     pub files: Vec<PathBuf>,
-    pub defined_paths: HashSet<PathBuf>,
+    pub defined_paths: Vec<PathBuf>,
     pub occurred_paths: HashSet<PathBuf>,
+    pub refs_with_usage: Vec<String>,
+    pub consumers_with_usage: Vec<PathBuf>,
+    pub suppression: Option<String>,
+    pub blame: Option<Blame>,
+    pub removable_lines: usize,
+    pub definition_spans: Vec<DefinitionSpan>,
+    pub occurrence_lines: BTreeMap<PathBuf, Vec<usize>>,
 }
 
 impl From<&TokenUsage> for AnalyzedToken {
@@ -18,15 +27,44 @@ impl From<&TokenUsage> for AnalyzedToken {
             token: usage.result.token.token.to_string(),
             first_path: usage.result.token.first_path().to_path_buf(),
             likelihood_status: usage.usage_likelihood.status,
-            likelihood_reason: usage.usage_likelihood.reason.clone(),
+            likelihood_reason: usage.usage_likelihood.reason.to_string(),
+            likelihood_score: usage.usage_likelihood.score,
+            suppression: usage.suppression.as_ref().map(|s| s.to_string()),
+            blame: usage.blame.clone(),
+            removable_lines: usage.removable_lines,
+            definition_spans: usage.spans.clone(),
+            occurrence_lines: usage
+                .result
+                .occurrence_lines
+                .iter()
+                .map(|(path, lines)| (path.to_path_buf(), lines.clone()))
+                .collect(),
             files: usage
                 .result
                 .occurrences
                 .keys()
                 .map(|v| v.to_path_buf())
                 .collect(),
-            defined_paths: usage.result.defined_paths(),
+            defined_paths: usage
+                .result
+                .token
+                .sorted_defined_paths()
+                .into_iter()
+                .cloned()
+                .collect(),
             occurred_paths: usage.result.occurred_paths(),
+            refs_with_usage: usage
+                .result
+                .refs_with_usage()
+                .into_iter()
+                .map(|r| r.to_string())
+                .collect(),
+            consumers_with_usage: usage
+                .result
+                .consumers_with_usage()
+                .into_iter()
+                .map(|c| c.to_path_buf())
+                .collect(),
         }
     }
 }
@@ -1,41 +1,40 @@
 use super::analyzed_token::AnalyzedToken;
+use super::error_message;
+use super::flags::Command;
 use super::formatters;
+use super::grouping::{self, Hotspot, TokenGroup};
 use super::project_configurations_loader::load_and_parse_config;
+use super::types::{GroupBy, LanguageExtension, ProgressFormat};
 use super::{Flags, Format};
-use project_configuration::{AssertionConflict, ProjectConfiguration};
+use analysis::{
+    Allowlist, Analysis, AnalysisReport, Baseline, BaselineEntry, BaselineError, DiffBase, Glob,
+    WalkOptions, ALLOWLIST_FILE_NAME,
+};
+use itertools::Itertools;
+use project_configuration::AssertionConflict;
+use read_ctags::TagsReader;
 use std::collections::{HashMap, HashSet};
 use std::iter::FromIterator;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
 use token_analysis::{
-    AnalysisFilter, SortOrder, TokenUsage, TokenUsageResults, UsageLikelihoodStatus,
+    AnalysisFilter, DeadCluster, SortOrder, TokenUsage, UsageLikelihoodStatus, HISTORY_FILE_NAME,
+    TRIAGE_FILE_NAME,
+};
+use token_search::{
+    FileRestriction, JsonProgress, LanguageRestriction, ProgressSink, SilentProgress,
+    TerminalProgress,
 };
-use token_search::{LanguageRestriction, Token, TokenSearchConfig, TokenSearchResults};
 
 pub struct CliConfiguration<'a> {
     flags: &'a Flags,
-    token_search_config: TokenSearchConfig,
-    analysis_filter: AnalysisFilter,
-    project_configuration: ProjectConfiguration,
-    outcome: TokenUsageResults,
+    report: AnalysisReport,
 }
 
 impl<'a> CliConfiguration<'a> {
-    pub fn new(flags: &'a Flags, tokens: Vec<Token>) -> Self {
-        let token_search_config = build_token_search_config(flags, tokens);
-        let analysis_filter = build_analysis_filter(flags);
-        let results = TokenSearchResults::generate_with_config(&token_search_config);
-        let project_configuration = load_and_parse_config()
-            .best_match(&results)
-            .unwrap_or(ProjectConfiguration::default());
-        let outcome =
-            TokenUsageResults::calculate(&token_search_config, results, &project_configuration);
-
-        Self {
-            flags,
-            token_search_config,
-            analysis_filter,
-            project_configuration,
-            outcome,
-        }
+    pub fn new(flags: &'a Flags, report: AnalysisReport) -> Self {
+        Self { flags, report }
     }
 
     pub fn render(&self) {
@@ -50,104 +49,309 @@ impl<'a> CliConfiguration<'a> {
         !self.flags.no_summary
     }
 
-    pub fn sort_order(&self) -> &SortOrder {
-        &self.analysis_filter.sort_order
+    pub fn sort_order(&self) -> &[SortOrder] {
+        &self.report.filter.sort_order
+    }
+
+    pub fn older_than(&self) -> Option<String> {
+        self.report.filter.older_than.map(|age| age.to_string())
+    }
+
+    pub fn group_by(&self) -> Option<GroupBy> {
+        self.flags.group_by
+    }
+
+    pub fn groups(&self) -> Option<Vec<TokenGroup<'_>>> {
+        self.flags
+            .group_by
+            .map(|group_by| grouping::group(&self.report.findings(), group_by))
+    }
+
+    pub fn removable_lines(&self) -> usize {
+        self.report.removable_lines()
+    }
+
+    pub fn allowlisted_count(&self) -> usize {
+        self.report.allowlisted().len()
+    }
+
+    pub fn suppressed_count(&self) -> usize {
+        self.report.suppressed().len()
+    }
+
+    pub fn stale_baseline_entries(&self) -> Vec<&BaselineEntry> {
+        self.report.stale_baseline_entries()
+    }
+
+    pub fn baseline(&self) -> Option<String> {
+        self.flags
+            .baseline
+            .as_ref()
+            .map(|p| p.display().to_string())
+    }
+
+    pub fn write_baseline(&self, path: &Path) -> Result<usize, BaselineError> {
+        let findings = self.report.findings_ignoring_baseline();
+        Baseline::from_findings(&findings)
+            .write(path)
+            .map(|_| findings.len())
+    }
+
+    pub fn hotspots(&self) -> Vec<Hotspot> {
+        grouping::hotspots(&self.report.findings(), self.report.usages())
     }
 
     pub fn usage_likelihood_filter(&self) -> Vec<String> {
-        self.analysis_filter
+        self.report
+            .filter
             .usage_likelihood_filter
             .iter()
             .map(|f| f.to_string())
             .collect()
     }
 
+    pub fn dead_clusters(&self) -> Vec<&DeadCluster> {
+        self.report.dead_clusters()
+    }
+
+    pub fn used_only_in(&self) -> Vec<String> {
+        self.report
+            .filter
+            .used_only_in
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    pub fn kinds(&self) -> Vec<String> {
+        self.report
+            .filter
+            .kinds
+            .iter()
+            .map(|k| k.to_string())
+            .collect()
+    }
+
     pub fn max_token_length(&self) -> usize {
-        self.outcome
-            .filter(&self.analysis_filter)
+        self.report
+            .findings()
             .iter()
             .map(|t| t.result.token.token.len())
-            .into_iter()
             .max()
             .unwrap_or(0)
     }
 
     pub fn max_file_length(&self) -> usize {
-        self.outcome
-            .filter(&self.analysis_filter)
+        self.report
+            .findings()
             .iter()
             .map(|t| t.result.token.first_path().to_string_lossy().len())
-            .into_iter()
             .max()
             .unwrap_or(0)
     }
 
     pub fn language_restriction(&self) -> String {
-        self.token_search_config.language_restriction.to_string()
+        self.report.language_restriction.to_string()
+    }
+
+    pub fn file_restriction(&self) -> String {
+        self.report.file_restriction.to_string()
+    }
+
+    pub fn file_source(&self) -> String {
+        self.report.file_source.to_string()
+    }
+
+    pub fn changes(&self) -> Option<String> {
+        self.report.changes.as_ref().map(|c| c.to_string())
     }
 
     pub fn for_json(&self) -> Vec<&TokenUsage> {
-        self.outcome.filter(&self.analysis_filter)
+        self.report.findings()
     }
 
     pub fn analyses(&self) -> Vec<AnalyzedToken> {
-        self.outcome
-            .filter(&self.analysis_filter)
+        self.report
+            .findings()
             .into_iter()
             .map(|t| t.into())
             .collect()
     }
 
     pub fn configuration_name(&self) -> String {
-        self.project_configuration.name.to_string()
+        self.report.configuration_name().to_string()
     }
 
     pub fn low_likelihood_conflicts(&self) -> HashMap<String, Vec<AssertionConflict>> {
-        let mut conflict_results = HashMap::new();
+        self.report.low_likelihood_conflicts()
+    }
+}
+
+pub fn build_analysis(
+    cmd: &Flags,
+    tags_reader: TagsReader,
+    baseline: Option<Baseline>,
+    allowlist: Allowlist,
+) -> Analysis {
+    let mut analysis_filter = build_analysis_filter(cmd);
+    analysis_filter.set_allowlist(allowlist);
 
-        for ll in self.project_configuration.low_likelihood.iter() {
-            let conflicts = ll.conflicts();
+    if let Some(baseline) = baseline {
+        analysis_filter.set_baseline(baseline);
+    }
 
-            if conflicts.len() > 0 {
-                conflict_results.insert(ll.name.to_string(), conflicts);
+    let mut analysis = Analysis::default()
+        .tags_reader(tags_reader)
+        .project_configurations(match load_and_parse_config() {
+            Ok(configurations) => configurations,
+            Err(e) => {
+                error_message::failed_configuration(e);
+                process::exit(1)
             }
-        }
+        })
+        .walk_options(build_walk_options(cmd))
+        .file_restriction(build_file_restriction(cmd))
+        .git_refs(cmd.git_refs.clone())
+        .consumers(cmd.consumers.clone())
+        .filter(analysis_filter)
+        .progress(build_progress_sink(cmd));
 
-        conflict_results
+    if let Some(language_restriction) =
+        build_language_restriction(&cmd.only_filetypes, &cmd.except_filetypes)
+    {
+        analysis = analysis.language_restriction(language_restriction);
     }
+
+    if let Some(reference) = &cmd.since {
+        analysis = analysis.changes(DiffBase::Since(reference.to_string()));
+    }
+
+    if cmd.staged {
+        analysis = analysis.changes(DiffBase::Staged);
+    }
+
+    if cmd.search_all_files {
+        analysis = analysis.ignore_project_file_restriction();
+    }
+
+    if cmd.blame {
+        analysis = analysis.blame();
+    }
+
+    if cmd.transitive {
+        analysis = analysis.transitive();
+    }
+
+    analysis
 }
 
-fn build_token_search_config(cmd: &Flags, token_results: Vec<Token>) -> TokenSearchConfig {
-    let mut search_config = TokenSearchConfig::default();
-    search_config.tokens = token_results;
+pub fn build_walk_options(cmd: &Flags) -> WalkOptions {
+    let mut walk_options = WalkOptions::default();
 
-    if cmd.no_progress {
-        search_config.display_progress = false;
+    if let Some(directory) = &cmd.directory {
+        walk_options.root = directory.to_path_buf();
     }
 
-    if !cmd.only_filetypes.is_empty() {
-        search_config.language_restriction = LanguageRestriction::Only(to_hash_set(
-            &cmd.only_filetypes
-                .clone()
-                .into_iter()
-                .map(|v| v.into())
+    walk_options.source = cmd.files_from.map(|v| v.into());
+    walk_options.follow_symlinks = cmd.follow_symlinks;
+    walk_options.hidden = !cmd.no_hidden;
+    walk_options
+        .ignore_filenames
+        .extend(cmd.ignore_files.iter().cloned());
+    walk_options.max_depth = cmd.max_depth;
+    walk_options.globs = cmd.globs.clone();
+
+    walk_options
+}
+
+fn build_language_restriction(
+    only_filetypes: &[LanguageExtension],
+    except_filetypes: &[LanguageExtension],
+) -> Option<LanguageRestriction> {
+    let mut language_restriction = None;
+
+    if !only_filetypes.is_empty() {
+        language_restriction = Some(LanguageRestriction::Only(to_hash_set(
+            only_filetypes
+                .iter()
+                .map(|&v| v.into())
                 .collect::<Vec<_>>()
                 .as_slice(),
-        ));
+        )));
     }
 
-    if !cmd.except_filetypes.is_empty() {
-        search_config.language_restriction = LanguageRestriction::Except(to_hash_set(
-            &cmd.except_filetypes
-                .clone()
-                .into_iter()
-                .map(|v| v.into())
+    if !except_filetypes.is_empty() {
+        language_restriction = Some(LanguageRestriction::Except(to_hash_set(
+            except_filetypes
+                .iter()
+                .map(|&v| v.into())
                 .collect::<Vec<_>>()
                 .as_slice(),
-        ));
+        )));
     }
 
-    search_config
+    language_restriction
+}
+
+fn build_file_restriction(cmd: &Flags) -> FileRestriction {
+    FileRestriction {
+        languages: build_language_restriction(
+            &cmd.search_only_filetypes,
+            &cmd.search_except_filetypes,
+        )
+        .unwrap_or(LanguageRestriction::NoRestriction),
+        excluded_globs: cmd
+            .search_excluded_globs
+            .iter()
+            .cloned()
+            .chain(unused_file_globs(cmd))
+            .collect(),
+        excluded_categories: cmd
+            .search_excluded_categories
+            .iter()
+            .map(|&c| c.into())
+            .collect(),
+    }
+}
+
+/// Baseline, allowlist, history, and triage files list tokens by name, so searching them would
+/// count as usage
+fn unused_file_globs(cmd: &Flags) -> Vec<Glob> {
+    let root = build_walk_options(cmd).root;
+    let allowlist = Some(PathBuf::from(ALLOWLIST_FILE_NAME)).filter(|p| root.join(p).exists());
+    let history = match &cmd.cmd {
+        Some(Command::Snapshot(snapshot_flags)) => Some(snapshot_flags.history.clone()),
+        _ => Some(PathBuf::from(HISTORY_FILE_NAME)).filter(|p| root.join(p).exists()),
+    };
+    let triage = match &cmd.cmd {
+        Some(Command::Triage(triage_flags)) => Some(triage_flags.state.clone()),
+        _ => Some(PathBuf::from(TRIAGE_FILE_NAME)).filter(|p| root.join(p).exists()),
+    };
+
+    cmd.baseline
+        .iter()
+        .chain(cmd.write_baseline.iter())
+        .chain(allowlist.iter())
+        .chain(history.iter())
+        .chain(triage.iter())
+        .filter_map(|path| {
+            let path = path.strip_prefix(&root).unwrap_or(path);
+            Glob::new(path.to_str()?).ok()
+        })
+        .unique()
+        .collect()
+}
+
+fn build_progress_sink(cmd: &Flags) -> Arc<dyn ProgressSink> {
+    if cmd.no_progress {
+        return Arc::new(SilentProgress);
+    }
+
+    match cmd.progress {
+        ProgressFormat::Terminal => Arc::new(TerminalProgress::default()),
+        ProgressFormat::Json => Arc::new(JsonProgress::default()),
+        ProgressFormat::None => Arc::new(SilentProgress),
+    }
 }
 
 fn build_analysis_filter(cmd: &Flags) -> AnalysisFilter {
@@ -157,14 +361,19 @@ fn build_analysis_filter(cmd: &Flags) -> AnalysisFilter {
         analysis_filter.usage_likelihood_filter = cmd.likelihoods.clone();
     }
 
-    if cmd.all_likelihoods {
+    if cmd.all_likelihoods || (cmd.likelihoods.is_empty() && !cmd.used_only_in.is_empty()) {
         analysis_filter.usage_likelihood_filter = UsageLikelihoodStatus::all();
     }
 
-    analysis_filter.set_order_field(cmd.sort_order.clone().into());
+    analysis_filter.used_only_in = cmd.used_only_in.clone();
+    analysis_filter.kinds = cmd.kinds.clone();
+    analysis_filter.include_suppressed = cmd.show_suppressed;
+    analysis_filter.older_than = cmd.older_than;
+
+    analysis_filter.set_sort_order(cmd.sort_order.clone());
 
     if cmd.reverse {
-        analysis_filter.set_order_descending();
+        analysis_filter.reverse_sort_order();
     }
 
     analysis_filter.set_ignored(cmd.ignore.clone());
@@ -9,29 +9,58 @@ use super::doctor::{
     check_up::*, files_count::*, loaded_project_configurations::*,
     tags_included_in_files_searched::*, tokens_count::*, using_universal_ctags::*,
 };
+use super::project_configurations_loader::load_and_parse_config;
+use codebase_files::WalkOptions;
 use colored::*;
 use read_ctags::TagsReader;
+use token_search::Token;
 
 pub struct Doctor {
     checks: Vec<Box<dyn CheckUp>>,
 }
 
 impl Doctor {
-    pub fn new(tags_reader: &TagsReader) -> Self {
+    pub fn new(tags_reader: &TagsReader, walk_options: &WalkOptions) -> Self {
+        let walk_options = Self::with_configured_file_source(tags_reader, walk_options);
+
         Self {
             checks: vec![
-                Box::new(IncludingTagsInFilesSearched::new(tags_reader)),
+                Box::new(IncludingTagsInFilesSearched::new(
+                    tags_reader,
+                    &walk_options,
+                )),
                 Box::new(TokensCount::new(tags_reader)),
-                Box::new(FilesCount::new()),
+                Box::new(FilesCount::new(&walk_options)),
                 Box::new(UsingUniversalCtags::new(tags_reader)),
                 Box::new(LoadedProjectConfigurations::new()),
             ],
         }
     }
 
+    /// List files from the source the matching project configuration selects, as analysis does,
+    /// unless one was provided
+    fn with_configured_file_source(
+        tags_reader: &TagsReader,
+        walk_options: &WalkOptions,
+    ) -> WalkOptions {
+        let mut walk_options = walk_options.clone();
+
+        if walk_options.source.is_none() {
+            if let (Ok(configurations), Ok((_, tokens))) =
+                (load_and_parse_config(), Token::all(tags_reader))
+            {
+                walk_options.source = configurations
+                    .best_match_for_tokens(&tokens.iter().collect::<Vec<_>>())
+                    .and_then(|configuration| configuration.file_source);
+            }
+        }
+
+        walk_options
+    }
+
     pub fn render(&self) {
         println!("Unused Doctor");
-        println!("");
+        println!();
 
         let mut oks = 0;
         let mut warnings = 0;
@@ -44,10 +73,10 @@ impl Doctor {
                 Status::Error(_) => errors += 1,
             }
 
-            Self::render_check_up(check)
+            Self::render_check_up(check.as_ref())
         }
 
-        println!("");
+        println!();
         println!(
             "{}: {}, {}, {}",
             Self::colorized_outcome(warnings, errors),
@@ -69,7 +98,7 @@ impl Doctor {
         }
     }
 
-    fn render_check_up(check_up: &Box<dyn CheckUp>) {
+    fn render_check_up(check_up: &dyn CheckUp) {
         match check_up.status() {
             Status::OK(message) => Self::render_status("OK".green(), check_up.name(), message),
             Status::Warn(message) => {
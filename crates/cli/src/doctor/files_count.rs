@@ -1,12 +1,11 @@
 use super::check_up::{CheckUp, Status};
-use codebase_files::CodebaseFiles;
+use codebase_files::{CodebaseFiles, WalkError, WalkOptions};
 
-pub struct FilesCount(usize);
+pub struct FilesCount(Result<usize, WalkError>);
 
 impl FilesCount {
-    pub fn new() -> Self {
-        let file_paths = CodebaseFiles::all().paths;
-        Self(file_paths.len())
+    pub fn new(walk_options: &WalkOptions) -> Self {
+        Self(CodebaseFiles::with_options(walk_options).map(|files| files.paths.len()))
     }
 }
 
@@ -16,11 +15,10 @@ impl CheckUp for FilesCount {
     }
 
     fn status(&self) -> Status {
-        let message = format!("{} file(s) found", self.0);
-        if self.0 == 0 {
-            Status::Warn(message)
-        } else {
-            Status::OK(message)
+        match &self.0 {
+            Ok(0) => Status::Warn(String::from("0 file(s) found")),
+            Ok(count) => Status::OK(format!("{} file(s) found", count)),
+            Err(e) => Status::Error(format!("Unable to find files: {}", e)),
         }
     }
 }
@@ -2,9 +2,9 @@ use super::{
     super::project_configurations_loader::load_and_parse_config,
     check_up::{CheckUp, Status},
 };
-use project_configuration::ProjectConfigurations;
+use project_configuration::{ConfigurationError, ProjectConfigurations};
 
-pub struct LoadedProjectConfigurations(ProjectConfigurations);
+pub struct LoadedProjectConfigurations(Result<ProjectConfigurations, ConfigurationError>);
 
 impl LoadedProjectConfigurations {
     pub fn new() -> Self {
@@ -12,7 +12,10 @@ impl LoadedProjectConfigurations {
     }
 
     fn config_keys(&self) -> Vec<String> {
-        self.0.project_config_names()
+        self.0
+            .as_ref()
+            .map(|configs| configs.project_config_names())
+            .unwrap_or_default()
     }
 }
 
@@ -22,6 +25,10 @@ impl CheckUp for LoadedProjectConfigurations {
     }
 
     fn status(&self) -> Status {
+        if let Err(e) = &self.0 {
+            return Status::Error(format!("Unable to load project configurations: {}", e));
+        }
+
         if self.config_keys().is_empty() {
             Status::Warn(
                 "No project configurations were loaded; using default config instead.".to_string(),
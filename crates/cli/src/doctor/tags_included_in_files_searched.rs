@@ -1,5 +1,5 @@
 use super::check_up::{CheckUp, Status};
-use codebase_files::CodebaseFiles;
+use codebase_files::{CodebaseFiles, WalkOptions};
 use read_ctags::TagsReader;
 use std::path::PathBuf;
 use token_search::Token;
@@ -13,13 +13,17 @@ pub enum IncludingTagsInFilesSearched {
 }
 
 impl IncludingTagsInFilesSearched {
-    pub fn new(tags_reader: &TagsReader) -> Self {
-        match Token::all(tags_reader) {
-            Ok((ctags_path, _)) => IncludingTagsInFilesSearched::Success {
-                files_searched: CodebaseFiles::all().paths,
+    pub fn new(tags_reader: &TagsReader, walk_options: &WalkOptions) -> Self {
+        match (
+            Token::all(tags_reader),
+            CodebaseFiles::with_options(walk_options),
+        ) {
+            (Ok((ctags_path, _)), Ok(files)) => IncludingTagsInFilesSearched::Success {
+                files_searched: files.paths,
                 ctags_path,
             },
-            Err(e) => IncludingTagsInFilesSearched::Failure(format!("{}", e)),
+            (Err(e), _) => IncludingTagsInFilesSearched::Failure(format!("{}", e)),
+            (_, Err(e)) => IncludingTagsInFilesSearched::Failure(format!("{}", e)),
         }
     }
 
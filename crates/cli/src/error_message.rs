@@ -1,15 +1,113 @@
+use codebase_files::WalkError;
 use colored::*;
+use project_configuration::{AllowlistError, ConfigurationError};
 use read_ctags::ReadCtagsError;
+use std::io;
+use token_analysis::{BaselineError, HistoryError, TriageError};
 
 pub fn failed_token_parse(err: ReadCtagsError) {
     eprintln!("{}", "Failed to parse tags".red());
-    eprintln!("");
+    eprintln!();
     eprintln!("Uh oh!");
-    eprintln!("");
+    eprintln!();
     eprintln!("It looks there's an issue with your ctags file; either it doesn't exist, or the formatting is off.");
-    eprintln!("");
+    eprintln!();
     eprintln!("Ensure you've installed Universal Ctags (https://ctags.io/) and re-run it within your application.");
-    eprintln!("");
+    eprintln!();
+    eprintln!("Error:");
+    eprintln!("{}", format!("{}", err).cyan());
+}
+
+pub fn failed_git_ref(err: io::Error) {
+    eprintln!("{}", "Failed to read git reference".red());
+    eprintln!();
+    eprintln!("Ensure the reference exists in the local repository (e.g. fetch the branch first).");
+    eprintln!();
+    eprintln!("Error:");
+    eprintln!("{}", format!("{}", err).cyan());
+}
+
+pub fn failed_walk(err: WalkError) {
+    eprintln!("{}", "Failed to find files".red());
+    eprintln!();
+    eprintln!("Ensure any globs provided are valid, and when listing files from git, that you're within a git repository.");
+    eprintln!();
+    eprintln!("Error:");
+    eprintln!("{}", format!("{}", err).cyan());
+}
+
+pub fn failed_blame(err: io::Error) {
+    eprintln!("{}", "Failed to blame definitions".red());
+    eprintln!();
+    eprintln!("Ensure unused is run within a git repository with git available on your PATH.");
+    eprintln!();
+    eprintln!("Error:");
+    eprintln!("{}", format!("{}", err).cyan());
+}
+
+pub fn failed_fix(err: io::Error) {
+    eprintln!("{}", "Failed to remove definitions".red());
+    eprintln!();
+    eprintln!("Some files may have been changed; review them with git before re-running.");
+    eprintln!();
+    eprintln!("Error:");
+    eprintln!("{}", format!("{}", err).cyan());
+}
+
+pub fn failed_configuration(err: ConfigurationError) {
+    eprintln!("{}", "Failed to load project configurations".red());
+    eprintln!();
+    eprintln!(
+        "Fix the entry in ~/.config/unused/unused.yml, or compare it with unused default-yaml."
+    );
+    eprintln!();
+    eprintln!("Error:");
+    eprintln!("{}", format!("{}", err).cyan());
+}
+
+pub fn failed_baseline(err: BaselineError) {
+    eprintln!("{}", "Failed to read or write baseline".red());
+    eprintln!();
+    eprintln!("Ensure the baseline file exists and was written by unused --write-baseline.");
+    eprintln!();
+    eprintln!("Error:");
+    eprintln!("{}", format!("{}", err).cyan());
+}
+
+pub fn failed_allowlist(err: AllowlistError) {
+    eprintln!("{}", "Failed to read or write allowlist".red());
+    eprintln!();
+    eprintln!("Ensure .unused-allowlist is a YAML list of entries, each with a token and reason.");
+    eprintln!();
+    eprintln!("Error:");
+    eprintln!("{}", format!("{}", err).cyan());
+}
+
+pub fn failed_history(err: HistoryError) {
+    eprintln!("{}", "Failed to read or write history".red());
+    eprintln!();
+    eprintln!("Ensure the history file was written by unused snapshot.");
+    eprintln!();
+    eprintln!("Error:");
+    eprintln!("{}", format!("{}", err).cyan());
+}
+
+pub fn failed_triage(err: TriageError) {
+    eprintln!("{}", "Failed to read or write triage decisions".red());
+    eprintln!();
+    eprintln!("Ensure the triage file was written by unused triage, or remove it to start over.");
+    eprintln!();
+    eprintln!("Error:");
+    eprintln!("{}", format!("{}", err).cyan());
+}
+
+pub fn failed_terminal(err: io::Error) {
+    eprintln!("{}", "Failed to run interactive triage".red());
+    eprintln!();
+    eprintln!(
+        "unused triage requires an interactive terminal; use the standard output for scripts."
+    );
+    eprintln!();
     eprintln!("Error:");
     eprintln!("{}", format!("{}", err).cyan());
 }
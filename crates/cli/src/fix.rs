@@ -0,0 +1,53 @@
+use super::error_message;
+use super::flags::FixFlags;
+use analysis::{AnalysisReport, RemovalPlan, WalkOptions};
+use colored::*;
+use std::process;
+
+pub fn run(flags: &FixFlags, report: &AnalysisReport, walk_options: &WalkOptions) {
+    let plan = RemovalPlan::new(
+        &walk_options.root,
+        &report.findings(),
+        flags.include_ambiguous,
+    );
+
+    if !plan.skipped.is_empty() {
+        eprintln!(
+            "Skipped definitions: ({})",
+            plan.skipped.len().to_string().yellow()
+        );
+        for skipped in plan.skipped.iter() {
+            eprintln!(
+                "   * {} {} {}",
+                skipped.token.yellow(),
+                skipped.path.display().to_string().cyan(),
+                format!("({})", skipped.reason).cyan()
+            );
+        }
+    }
+
+    if plan.files.is_empty() {
+        eprintln!("No definitions to remove");
+        return;
+    }
+
+    if flags.apply {
+        if let Err(e) = plan.apply(&walk_options.root) {
+            error_message::failed_fix(e);
+            process::exit(1)
+        }
+
+        println!(
+            "Removed {} lines from {} files",
+            plan.removed_lines().to_string().cyan(),
+            plan.files.len().to_string().cyan()
+        );
+    } else {
+        print!("{}", plan.diff());
+        eprintln!(
+            "Would remove {} lines from {} files; use --apply to write changes",
+            plan.removed_lines().to_string().cyan(),
+            plan.files.len().to_string().cyan()
+        );
+    }
+}
@@ -1,7 +1,11 @@
 use super::types::*;
-use clap::{Parser, Subcommand};
+use analysis::Glob;
+use clap::{Args, Parser, Subcommand};
+use read_ctags::TokenKind;
 use std::path::PathBuf;
-use token_analysis::UsageLikelihoodStatus;
+use token_analysis::{
+    Age, SortOrder, UsageLikelihoodStatus, UsageScope, HISTORY_FILE_NAME, TRIAGE_FILE_NAME,
+};
 
 #[derive(Debug, Subcommand)]
 pub enum Command {
@@ -10,6 +14,104 @@ pub enum Command {
 
     /// Write the default YAML configuration to STDOUT
     DefaultYaml,
+
+    /// Add a token to the project's .unused-allowlist, or prune entries for removed tokens
+    Ignore(IgnoreFlags),
+
+    /// Record a snapshot of current findings to the project's history file
+    Snapshot(SnapshotFlags),
+
+    /// Show how findings changed across snapshots in the project's history file
+    Trend(TrendFlags),
+
+    /// Remove the definitions of reported tokens, printing a unified diff unless --apply is used
+    ///
+    /// Definitions are removed from their tag's line through its end line (generate tags with
+    /// --fields=+ne); definitions which can't be removed safely are listed on STDERR. Findings are
+    /// chosen with the same flags as the report, e.g. `unused fix --kinds function`.
+    Fix(FixFlags),
+
+    /// Step through findings in a full-screen terminal interface, marking each to delete later,
+    /// ignore (adding it to the project's .unused-allowlist), or skip
+    ///
+    /// Decisions are saved after each change, so triage resumes where it left off.
+    Triage(TriageFlags),
+}
+
+#[derive(Debug, Args)]
+pub struct FixFlags {
+    /// Print a unified diff of the removals without changing any files (the default)
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Write the removals to disk
+    #[arg(long, conflicts_with = "dry_run")]
+    pub apply: bool,
+
+    /// Also remove tokens whose name has several definitions, and tokens only used by other
+    /// unused tokens
+    #[arg(long)]
+    pub include_ambiguous: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct TriageFlags {
+    /// File to save triage decisions to
+    #[arg(long, default_value = TRIAGE_FILE_NAME)]
+    pub state: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct SnapshotFlags {
+    /// History file to append to
+    #[arg(long, default_value = HISTORY_FILE_NAME)]
+    pub history: PathBuf,
+
+    /// Label for the snapshot, e.g. a release or commit
+    #[arg(long)]
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct TrendFlags {
+    /// History file to read
+    #[arg(long, default_value = HISTORY_FILE_NAME)]
+    pub history: PathBuf,
+
+    /// Snapshot (numbered from 1) to compare from; defaults to the second-to-last
+    #[arg(long)]
+    pub from: Option<usize>,
+
+    /// Snapshot (numbered from 1) to compare to; defaults to the last
+    #[arg(long)]
+    pub to: Option<usize>,
+}
+
+#[derive(Debug, Args)]
+pub struct IgnoreFlags {
+    /// Token to allow
+    #[arg(required_unless_present = "prune", conflicts_with = "prune")]
+    pub token: Option<String>,
+
+    /// Only allow the token when defined at this path, or within this directory
+    #[arg(long)]
+    pub path: Option<PathBuf>,
+
+    /// Only allow the token when defined as this kind, e.g. method
+    #[arg(long)]
+    pub kind: Option<TokenKind>,
+
+    /// Why the token is used despite being reported
+    #[arg(long, required_unless_present = "prune")]
+    pub reason: Option<String>,
+
+    /// Who allowed the token; defaults to the git user name
+    #[arg(long)]
+    pub author: Option<String>,
+
+    /// Remove entries whose token no longer exists in the tags file
+    #[arg(long)]
+    pub prune: bool,
 }
 
 #[derive(Debug, Parser)]
@@ -33,40 +135,129 @@ pub struct Flags {
     pub json: bool,
 
     /// Hide progress bar
+    ///
+    /// This is equivalent to --progress=none
     #[arg(long, short = 'P')]
     pub no_progress: bool,
 
+    /// Report progress as a terminal progress bar, JSON lines on STDERR, or not at all
+    #[arg(long, value_parser, default_value_t)]
+    pub progress: ProgressFormat,
+
     /// Include tokens that fall into any likelihood category
-    #[arg(long, short = 'a')]
+    #[arg(long, short = 'a', global = true)]
     pub all_likelihoods: bool,
 
     /// Limit token output to those that match the provided likelihood(s)
     ///
-    /// This allows for a comma-delimited list of likelihoods (low, medium, high).
-    #[arg(
-        long = "likelihood",
-        short = 'l',
-        value_delimiter = ',',
-        default_value = "high"
-    )]
+    /// This allows for a comma-delimited list of likelihoods (low, medium, high). Defaults to
+    /// high.
+    #[arg(long = "likelihood", short = 'l', value_delimiter = ',', global = true)]
     pub likelihoods: Vec<UsageLikelihoodStatus>,
 
-    /// Sort output
-    #[arg(long, value_parser, default_value_t)]
-    pub sort_order: SortOrder,
+    /// Limit token output to those used only in tests or only in config
+    ///
+    /// This allows for a comma-delimited list of scopes (tests, config). Tokens of any
+    /// likelihood are included unless --likelihood is provided.
+    #[arg(long, value_delimiter = ',', global = true)]
+    pub used_only_in: Vec<UsageScope>,
+
+    /// Limit token output to those defined as the provided kind(s), e.g. field,callback
+    #[arg(long, value_delimiter = ',', global = true)]
+    pub kinds: Vec<TokenKind>,
+
+    /// Include tokens suppressed by an unused:ignore comment at their definition
+    #[arg(long, global = true)]
+    pub show_suppressed: bool,
+
+    /// Sort output by one or more keys, each optionally suffixed with :asc or :desc, e.g.
+    /// likelihood,file:desc. Keys: token, file, directory, occurrences, likelihood, kind,
+    /// language, definitions, score, age, size (estimated removable lines)
+    #[arg(long, value_delimiter = ',', default_value = "token")]
+    pub sort_order: Vec<SortOrder>,
+
+    /// Show when each token's definitions were last modified, and by whom, using git blame
+    ///
+    /// Results are cached within the repository's git directory. This is enabled automatically
+    /// when sorting by age or using --older-than.
+    #[arg(long)]
+    pub blame: bool,
+
+    /// Report tokens only referenced from the definitions of unused tokens as high likelihood
+    #[arg(long, global = true)]
+    pub transitive: bool,
+
+    /// Limit token output to those whose definitions were last committed at least this long
+    /// ago, e.g. 180d, 6w, 3m, or 2y
+    #[arg(long, value_name = "AGE", global = true)]
+    pub older_than: Option<Age>,
 
     /// Reverse sort order
     #[arg(long)]
     pub reverse: bool,
 
+    /// Group output by the primary definition's file, directory, kind, or language
+    ///
+    /// Each group lists subtotals by likelihood, and directories are ranked as hotspots by their
+    /// findings, weighted by the share of their tokens that are reported.
+    #[arg(long, value_parser)]
+    pub group_by: Option<GroupBy>,
+
     /// Limit tokens to those defined in the provided file extension(s)
-    #[arg(long, value_parser, value_delimiter = ',')]
+    #[arg(long, value_parser, value_delimiter = ',', global = true)]
     pub only_filetypes: Vec<LanguageExtension>,
 
     /// Limit tokens to those defined except for the provided file extension(s)
-    #[arg(long, value_parser, value_delimiter = ',')]
+    #[arg(long, value_parser, value_delimiter = ',', global = true)]
     pub except_filetypes: Vec<LanguageExtension>,
 
+    /// Limit files searched to those with the provided file extension(s)
+    #[arg(long, value_parser, value_delimiter = ',')]
+    pub search_only_filetypes: Vec<LanguageExtension>,
+
+    /// Search all files except those with the provided file extension(s)
+    #[arg(long, value_parser, value_delimiter = ',')]
+    pub search_except_filetypes: Vec<LanguageExtension>,
+
+    /// Don't search files matching the provided glob(s)
+    ///
+    /// This supports providing multiple values with a comma-delimited list
+    #[arg(long = "search-exclude", value_parser = Glob::new, value_delimiter = ',')]
+    pub search_excluded_globs: Vec<Glob>,
+
+    /// Don't search files within the provided category (or categories)
+    #[arg(long = "search-exclude-category", value_parser, value_delimiter = ',')]
+    pub search_excluded_categories: Vec<SearchCategory>,
+
+    /// Search files the project configuration excludes by default (e.g. documentation)
+    #[arg(long)]
+    pub search_all_files: bool,
+
+    /// Also search the provided git reference(s), such as long-lived release branches
+    ///
+    /// Files are read from the local repository; tokens used only on these references are
+    /// reported as medium likelihood. This supports a comma-delimited list of references.
+    #[arg(long = "git-ref", value_delimiter = ',')]
+    pub git_refs: Vec<String>,
+
+    /// Also search the provided consumer codebase(s), e.g. applications using this library
+    ///
+    /// Tokens referenced within any consumer are considered used.
+    #[arg(long = "consumer", num_args = 1.., value_delimiter = ',')]
+    pub consumers: Vec<PathBuf>,
+
+    /// Only report tokens defined in lines added or modified since the provided git reference
+    ///
+    /// Changes are computed from the common ancestor of the reference and HEAD through the
+    /// working tree, including untracked files; occurrences are still searched across the whole
+    /// codebase.
+    #[arg(long, value_name = "GIT_REF", conflicts_with = "staged", global = true)]
+    pub since: Option<String>,
+
+    /// Only report tokens defined in lines added or modified within staged changes
+    #[arg(long, global = true)]
+    pub staged: bool,
+
     /// Format output
     #[arg(long, value_parser, default_value = "standard", default_value_t)]
     pub format: Format,
@@ -74,17 +265,64 @@ pub struct Flags {
     /// Ignore files/directories matching the provided value
     ///
     /// This supports providing multiple values with a comma-delimited list
-    #[arg(long, value_delimiter = ',')]
+    #[arg(long, value_delimiter = ',', global = true)]
     pub ignore: Vec<String>,
 
     /// Return an exit status of 1 if any tokens are found
     #[arg(long)]
     pub harsh: bool,
 
+    /// Only report tokens which aren't recorded in the provided baseline file
+    ///
+    /// Baseline entries which no longer match a finding are listed so the file can be pruned.
+    #[arg(long, value_name = "FILE", global = true)]
+    pub baseline: Option<PathBuf>,
+
+    /// Record current findings to the provided baseline file
+    #[arg(long, value_name = "FILE")]
+    pub write_baseline: Option<PathBuf>,
+
     /// Override path to tags file
     #[arg(long, short = 't')]
     pub tags_file_path: Option<PathBuf>,
 
+    /// Run as if unused was started in the provided directory
+    ///
+    /// Relative paths provided to other flags (e.g. tags file, consumers) are resolved from it.
+    #[arg(short = 'C', value_name = "DIR")]
+    pub directory: Option<PathBuf>,
+
+    /// Where to list files from: walk the filesystem, or files tracked by git
+    ///
+    /// git-untracked also includes untracked files which aren't ignored. When not provided, the
+    /// project configuration's file_source is used, falling back to walking the filesystem.
+    #[arg(long, value_parser)]
+    pub files_from: Option<FilesFrom>,
+
+    /// Follow symbolic links when walking the codebase
+    #[arg(long)]
+    pub follow_symlinks: bool,
+
+    /// Skip hidden files and directories when walking the codebase
+    #[arg(long)]
+    pub no_hidden: bool,
+
+    /// Additional ignore file name(s), in .gitignore format, read from each directory walked
+    ///
+    /// .unusedignore files are always read.
+    #[arg(long = "ignore-file", value_delimiter = ',')]
+    pub ignore_files: Vec<String>,
+
+    /// Maximum depth to descend when walking the codebase
+    #[arg(long)]
+    pub max_depth: Option<usize>,
+
+    /// Only search files matching the provided glob(s); prefix a glob with ! to exclude instead
+    ///
+    /// This supports providing multiple values with a comma-delimited list
+    #[arg(long = "glob", value_delimiter = ',')]
+    pub globs: Vec<String>,
+
     #[command(subcommand)]
     pub cmd: Option<Command>,
 }
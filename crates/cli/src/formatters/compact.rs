@@ -1,24 +1,60 @@
-use super::internal::{colored::*, configuration_warnings, CliConfiguration};
+use super::internal::{
+    colored::*, configuration_warnings, stale_baseline_entries, AnalyzedToken, CliConfiguration,
+};
 use token_analysis::UsageLikelihoodStatus;
 
 pub fn format(cli_config: &CliConfiguration) {
     let token_width = cli_config.max_token_length() + 3;
     let file_width = cli_config.max_file_length() + 3;
-    for analysis in cli_config.analyses() {
-        let display_token = match analysis.likelihood_status {
-            UsageLikelihoodStatus::High => analysis.token.red(),
-            UsageLikelihoodStatus::Medium => analysis.token.yellow(),
-            UsageLikelihoodStatus::Low => analysis.token.green(),
-        };
+
+    match cli_config.groups() {
+        Some(groups) => {
+            for group in groups {
+                println!(
+                    "{} {}",
+                    group.key.white(),
+                    format!(
+                        "({} tokens, {} files, {} high)",
+                        group.token_count, group.file_count, group.high
+                    )
+                    .cyan()
+                );
+                for analysis in group.analyses() {
+                    analyzed_token(analysis, token_width, file_width);
+                }
+            }
+        }
+        None => {
+            for analysis in cli_config.analyses() {
+                analyzed_token(analysis, token_width, file_width);
+            }
+        }
+    }
+
+    for cluster in cli_config.dead_clusters() {
         println!(
-            "{:token_width$} {:file_width$} {}",
-            display_token,
-            analysis.first_path.to_string_lossy().cyan(),
-            analysis.likelihood_reason,
-            token_width = token_width,
-            file_width = file_width
+            "{} {}",
+            "dead cluster:".white(),
+            cluster.tokens.join(", ").red()
         );
     }
 
     configuration_warnings(cli_config);
+    stale_baseline_entries(cli_config);
+}
+
+fn analyzed_token(analysis: AnalyzedToken, token_width: usize, file_width: usize) {
+    let display_token = match analysis.likelihood_status {
+        UsageLikelihoodStatus::High => analysis.token.red(),
+        UsageLikelihoodStatus::Medium => analysis.token.yellow(),
+        UsageLikelihoodStatus::Low => analysis.token.green(),
+    };
+    println!(
+        "{:token_width$} {:file_width$} {}",
+        display_token,
+        analysis.first_path.to_string_lossy().cyan(),
+        analysis.likelihood_reason,
+        token_width = token_width,
+        file_width = file_width
+    );
 }
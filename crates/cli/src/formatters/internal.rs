@@ -1,7 +1,10 @@
-pub use super::super::{cli_configuration::CliConfiguration, flags::Flags};
+pub use super::super::analyzed_token::AnalyzedToken;
+pub use super::super::cli_configuration::CliConfiguration;
+pub use super::super::grouping::TokenGroup;
 pub use colored;
 use colored::*;
 use project_configuration::ProjectConfigurations;
+pub use token_analysis::age::format_timestamp;
 
 pub fn configuration_warnings(config: &CliConfiguration) {
     for (likelihood_name, conflicts) in config.low_likelihood_conflicts() {
@@ -13,7 +16,7 @@ pub fn configuration_warnings(config: &CliConfiguration) {
         for conflict in conflicts {
             let keys: Vec<_> = conflict
                 .assertions()
-                .into_iter()
+                .iter()
                 .filter_map(ProjectConfigurations::assertion_to_key)
                 .collect();
 
@@ -28,3 +31,24 @@ pub fn configuration_warnings(config: &CliConfiguration) {
         }
     }
 }
+
+pub fn stale_baseline_entries(config: &CliConfiguration) {
+    let entries = config.stale_baseline_entries();
+
+    if entries.is_empty() {
+        return;
+    }
+
+    eprintln!(
+        "Baseline entries no longer reported: ({})",
+        format!("{}", entries.len()).yellow()
+    );
+
+    for entry in entries {
+        eprintln!(
+            "   * {} {}",
+            entry.token.yellow(),
+            entry.path.display().to_string().cyan()
+        );
+    }
+}
@@ -1,7 +1,37 @@
-use super::internal::{configuration_warnings, CliConfiguration};
+use super::super::grouping::{Hotspot, TokenGroup};
+use super::internal::{configuration_warnings, stale_baseline_entries, CliConfiguration};
+use serde::Serialize;
 use serde_json;
+use token_analysis::{DeadCluster, TokenUsage};
+
+/// Version of the output's shape, incremented whenever it changes incompatibly
+const VERSION: u32 = 1;
+
+/// Every key is present whether or not findings are grouped; `groups` and `hotspots` are empty
+/// without --group-by
+#[derive(Serialize)]
+struct Output<'a> {
+    version: u32,
+    tokens: Vec<&'a TokenUsage>,
+    groups: Vec<TokenGroup<'a>>,
+    hotspots: Vec<Hotspot>,
+    dead_clusters: Vec<&'a DeadCluster>,
+}
 
 pub fn format(cli_config: &CliConfiguration) {
-    println!("{}", serde_json::to_string(&cli_config.for_json()).unwrap());
+    let groups = cli_config.groups();
+    let output = Output {
+        version: VERSION,
+        tokens: cli_config.for_json(),
+        hotspots: match groups {
+            Some(_) => cli_config.hotspots(),
+            None => vec![],
+        },
+        groups: groups.unwrap_or_default(),
+        dead_clusters: cli_config.dead_clusters(),
+    };
+
+    println!("{}", serde_json::to_string(&output).unwrap());
     configuration_warnings(cli_config);
+    stale_baseline_entries(cli_config);
 }
@@ -1,74 +1,252 @@
-use super::internal::{colored::*, configuration_warnings, CliConfiguration};
+use super::internal::{
+    colored::*, configuration_warnings, format_timestamp, stale_baseline_entries, AnalyzedToken,
+    CliConfiguration, TokenGroup,
+};
 use std::collections::HashSet;
+use std::path::PathBuf;
 use token_analysis::UsageLikelihoodStatus;
 
 pub fn format(cli_config: &CliConfiguration) {
     let mut files_list = HashSet::new();
     let mut tokens_list = HashSet::new();
 
-    for analysis in cli_config.analyses() {
-        tokens_list.insert(analysis.token.clone());
-        for v in analysis.files {
-            files_list.insert(v);
+    match cli_config.groups() {
+        Some(groups) => {
+            for group in groups {
+                println!(
+                    "{} {}",
+                    format!("== {} ==", group.key).white(),
+                    group_subtotals(&group).cyan()
+                );
+                println!();
+
+                for analysis in group.analyses() {
+                    analyzed_token(analysis, &mut tokens_list, &mut files_list);
+                }
+            }
         }
+        None => {
+            for analysis in cli_config.analyses() {
+                analyzed_token(analysis, &mut tokens_list, &mut files_list);
+            }
+        }
+    }
 
-        let display_token = match analysis.likelihood_status {
-            UsageLikelihoodStatus::High => analysis.token.red(),
-            UsageLikelihoodStatus::Medium => analysis.token.yellow(),
-            UsageLikelihoodStatus::Low => analysis.token.green(),
-        };
-        println!("{}", display_token);
-        println!("   Reason: {}", analysis.likelihood_reason.cyan());
+    dead_clusters(cli_config);
+
+    if cli_config.group_by().is_some() {
+        hotspots(cli_config);
+    }
 
+    if cli_config.display_summary() {
+        usage_summary(tokens_list.len(), files_list.len(), cli_config);
+    }
+
+    configuration_warnings(cli_config);
+    stale_baseline_entries(cli_config);
+}
+
+fn analyzed_token(
+    analysis: AnalyzedToken,
+    tokens_list: &mut HashSet<String>,
+    files_list: &mut HashSet<PathBuf>,
+) {
+    tokens_list.insert(analysis.token.clone());
+    for v in analysis.files {
+        files_list.insert(v);
+    }
+
+    let display_token = match analysis.likelihood_status {
+        UsageLikelihoodStatus::High => analysis.token.red(),
+        UsageLikelihoodStatus::Medium => analysis.token.yellow(),
+        UsageLikelihoodStatus::Low => analysis.token.green(),
+    };
+    println!("{}", display_token);
+    println!("   Reason: {}", analysis.likelihood_reason.cyan());
+    println!("   Score: {}", analysis.likelihood_score.to_string().cyan());
+    if analysis.removable_lines > 0 {
         println!(
-            "   Defined in: ({})",
-            analysis.defined_paths.len().to_string().yellow()
+            "   Removable lines: {}",
+            format!("~{}", analysis.removable_lines).cyan()
         );
-        for d in analysis.defined_paths {
+    }
+    if let Some(suppression) = &analysis.suppression {
+        println!("   Suppressed: {}", suppression.cyan());
+    }
+    if let Some(blame) = &analysis.blame {
+        let last_modified = if blame.is_committed() {
+            format!(
+                "{} by {} ({})",
+                format_timestamp(blame.timestamp),
+                blame.author,
+                blame.short_commit()
+            )
+        } else {
+            String::from("uncommitted changes")
+        };
+        println!("   Last modified: {}", last_modified.cyan());
+    }
+
+    println!(
+        "   Defined in: ({})",
+        analysis.defined_paths.len().to_string().yellow()
+    );
+    for d in analysis.defined_paths {
+        println!("   * {}", d.to_string_lossy().yellow());
+    }
+
+    let occurred_count = analysis.occurred_paths.len();
+
+    if occurred_count > 0 {
+        println!("   Found in: ({})", occurred_count.to_string().yellow());
+        for d in &analysis.occurred_paths {
             println!("   * {}", d.to_string_lossy().yellow());
         }
+    }
 
-        let occurred_count = analysis.occurred_paths.len();
+    if !analysis.refs_with_usage.is_empty() {
+        println!(
+            "   Used on: ({})",
+            analysis.refs_with_usage.len().to_string().yellow()
+        );
+        for r in &analysis.refs_with_usage {
+            println!("   * {}", r.yellow());
+        }
+    }
 
-        if occurred_count > 0 {
-            println!("   Found in: ({})", occurred_count.to_string().yellow());
-            for d in &analysis.occurred_paths {
-                println!("   * {}", d.to_string_lossy().yellow());
-            }
+    if !analysis.consumers_with_usage.is_empty() {
+        println!(
+            "   Used by: ({})",
+            analysis.consumers_with_usage.len().to_string().yellow()
+        );
+        for c in &analysis.consumers_with_usage {
+            println!("   * {}", c.to_string_lossy().yellow());
         }
+    }
 
-        println!("");
+    println!();
+}
+
+fn group_subtotals(group: &TokenGroup) -> String {
+    format!(
+        "({} tokens across {} files: {} high, {} medium, {} low)",
+        group.token_count, group.file_count, group.high, group.medium, group.low
+    )
+}
+
+fn hotspots(cli_config: &CliConfiguration) {
+    let hotspots = cli_config.hotspots();
+
+    if hotspots.is_empty() {
+        return;
     }
 
-    if cli_config.display_summary() {
-        usage_summary(tokens_list.len(), files_list.len(), &cli_config);
+    println!("{}", "== HOTSPOTS ==".white());
+    for hotspot in hotspots.iter().take(10) {
+        println!(
+            "   {} {}",
+            hotspot.directory.yellow(),
+            format!(
+                "{:.0}% ({} of {} tokens across {} files)",
+                hotspot.density * 100.0,
+                hotspot.finding_count,
+                hotspot.token_count,
+                hotspot.file_count
+            )
+            .cyan()
+        );
     }
+    println!();
+}
 
-    configuration_warnings(cli_config);
+fn dead_clusters(cli_config: &CliConfiguration) {
+    let clusters = cli_config.dead_clusters();
+
+    if clusters.is_empty() {
+        return;
+    }
+
+    println!("{}", "== DEAD CLUSTERS ==".white());
+    for cluster in clusters {
+        println!("{}", cluster.tokens.join(", ").red());
+        for chain in &cluster.chains {
+            println!("   * {}", chain.join(" -> ").yellow());
+        }
+    }
+    println!();
 }
 
 fn usage_summary(tokens_count: usize, files_count: usize, cli_config: &CliConfiguration) {
-    println!("");
+    println!();
     println!("{}", "== UNUSED SUMMARY ==".white());
     println!("   Tokens found: {}", colorize_total(tokens_count));
     println!("   Files found: {}", colorize_total(files_count));
+    println!(
+        "   Removable lines (estimated): {}",
+        colorize_total(cli_config.removable_lines())
+    );
+    let allowlisted_count = cli_config.allowlisted_count();
+    if allowlisted_count > 0 {
+        println!(
+            "   Allowlisted tokens: {}",
+            allowlisted_count.to_string().cyan()
+        );
+    }
+    let suppressed_count = cli_config.suppressed_count();
+    if suppressed_count > 0 {
+        println!(
+            "   Suppressed tokens: {}",
+            suppressed_count.to_string().cyan()
+        );
+    }
     println!(
         "   Applied language filters: {}",
-        format!("{}", cli_config.language_restriction()).cyan()
+        cli_config.language_restriction().to_string().cyan()
     );
+    println!("   File source: {}", cli_config.file_source().cyan());
+    println!(
+        "   Searched files: {}",
+        cli_config.file_restriction().cyan()
+    );
+    if let Some(changes) = cli_config.changes() {
+        println!("   Limited to changes: {}", changes.cyan());
+    }
+    if let Some(baseline) = cli_config.baseline() {
+        println!("   Baseline: {}", baseline.cyan());
+    }
+    if let Some(older_than) = cli_config.older_than() {
+        println!("   Older than: {}", older_than.cyan());
+    }
+    if let Some(group_by) = cli_config.group_by() {
+        println!("   Grouped by: {}", group_by.to_string().cyan());
+    }
     println!(
         "   Sort order: {}",
-        format!("{}", cli_config.sort_order()).cyan()
+        cli_config
+            .sort_order()
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+            .cyan()
     );
     println!(
         "   Usage likelihood: {}",
         cli_config.usage_likelihood_filter().join(", ").cyan()
     );
+    let used_only_in = cli_config.used_only_in();
+    if !used_only_in.is_empty() {
+        println!("   Used only in: {}", used_only_in.join(", ").cyan());
+    }
+    let kinds = cli_config.kinds();
+    if !kinds.is_empty() {
+        println!("   Kinds: {}", kinds.join(", ").cyan());
+    }
     println!(
         "   Configuration setting: {}",
         cli_config.configuration_name().cyan()
     );
-    println!("");
+    println!();
 }
 
 fn colorize_total(amount: usize) -> colored::ColoredString {
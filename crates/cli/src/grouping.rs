@@ -0,0 +1,162 @@
+use super::analyzed_token::AnalyzedToken;
+use super::types::GroupBy;
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use token_analysis::{TokenUsage, UsageLikelihoodStatus};
+
+/// Findings sharing a file, directory, kind, or language, with subtotals
+#[derive(Serialize)]
+pub struct TokenGroup<'a> {
+    pub key: String,
+    pub token_count: usize,
+    pub file_count: usize,
+    pub high: usize,
+    pub medium: usize,
+    pub low: usize,
+    /// Serialized as token names; each token's details are listed once, outside any group
+    #[serde(serialize_with = "token_names")]
+    pub tokens: Vec<&'a TokenUsage>,
+}
+
+impl<'a> TokenGroup<'a> {
+    fn new(key: String, tokens: Vec<&'a TokenUsage>) -> Self {
+        let count = |status| {
+            tokens
+                .iter()
+                .filter(|t| t.usage_likelihood.status == status)
+                .count()
+        };
+
+        TokenGroup {
+            token_count: tokens.len(),
+            file_count: tokens
+                .iter()
+                .map(|t| t.result.token.first_path())
+                .collect::<HashSet<_>>()
+                .len(),
+            high: count(UsageLikelihoodStatus::High),
+            medium: count(UsageLikelihoodStatus::Medium),
+            low: count(UsageLikelihoodStatus::Low),
+            key,
+            tokens,
+        }
+    }
+
+    pub fn analyses(&self) -> Vec<AnalyzedToken> {
+        self.tokens.iter().map(|t| (*t).into()).collect()
+    }
+}
+
+/// A directory ranked by its findings, weighted by the share of its defined tokens reported
+#[derive(Serialize)]
+pub struct Hotspot {
+    pub directory: String,
+    pub finding_count: usize,
+    pub file_count: usize,
+    pub token_count: usize,
+    pub density: f64,
+}
+
+/// Group findings by key, largest groups first, preserving the order of findings within each
+pub fn group<'a>(findings: &[&'a TokenUsage], group_by: GroupBy) -> Vec<TokenGroup<'a>> {
+    let mut groups: BTreeMap<String, Vec<&'a TokenUsage>> = BTreeMap::new();
+
+    for finding in findings {
+        groups
+            .entry(group_key(finding, group_by))
+            .or_default()
+            .push(finding);
+    }
+
+    let mut groups: Vec<_> = groups
+        .into_iter()
+        .map(|(key, tokens)| TokenGroup::new(key, tokens))
+        .collect();
+    groups.sort_by_key(|g| std::cmp::Reverse(g.token_count));
+    groups
+}
+
+/// Directories containing findings, ranked by their finding count weighted by density
+///
+/// Density alone would rank a directory with a single token, which is reported, above one where
+/// half of two hundred tokens are reported.
+pub fn hotspots(findings: &[&TokenUsage], usages: &[TokenUsage]) -> Vec<Hotspot> {
+    let mut token_counts: HashMap<String, usize> = HashMap::new();
+    for usage in usages {
+        *token_counts.entry(usage.directory()).or_default() += 1;
+    }
+
+    let mut findings_by_directory: BTreeMap<String, Vec<&TokenUsage>> = BTreeMap::new();
+    for finding in findings {
+        findings_by_directory
+            .entry(finding.directory())
+            .or_default()
+            .push(finding);
+    }
+
+    let mut hotspots: Vec<_> = findings_by_directory
+        .into_iter()
+        .map(|(directory, findings)| {
+            let token_count = token_counts
+                .get(&directory)
+                .copied()
+                .unwrap_or_default()
+                .max(findings.len());
+
+            Hotspot {
+                finding_count: findings.len(),
+                file_count: findings
+                    .iter()
+                    .map(|t| t.result.token.first_path())
+                    .collect::<HashSet<_>>()
+                    .len(),
+                token_count,
+                density: findings.len() as f64 / token_count as f64,
+                directory,
+            }
+        })
+        .collect();
+
+    hotspots.sort_by(|a, b| {
+        b.weight()
+            .partial_cmp(&a.weight())
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| b.finding_count.cmp(&a.finding_count))
+            .then_with(|| a.directory.cmp(&b.directory))
+    });
+    hotspots
+}
+
+impl Hotspot {
+    fn weight(&self) -> f64 {
+        self.finding_count as f64 * self.density
+    }
+}
+
+fn token_names<S>(tokens: &[&TokenUsage], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.collect_seq(tokens.iter().map(|t| &t.result.token.token))
+}
+
+fn group_key(usage: &TokenUsage, group_by: GroupBy) -> String {
+    let primary = usage.result.token.primary_definition();
+
+    match group_by {
+        GroupBy::File => usage
+            .result
+            .token
+            .first_path()
+            .to_string_lossy()
+            .to_string(),
+        GroupBy::Directory => usage.directory(),
+        GroupBy::Kind => primary
+            .map(|d| d.kind.to_string())
+            .unwrap_or_else(|| String::from("unknown")),
+        GroupBy::Language => primary
+            .and_then(|d| d.language)
+            .map(|l| l.to_string())
+            .unwrap_or_else(|| String::from("unknown")),
+    }
+}
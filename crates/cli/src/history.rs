@@ -0,0 +1,196 @@
+use super::error_message;
+use super::flags::{SnapshotFlags, TrendFlags};
+use super::types::Format;
+use analysis::{AnalysisReport, WalkOptions};
+use colored::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::process;
+use token_analysis::age::{self, format_timestamp};
+use token_analysis::{CountChange, History, Snapshot, SnapshotDiff};
+
+#[derive(Serialize)]
+struct SnapshotSummary<'a> {
+    number: usize,
+    recorded_at: u64,
+    label: &'a Option<String>,
+    total: usize,
+    likelihoods: &'a BTreeMap<String, usize>,
+    languages: &'a BTreeMap<String, usize>,
+    directories: &'a BTreeMap<String, usize>,
+}
+
+#[derive(Serialize)]
+struct Trend<'a> {
+    snapshots: Vec<SnapshotSummary<'a>>,
+    changes: Option<Changes>,
+}
+
+#[derive(Serialize)]
+struct Changes {
+    from: usize,
+    to: usize,
+    #[serde(flatten)]
+    diff: SnapshotDiff,
+}
+
+pub fn snapshot(flags: &SnapshotFlags, report: &AnalysisReport, walk_options: &WalkOptions) {
+    let path = walk_options.root.join(&flags.history);
+    let snapshot = Snapshot::new(&report.findings(), age::now(), flags.label.clone());
+
+    if let Err(e) = History::append(&path, &snapshot) {
+        error_message::failed_history(e);
+        process::exit(1)
+    }
+
+    println!(
+        "Recorded {} findings to {}",
+        snapshot.total.to_string().cyan(),
+        flags.history.display().to_string().cyan()
+    );
+}
+
+pub fn trend(flags: &TrendFlags, format: &Format, walk_options: &WalkOptions) {
+    let history = match History::load(&walk_options.root.join(&flags.history)) {
+        Ok(history) => history,
+        Err(e) => {
+            error_message::failed_history(e);
+            process::exit(1)
+        }
+    };
+    let snapshots = history.snapshots();
+    let count = snapshots.len();
+    let to = flags.to.unwrap_or(count);
+    let from = flags.from.unwrap_or_else(|| to.saturating_sub(1).max(1));
+
+    for number in [flags.from, flags.to].iter().flatten() {
+        if *number == 0 || *number > count {
+            eprintln!(
+                "{}",
+                format!("Snapshot {} does not exist; there are {}", number, count).red()
+            );
+            process::exit(1)
+        }
+    }
+
+    let trend = Trend {
+        snapshots: snapshots
+            .iter()
+            .enumerate()
+            .map(|(idx, s)| SnapshotSummary {
+                number: idx + 1,
+                recorded_at: s.recorded_at,
+                label: &s.label,
+                total: s.total,
+                likelihoods: &s.likelihoods,
+                languages: &s.languages,
+                directories: &s.directories,
+            })
+            .collect(),
+        changes: Some(from)
+            .filter(|from| count > 1 && *from != to)
+            .map(|from| Changes {
+                from,
+                to,
+                diff: snapshots[from - 1].diff(&snapshots[to - 1]),
+            }),
+    };
+
+    match format {
+        Format::Json => println!("{}", serde_json::to_string(&trend).unwrap()),
+        _ => render_table(&trend),
+    }
+}
+
+fn render_table(trend: &Trend) {
+    if trend.snapshots.is_empty() {
+        println!("No snapshots recorded; run `unused snapshot` to record one");
+        return;
+    }
+
+    println!("{}", "== TREND ==".white());
+    println!(
+        "   {:>3}  {:<20}  {:<12}  {:>6}  {:>6}  {:>6}  {:>6}  {:>6}",
+        "#", "Recorded", "Label", "Total", "High", "Medium", "Low", "Change"
+    );
+
+    let mut previous: Option<usize> = None;
+    for snapshot in &trend.snapshots {
+        let likelihood = |status: &str| snapshot.likelihoods.get(status).copied().unwrap_or(0);
+        let change = previous
+            .map(|p| {
+                colorize_delta(
+                    CountChange {
+                        before: p,
+                        after: snapshot.total,
+                    }
+                    .delta(),
+                )
+            })
+            .unwrap_or_else(|| "".normal());
+
+        println!(
+            "   {:>3}  {:<20}  {:<12}  {:>6}  {:>6}  {:>6}  {:>6}  {:>6}",
+            snapshot.number,
+            format_timestamp(snapshot.recorded_at),
+            snapshot.label.as_deref().unwrap_or(""),
+            snapshot.total,
+            likelihood("high"),
+            likelihood("medium"),
+            likelihood("low"),
+            change
+        );
+        previous = Some(snapshot.total);
+    }
+    println!();
+
+    if let Some(changes) = &trend.changes {
+        println!(
+            "{}",
+            format!("== CHANGES ({} -> {}) ==", changes.from, changes.to).white()
+        );
+        render_count_changes("Total", &[("findings".to_string(), changes.diff.total)]);
+        render_count_changes("Likelihoods", &to_vec(&changes.diff.likelihoods));
+        render_count_changes("Languages", &to_vec(&changes.diff.languages));
+        render_count_changes("Directories", &to_vec(&changes.diff.directories));
+        render_tokens("Added", &changes.diff.added);
+        render_tokens("Removed", &changes.diff.removed);
+        println!();
+    }
+}
+
+fn to_vec(changes: &BTreeMap<String, CountChange>) -> Vec<(String, CountChange)> {
+    changes.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+}
+
+fn render_count_changes(title: &str, changes: &[(String, CountChange)]) {
+    if changes.is_empty() {
+        return;
+    }
+
+    println!("   {}:", title);
+    for (key, change) in changes {
+        println!(
+            "   * {}: {} -> {} ({})",
+            key.yellow(),
+            change.before,
+            change.after,
+            colorize_delta(change.delta())
+        );
+    }
+}
+
+fn render_tokens(title: &str, tokens: &[String]) {
+    println!("   {}: ({})", title, tokens.len().to_string().yellow());
+    for token in tokens {
+        println!("   * {}", token.yellow());
+    }
+}
+
+fn colorize_delta(delta: i64) -> ColoredString {
+    match delta {
+        d if d > 0 => format!("+{}", d).red(),
+        d if d < 0 => d.to_string().green(),
+        _ => "0".normal(),
+    }
+}
@@ -0,0 +1,75 @@
+use super::error_message;
+use super::flags::IgnoreFlags;
+use analysis::{Allowlist, AllowlistEntry, Token, WalkOptions, ALLOWLIST_FILE_NAME};
+use colored::*;
+use read_ctags::TagsReader;
+use std::path::Path;
+use std::process::{self, Command};
+
+pub fn run(flags: &IgnoreFlags, tags_reader: &TagsReader, walk_options: &WalkOptions) {
+    let path = walk_options.root.join(ALLOWLIST_FILE_NAME);
+    let mut allowlist = match Allowlist::load(&path) {
+        Ok(allowlist) => allowlist,
+        Err(e) => {
+            error_message::failed_allowlist(e);
+            process::exit(1)
+        }
+    };
+
+    if flags.prune {
+        let tokens = match Token::all(tags_reader) {
+            Ok((_, tokens)) => tokens,
+            Err(e) => {
+                error_message::failed_token_parse(e);
+                process::exit(1)
+            }
+        };
+
+        let removed = allowlist.prune(&tokens);
+        println!(
+            "Removed {} entries from {}",
+            removed.len().to_string().cyan(),
+            ALLOWLIST_FILE_NAME.cyan()
+        );
+        for entry in removed {
+            println!("   * {}", entry.token.yellow());
+        }
+
+        if let Err(e) = allowlist.write(&path) {
+            error_message::failed_allowlist(e);
+            process::exit(1)
+        }
+    } else if let (Some(token), Some(reason)) = (&flags.token, &flags.reason) {
+        let entry = AllowlistEntry {
+            token: token.to_string(),
+            path: flags.path.clone(),
+            kind: flags.kind,
+            reason: reason.to_string(),
+            author: flags
+                .author
+                .clone()
+                .or_else(|| git_user_name(&walk_options.root)),
+        };
+
+        if let Err(e) = allowlist.append(&path, entry) {
+            error_message::failed_allowlist(e);
+            process::exit(1)
+        }
+        println!("Added {} to {}", token.cyan(), ALLOWLIST_FILE_NAME.cyan());
+    }
+}
+
+/// The git user configured for the repository containing the root
+pub(crate) fn git_user_name(root: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .current_dir(root)
+        .args(["config", "user.name"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let name = String::from_utf8(output.stdout).ok()?.trim().to_string();
+
+    Some(name).filter(|n| !n.is_empty())
+}
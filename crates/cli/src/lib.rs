@@ -2,20 +2,27 @@ mod analyzed_token;
 mod cli_configuration;
 mod doctor;
 mod error_message;
+mod fix;
 mod flags;
 mod formatters;
+mod grouping;
+mod history;
+mod ignore;
 mod project_configurations_loader;
+mod triage;
 mod types;
 
+use analysis::{
+    Allowlist, AnalysisError, AnalysisReport, Baseline, WalkOptions, ALLOWLIST_FILE_NAME,
+};
 use clap::Parser;
-use cli_configuration::CliConfiguration;
+use cli_configuration::{build_analysis, build_walk_options, CliConfiguration};
 use colored::*;
 use doctor::Doctor;
 use flags::Flags;
 use project_configuration::ProjectConfigurations;
 use read_ctags::TagsReader;
 use std::process;
-use token_search::Token;
 use types::Format;
 
 pub fn run() {
@@ -29,26 +36,109 @@ pub fn run() {
         control::set_override(false);
     }
 
-    let mut tags_reader = TagsReader::default();
+    let walk_options = build_walk_options(&flags);
+    let mut tags_reader = TagsReader::within(&walk_options.root);
     if let Some(tags_file_path) = &flags.tags_file_path {
-        tags_reader.for_tags_file(tags_file_path.to_path_buf());
+        tags_reader.for_tags_file(walk_options.root.join(tags_file_path));
     }
 
     match flags.cmd {
-        Some(flags::Command::Doctor) => Doctor::new(&tags_reader).render(),
+        Some(flags::Command::Doctor) => Doctor::new(&tags_reader, &walk_options).render(),
         Some(flags::Command::DefaultYaml) => println!("{}", ProjectConfigurations::default_yaml()),
-        None => match Token::all(&tags_reader) {
-            Ok((_, results)) => {
-                let configuration = CliConfiguration::new(&flags, results);
-                configuration.render();
-                if flags.harsh && !configuration.analyses().is_empty() {
-                    process::exit(1);
+        Some(flags::Command::Ignore(ref ignore_flags)) => {
+            ignore::run(ignore_flags, &tags_reader, &walk_options)
+        }
+        Some(flags::Command::Snapshot(ref snapshot_flags)) => {
+            let report = run_analysis(&flags, tags_reader, &walk_options);
+            history::snapshot(snapshot_flags, &report, &walk_options)
+        }
+        Some(flags::Command::Trend(ref trend_flags)) => {
+            history::trend(trend_flags, &flags.format, &walk_options)
+        }
+        Some(flags::Command::Fix(ref fix_flags)) => {
+            let report = run_analysis(&flags, tags_reader, &walk_options);
+            fix::run(fix_flags, &report, &walk_options)
+        }
+        Some(flags::Command::Triage(ref triage_flags)) => {
+            let report = run_analysis(&flags, tags_reader, &walk_options);
+            let analyses = CliConfiguration::new(&flags, report).analyses();
+            triage::run(triage_flags, analyses, &walk_options)
+        }
+        None => {
+            let report = run_analysis(&flags, tags_reader, &walk_options);
+            let configuration = CliConfiguration::new(&flags, report);
+            if let Some(path) = &flags.write_baseline {
+                match configuration.write_baseline(&walk_options.root.join(path)) {
+                    Ok(count) => eprintln!(
+                        "Wrote {} findings to baseline {}",
+                        count.to_string().cyan(),
+                        path.display().to_string().cyan()
+                    ),
+                    Err(e) => {
+                        error_message::failed_baseline(e);
+                        process::exit(1)
+                    }
                 }
             }
-            Err(e) => {
-                error_message::failed_token_parse(e);
-                process::exit(1)
+            configuration.render();
+            if flags.harsh && !configuration.analyses().is_empty() {
+                process::exit(1);
             }
-        },
+        }
+    }
+}
+
+fn run_analysis(
+    flags: &Flags,
+    tags_reader: TagsReader,
+    walk_options: &WalkOptions,
+) -> AnalysisReport {
+    match build_analysis(
+        flags,
+        tags_reader,
+        load_baseline(flags, walk_options),
+        load_allowlist(walk_options),
+    )
+    .run()
+    {
+        Ok(report) => report,
+        Err(AnalysisError::Tags(e)) => {
+            error_message::failed_token_parse(e);
+            process::exit(1)
+        }
+        Err(AnalysisError::GitRef(e)) => {
+            error_message::failed_git_ref(e);
+            process::exit(1)
+        }
+        Err(AnalysisError::Files(e)) => {
+            error_message::failed_walk(e);
+            process::exit(1)
+        }
+        Err(AnalysisError::Blame(e)) => {
+            error_message::failed_blame(e);
+            process::exit(1)
+        }
+    }
+}
+
+fn load_baseline(flags: &Flags, walk_options: &WalkOptions) -> Option<Baseline> {
+    let path = walk_options.root.join(flags.baseline.as_ref()?);
+
+    match Baseline::load(&path) {
+        Ok(baseline) => Some(baseline),
+        Err(e) => {
+            error_message::failed_baseline(e);
+            process::exit(1)
+        }
+    }
+}
+
+fn load_allowlist(walk_options: &WalkOptions) -> Allowlist {
+    match Allowlist::load(&walk_options.root.join(ALLOWLIST_FILE_NAME)) {
+        Ok(allowlist) => allowlist,
+        Err(e) => {
+            error_message::failed_allowlist(e);
+            process::exit(1)
+        }
     }
 }
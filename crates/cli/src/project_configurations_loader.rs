@@ -1,10 +1,9 @@
-use dirs_next;
-use project_configuration::ProjectConfigurations;
+use project_configuration::{ConfigurationError, ProjectConfigurations};
 use std::fs;
 use std::io;
 use std::path::Path;
 
-pub fn load_and_parse_config() -> ProjectConfigurations {
+pub fn load_and_parse_config() -> Result<ProjectConfigurations, ConfigurationError> {
     let contents = file_path_in_home_dir(".config/unused/unused.yml")
         .and_then(|path| read_file(&path).ok())
         .unwrap_or(ProjectConfigurations::default_yaml());
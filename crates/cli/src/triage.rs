@@ -0,0 +1,161 @@
+mod app;
+mod ui;
+
+use super::analyzed_token::AnalyzedToken;
+use super::error_message;
+use super::flags::TriageFlags;
+use super::triage::app::{Mode, SaveError, TriageApp};
+use analysis::{Allowlist, Triage, TriageDecision, WalkOptions, ALLOWLIST_FILE_NAME};
+use colored::*;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::DefaultTerminal;
+use std::io::{self, IsTerminal};
+use std::process;
+
+pub fn run(flags: &TriageFlags, findings: Vec<AnalyzedToken>, walk_options: &WalkOptions) {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        error_message::failed_terminal(io::Error::other("STDIN and STDOUT must be a terminal"));
+        process::exit(1)
+    }
+
+    let triage_path = walk_options.root.join(&flags.state);
+    let triage = match Triage::load(&triage_path) {
+        Ok(triage) => triage,
+        Err(e) => {
+            error_message::failed_triage(e);
+            process::exit(1)
+        }
+    };
+    let allowlist_path = walk_options.root.join(ALLOWLIST_FILE_NAME);
+    let allowlist = match Allowlist::load(&allowlist_path) {
+        Ok(allowlist) => allowlist,
+        Err(e) => {
+            error_message::failed_allowlist(e);
+            process::exit(1)
+        }
+    };
+
+    if findings.is_empty() {
+        println!("No findings to triage");
+        return;
+    }
+
+    let mut app = TriageApp::new(
+        findings,
+        triage,
+        triage_path,
+        allowlist,
+        allowlist_path,
+        walk_options.root.to_path_buf(),
+    );
+    let mut terminal = match ratatui::try_init() {
+        Ok(terminal) => terminal,
+        Err(e) => {
+            error_message::failed_terminal(e);
+            process::exit(1)
+        }
+    };
+    let result = event_loop(&mut terminal, &mut app);
+    ratatui::restore();
+
+    match result {
+        Ok(Ok(())) => summary(&app),
+        Ok(Err(SaveError::Triage(e))) => {
+            error_message::failed_triage(e);
+            process::exit(1)
+        }
+        Ok(Err(SaveError::Allowlist(e))) => {
+            error_message::failed_allowlist(e);
+            process::exit(1)
+        }
+        Err(e) => {
+            error_message::failed_terminal(e);
+            process::exit(1)
+        }
+    }
+}
+
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    app: &mut TriageApp,
+) -> io::Result<Result<(), SaveError>> {
+    loop {
+        terminal.draw(|frame| ui::draw(frame, app))?;
+
+        let key = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+        app.status = None;
+
+        let saved = match &mut app.mode {
+            Mode::Reason(reason) => match key.code {
+                KeyCode::Enter if !reason.trim().is_empty() => {
+                    let reason = reason.trim().to_string();
+                    app.mode = Mode::Browse;
+                    app.ignore(&reason)
+                }
+                KeyCode::Esc => {
+                    app.mode = Mode::Browse;
+                    Ok(())
+                }
+                KeyCode::Backspace => {
+                    reason.pop();
+                    Ok(())
+                }
+                KeyCode::Char(c) => {
+                    reason.push(c);
+                    Ok(())
+                }
+                _ => Ok(()),
+            },
+            Mode::Browse => match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(Ok(())),
+                KeyCode::Char('k') | KeyCode::Up => {
+                    app.select_previous();
+                    Ok(())
+                }
+                KeyCode::Char('j') | KeyCode::Down => {
+                    app.select_next();
+                    Ok(())
+                }
+                KeyCode::Char('n') => {
+                    app.select_next_undecided();
+                    Ok(())
+                }
+                KeyCode::Char('d') => app.decide(Some(TriageDecision::DeleteLater)),
+                KeyCode::Char('s') => app.decide(Some(TriageDecision::Skip)),
+                KeyCode::Char('u') => app.decide(None),
+                KeyCode::Char('i') => {
+                    app.mode = Mode::Reason(String::new());
+                    Ok(())
+                }
+                _ => Ok(()),
+            },
+        };
+
+        if let Err(e) = saved {
+            return Ok(Err(e));
+        }
+    }
+}
+
+fn summary(app: &TriageApp) {
+    println!(
+        "Triaged {} of {} findings",
+        app.decided_count().to_string().cyan(),
+        app.findings.len().to_string().cyan()
+    );
+    println!(
+        "   Delete later: {}",
+        app.count(TriageDecision::DeleteLater).to_string().cyan()
+    );
+    println!(
+        "   Ignored: {}",
+        app.count(TriageDecision::Ignore).to_string().cyan()
+    );
+    println!(
+        "   Skipped: {}",
+        app.count(TriageDecision::Skip).to_string().cyan()
+    );
+}
@@ -0,0 +1,274 @@
+use super::super::analyzed_token::AnalyzedToken;
+use super::super::ignore::git_user_name;
+use analysis::{Allowlist, AllowlistEntry, AllowlistError, Triage, TriageDecision, TriageError};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Whether keys navigate findings or edit the reason for ignoring one
+pub enum Mode {
+    Browse,
+    Reason(String),
+}
+
+pub enum SaveError {
+    Triage(TriageError),
+    Allowlist(AllowlistError),
+}
+
+pub struct TriageApp {
+    pub findings: Vec<AnalyzedToken>,
+    pub selected: usize,
+    pub mode: Mode,
+    pub status: Option<String>,
+    pub triage: Triage,
+    triage_path: PathBuf,
+    allowlist: Allowlist,
+    allowlist_path: PathBuf,
+    root: PathBuf,
+    file_lines: HashMap<PathBuf, Option<Vec<String>>>,
+}
+
+impl TriageApp {
+    pub fn new(
+        findings: Vec<AnalyzedToken>,
+        triage: Triage,
+        triage_path: PathBuf,
+        allowlist: Allowlist,
+        allowlist_path: PathBuf,
+        root: PathBuf,
+    ) -> Self {
+        let mut app = TriageApp {
+            findings,
+            selected: 0,
+            mode: Mode::Browse,
+            status: None,
+            triage,
+            triage_path,
+            allowlist,
+            allowlist_path,
+            root,
+            file_lines: HashMap::new(),
+        };
+        app.selected = app.next_undecided_from(0).unwrap_or(0);
+        app
+    }
+
+    pub fn current(&self) -> Option<&AnalyzedToken> {
+        self.findings.get(self.selected)
+    }
+
+    pub fn decision(&self, finding: &AnalyzedToken) -> Option<TriageDecision> {
+        self.triage.decision(&finding.token, &finding.first_path)
+    }
+
+    pub fn decided_count(&self) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| self.decision(f).is_some())
+            .count()
+    }
+
+    pub fn count(&self, decision: TriageDecision) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| self.decision(f) == Some(decision))
+            .count()
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.findings.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Move to the next undecided finding, wrapping around to the start
+    pub fn select_next_undecided(&mut self) {
+        match self.next_undecided_from(self.selected + 1) {
+            Some(index) => self.selected = index,
+            None => self.status = Some("Every finding has a decision".to_string()),
+        }
+    }
+
+    /// Record a decision for the current finding and move to the next undecided one
+    pub fn decide(&mut self, decision: Option<TriageDecision>) -> Result<(), SaveError> {
+        let finding = match self.findings.get(self.selected) {
+            Some(finding) => finding,
+            None => return Ok(()),
+        };
+        let token = finding.token.to_string();
+        let previous = self.decision(finding);
+
+        self.triage.decide(&token, &finding.first_path, decision);
+        self.triage
+            .write(&self.triage_path)
+            .map_err(SaveError::Triage)?;
+
+        self.status = Some(match decision {
+            Some(decision) => format!("Marked {} as {}", token, decision),
+            None if previous == Some(TriageDecision::Ignore) => format!(
+                "Cleared decision for {}; its allowlist entry was kept",
+                token
+            ),
+            None => format!("Cleared decision for {}", token),
+        });
+        if decision.is_some() {
+            if let Some(index) = self.next_undecided_from(self.selected + 1) {
+                self.selected = index;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add the current finding to the allowlist with the provided reason, then mark it ignored
+    pub fn ignore(&mut self, reason: &str) -> Result<(), SaveError> {
+        let finding = match self.findings.get(self.selected) {
+            Some(finding) => finding,
+            None => return Ok(()),
+        };
+
+        let entry = AllowlistEntry {
+            token: finding.token.to_string(),
+            path: Some(finding.first_path.to_path_buf()),
+            kind: None,
+            reason: reason.to_string(),
+            author: git_user_name(&self.root),
+        };
+        self.allowlist
+            .append(&self.allowlist_path, entry)
+            .map_err(SaveError::Allowlist)?;
+
+        self.decide(Some(TriageDecision::Ignore))
+    }
+
+    /// Lines of a file relative to the project root, read once and cached
+    pub fn file_lines(&mut self, path: &Path) -> Option<&[String]> {
+        let root = &self.root;
+        self.file_lines
+            .entry(path.to_path_buf())
+            .or_insert_with(|| {
+                fs::read_to_string(root.join(path))
+                    .ok()
+                    .map(|contents| contents.lines().map(|l| l.to_string()).collect())
+            })
+            .as_deref()
+    }
+
+    fn next_undecided_from(&self, start: usize) -> Option<usize> {
+        let len = self.findings.len();
+
+        (0..len)
+            .map(|offset| (start + offset) % len)
+            .find(|&index| self.decision(&self.findings[index]).is_none())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeMap, HashSet};
+    use tempfile::TempDir;
+    use token_analysis::UsageLikelihoodStatus;
+
+    fn build_finding(token: &str) -> AnalyzedToken {
+        AnalyzedToken {
+            token: token.to_string(),
+            first_path: PathBuf::from("app/models/person.rb"),
+            likelihood_status: UsageLikelihoodStatus::High,
+            likelihood_reason: String::from("Only one occurrence exists"),
+            likelihood_score: 100,
+            files: vec![],
+            defined_paths: vec![PathBuf::from("app/models/person.rb")],
+            occurred_paths: HashSet::new(),
+            refs_with_usage: vec![],
+            consumers_with_usage: vec![],
+            suppression: None,
+            blame: None,
+            removable_lines: 0,
+            definition_spans: vec![],
+            occurrence_lines: BTreeMap::new(),
+        }
+    }
+
+    fn build_app(root: &TempDir, triage: Triage) -> TriageApp {
+        TriageApp::new(
+            ["name", "age", "email"]
+                .iter()
+                .map(|t| build_finding(t))
+                .collect(),
+            triage,
+            root.path().join(".unused-triage.json"),
+            Allowlist::default(),
+            root.path().join(".unused-allowlist"),
+            root.path().to_path_buf(),
+        )
+    }
+
+    #[test]
+    fn deciding_saves_and_moves_to_the_next_undecided_finding() {
+        let root = TempDir::new().unwrap();
+        let mut app = build_app(&root, Triage::default());
+
+        assert!(app.decide(Some(TriageDecision::DeleteLater)).is_ok());
+        assert_eq!(app.selected, 1);
+        assert_eq!(app.count(TriageDecision::DeleteLater), 1);
+
+        assert!(app.decide(None).is_ok());
+        assert_eq!(app.selected, 1);
+        assert_eq!(app.status.as_deref(), Some("Cleared decision for age"));
+
+        let saved = Triage::load(&root.path().join(".unused-triage.json")).unwrap();
+        assert_eq!(
+            saved.decision("name", Path::new("app/models/person.rb")),
+            Some(TriageDecision::DeleteLater)
+        );
+        assert_eq!(
+            saved.decision("age", Path::new("app/models/person.rb")),
+            None
+        );
+    }
+
+    #[test]
+    fn ignoring_adds_to_the_allowlist() {
+        let root = TempDir::new().unwrap();
+        let mut app = build_app(&root, Triage::default());
+        app.selected = 2;
+
+        assert!(app.ignore("called via reflection").is_ok());
+        assert_eq!(app.decision(&app.findings[2]), Some(TriageDecision::Ignore));
+        assert_eq!(app.selected, 0);
+
+        let allowlist = Allowlist::load(&root.path().join(".unused-allowlist")).unwrap();
+        assert_eq!(allowlist.entries().len(), 1);
+        assert_eq!(allowlist.entries()[0].token, "email");
+        assert_eq!(allowlist.entries()[0].reason, "called via reflection");
+        assert_eq!(
+            allowlist.entries()[0].path.as_deref(),
+            Some(Path::new("app/models/person.rb"))
+        );
+    }
+
+    #[test]
+    fn finds_the_next_undecided_finding_wrapping_around() {
+        let root = TempDir::new().unwrap();
+        let mut triage = Triage::default();
+        let person = Path::new("app/models/person.rb");
+        triage.decide("name", person, Some(TriageDecision::Skip));
+        triage.decide("email", person, Some(TriageDecision::DeleteLater));
+        let mut app = build_app(&root, triage);
+
+        assert_eq!(app.selected, 1);
+        assert_eq!(app.next_undecided_from(2), Some(1));
+
+        app.triage
+            .decide("age", person, Some(TriageDecision::Ignore));
+        app.select_next_undecided();
+        assert_eq!(app.selected, 1);
+        assert_eq!(app.status.as_deref(), Some("Every finding has a decision"));
+    }
+}
@@ -0,0 +1,256 @@
+use super::app::{Mode, TriageApp};
+use analysis::TriageDecision;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+use std::path::{Path, PathBuf};
+use token_analysis::age::format_timestamp;
+use token_analysis::UsageLikelihoodStatus;
+
+/// Lines shown from each definition before truncating
+const DEFINITION_PREVIEW_LINES: usize = 12;
+
+/// Occurrence lines shown before truncating
+const OCCURRENCE_PREVIEW_LINES: usize = 20;
+
+pub fn draw(frame: &mut Frame, app: &mut TriageApp) {
+    let [header, body, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(0),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [list, detail] =
+        Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)]).areas(body);
+    let [details, preview] =
+        Layout::vertical([Constraint::Length(8), Constraint::Min(0)]).areas(detail);
+
+    frame.render_widget(Paragraph::new(progress(app)), header);
+    findings(frame, app, list);
+    frame.render_widget(
+        Paragraph::new(finding_details(app)).block(bordered("Finding")),
+        details,
+    );
+    let preview_lines = finding_preview(app);
+    frame.render_widget(
+        Paragraph::new(preview_lines).block(bordered("Preview")),
+        preview,
+    );
+    frame.render_widget(Paragraph::new(help(app)), footer);
+}
+
+fn bordered(title: &str) -> Block<'_> {
+    Block::default().borders(Borders::ALL).title(title)
+}
+
+fn progress(app: &TriageApp) -> Line<'static> {
+    Line::from(vec![
+        Span::styled("unused triage", Style::new().bold()),
+        Span::raw(format!(
+            "  {} of {} decided  ",
+            app.decided_count(),
+            app.findings.len()
+        )),
+        Span::styled(
+            format!(
+                "delete later: {}  ignore: {}  skip: {}",
+                app.count(TriageDecision::DeleteLater),
+                app.count(TriageDecision::Ignore),
+                app.count(TriageDecision::Skip)
+            ),
+            Style::new().cyan(),
+        ),
+    ])
+}
+
+fn findings(frame: &mut Frame, app: &TriageApp, area: Rect) {
+    let items: Vec<ListItem> = app
+        .findings
+        .iter()
+        .map(|finding| {
+            let color = match finding.likelihood_status {
+                UsageLikelihoodStatus::High => Color::Red,
+                UsageLikelihoodStatus::Medium => Color::Yellow,
+                UsageLikelihoodStatus::Low => Color::Green,
+            };
+
+            ListItem::new(Line::from(vec![
+                marker(app.decision(finding)),
+                Span::raw(" "),
+                Span::styled(finding.token.to_string(), Style::new().fg(color)),
+            ]))
+        })
+        .collect();
+    let mut state = ListState::default().with_selected(Some(app.selected));
+
+    frame.render_stateful_widget(
+        List::new(items)
+            .block(bordered("Findings"))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+        area,
+        &mut state,
+    );
+}
+
+fn marker(decision: Option<TriageDecision>) -> Span<'static> {
+    match decision {
+        Some(TriageDecision::DeleteLater) => Span::styled("[D]", Style::new().red()),
+        Some(TriageDecision::Ignore) => Span::styled("[I]", Style::new().green()),
+        Some(TriageDecision::Skip) => Span::styled("[S]", Style::new().yellow()),
+        None => Span::styled("[ ]", Style::new().dark_gray()),
+    }
+}
+
+fn finding_details(app: &TriageApp) -> Vec<Line<'static>> {
+    let finding = match app.current() {
+        Some(finding) => finding,
+        None => return vec![],
+    };
+    let mut lines = vec![
+        field("Token", finding.token.to_string()),
+        field("Reason", finding.likelihood_reason.to_string()),
+        field("Score", finding.likelihood_score.to_string()),
+        field(
+            "Defined in",
+            finding
+                .defined_paths
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+    ];
+    if finding.removable_lines > 0 {
+        lines.push(field(
+            "Removable lines",
+            format!("~{}", finding.removable_lines),
+        ));
+    }
+    if let Some(blame) = &finding.blame {
+        lines.push(field(
+            "Last modified",
+            if blame.is_committed() {
+                format!(
+                    "{} by {} ({})",
+                    format_timestamp(blame.timestamp),
+                    blame.author,
+                    blame.short_commit()
+                )
+            } else {
+                "uncommitted changes".to_string()
+            },
+        ));
+    }
+
+    lines
+}
+
+fn field(name: &str, value: String) -> Line<'static> {
+    Line::from(vec![
+        Span::raw(format!("{}: ", name)),
+        Span::styled(value, Style::new().cyan()),
+    ])
+}
+
+/// Definition lines, followed by lines where the token occurs
+fn finding_preview(app: &mut TriageApp) -> Vec<Line<'static>> {
+    let finding = match app.current() {
+        Some(finding) => finding,
+        None => return vec![],
+    };
+    let spans = finding.definition_spans.clone();
+    let occurrences: Vec<(PathBuf, Vec<usize>)> = finding
+        .occurrence_lines
+        .iter()
+        .map(|(path, lines)| (path.to_path_buf(), lines.clone()))
+        .collect();
+    let mut lines = vec![];
+
+    for span in spans {
+        lines.push(heading(format!(
+            "{}:{}-{}",
+            span.path.display(),
+            span.start,
+            span.end
+        )));
+        let end = span.end.min(span.start + DEFINITION_PREVIEW_LINES - 1);
+        for number in span.start..=end {
+            let text = source_line(app, &span.path, number);
+            lines.push(numbered(number, text));
+        }
+        if end < span.end {
+            lines.push(Line::styled(
+                format!("      ... {} more lines", span.end - end),
+                Style::new().dark_gray(),
+            ));
+        }
+    }
+
+    lines.push(heading("Occurrences".to_string()));
+    let mut shown = 0;
+    for (path, numbers) in occurrences.iter() {
+        for &number in numbers.iter() {
+            if shown == OCCURRENCE_PREVIEW_LINES {
+                break;
+            }
+            shown += 1;
+
+            let text = source_line(app, path, number);
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{}:{} ", path.display(), number),
+                    Style::new().cyan(),
+                ),
+                Span::raw(text.trim().to_string()),
+            ]));
+        }
+    }
+    let total: usize = occurrences.iter().map(|(_, numbers)| numbers.len()).sum();
+    if total == 0 {
+        lines.push(Line::styled("No occurrences", Style::new().dark_gray()));
+    } else if total > shown {
+        lines.push(Line::styled(
+            format!("... {} more occurrences", total - shown),
+            Style::new().dark_gray(),
+        ));
+    }
+
+    lines
+}
+
+fn source_line(app: &mut TriageApp, path: &Path, number: usize) -> String {
+    app.file_lines(path)
+        .and_then(|lines| lines.get(number.saturating_sub(1)))
+        .map(|line| line.replace('\t', "    "))
+        .unwrap_or_default()
+}
+
+fn heading(text: String) -> Line<'static> {
+    Line::styled(text, Style::new().bold())
+}
+
+fn numbered(number: usize, text: String) -> Line<'static> {
+    Line::from(vec![
+        Span::styled(format!("{:>5} ", number), Style::new().dark_gray()),
+        Span::raw(text),
+    ])
+}
+
+fn help(app: &TriageApp) -> Line<'static> {
+    match &app.mode {
+        Mode::Reason(reason) => Line::from(vec![
+            Span::styled("Reason to ignore: ", Style::new().bold()),
+            Span::raw(format!("{}_", reason)),
+            Span::styled("  (enter to save, esc to cancel)", Style::new().dark_gray()),
+        ]),
+        Mode::Browse => match &app.status {
+            Some(status) => Line::styled(status.to_string(), Style::new().cyan()),
+            None => Line::styled(
+                "j/k move  n next undecided  d delete later  i ignore  s skip  u clear  q quit",
+                Style::new().dark_gray(),
+            ),
+        },
+    }
+}
@@ -1,7 +1,8 @@
 use clap::ValueEnum;
+use codebase_files::FileSource;
 use read_ctags::Language;
 use std::fmt::{Display, Formatter};
-use token_analysis::OrderField;
+use token_search::FileCategory;
 
 #[derive(Copy, Clone, Debug, ValueEnum)]
 pub enum LanguageExtension {
@@ -50,47 +51,45 @@ impl From<LanguageExtension> for Language {
     }
 }
 
-#[derive(Clone, Debug, ValueEnum)]
-pub enum SortOrder {
-    File,
-    Token,
-}
-
-impl From<SortOrder> for OrderField {
-    fn from(sort_order: SortOrder) -> Self {
-        match sort_order {
-            SortOrder::File => OrderField::File,
-            SortOrder::Token => OrderField::Token,
-        }
-    }
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub enum FilesFrom {
+    Walk,
+    Git,
+    GitUntracked,
 }
 
-impl From<OrderField> for SortOrder {
-    fn from(order_field: OrderField) -> Self {
-        match order_field {
-            OrderField::File => SortOrder::File,
-            OrderField::Token => SortOrder::Token,
+impl From<FilesFrom> for FileSource {
+    fn from(files_from: FilesFrom) -> Self {
+        match files_from {
+            FilesFrom::Walk => FileSource::Walk,
+            FilesFrom::Git => FileSource::Git {
+                include_untracked: false,
+            },
+            FilesFrom::GitUntracked => FileSource::Git {
+                include_untracked: true,
+            },
         }
     }
 }
 
-impl Default for SortOrder {
-    fn default() -> Self {
-        OrderField::default().into()
-    }
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub enum SearchCategory {
+    Documentation,
+    Fixtures,
 }
 
-impl Display for SortOrder {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        match self {
-            SortOrder::File => write!(f, "file"),
-            SortOrder::Token => write!(f, "token"),
+impl From<SearchCategory> for FileCategory {
+    fn from(category: SearchCategory) -> Self {
+        match category {
+            SearchCategory::Documentation => FileCategory::Documentation,
+            SearchCategory::Fixtures => FileCategory::Fixtures,
         }
     }
 }
 
-#[derive(Debug, Clone, ValueEnum)]
+#[derive(Debug, Clone, Default, ValueEnum)]
 pub enum Format {
+    #[default]
     Standard,
     Compact,
     Json,
@@ -106,8 +105,39 @@ impl Display for Format {
     }
 }
 
-impl Default for Format {
-    fn default() -> Self {
-        Format::Standard
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub enum GroupBy {
+    File,
+    Directory,
+    Kind,
+    Language,
+}
+
+impl Display for GroupBy {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            GroupBy::File => write!(f, "file"),
+            GroupBy::Directory => write!(f, "directory"),
+            GroupBy::Kind => write!(f, "kind"),
+            GroupBy::Language => write!(f, "language"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, ValueEnum)]
+pub enum ProgressFormat {
+    #[default]
+    Terminal,
+    Json,
+    None,
+}
+
+impl Display for ProgressFormat {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            ProgressFormat::Terminal => write!(f, "terminal"),
+            ProgressFormat::Json => write!(f, "json"),
+            ProgressFormat::None => write!(f, "none"),
+        }
     }
 }
@@ -0,0 +1,283 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::UNIX_EPOCH;
+
+const CACHE_VERSION: u32 = 1;
+const UNCOMMITTED: &str = "0000000000000000000000000000000000000000";
+
+/// The commit which last modified a line
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Blame {
+    pub commit: String,
+    pub author: String,
+    /// Author time, in seconds since the epoch
+    pub timestamp: u64,
+}
+
+impl Blame {
+    /// Has the line been committed, or is it only modified in the working tree?
+    pub fn is_committed(&self) -> bool {
+        self.commit != UNCOMMITTED
+    }
+
+    /// Abbreviated commit, or `uncommitted`
+    pub fn short_commit(&self) -> &str {
+        if self.is_committed() {
+            &self.commit[..self.commit.len().min(10)]
+        } else {
+            "uncommitted"
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct CachedFile {
+    modified: u64,
+    len: u64,
+    lines: BTreeMap<usize, Blame>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheContents {
+    version: u32,
+    head: String,
+    files: BTreeMap<String, CachedFile>,
+}
+
+/// Runs `git blame` within a repository, caching lines already blamed in its git directory
+///
+/// Cached lines are reused while `HEAD` is unchanged and the file's size and modification time
+/// match those recorded alongside them.
+pub struct BlameCache {
+    root: PathBuf,
+    path: PathBuf,
+    prefix: String,
+    contents: CacheContents,
+}
+
+impl BlameCache {
+    /// Load the cache for the repository containing the provided directory
+    pub fn within(root: &Path) -> io::Result<BlameCache> {
+        let git_dir = PathBuf::from(Self::rev_parse(root, "--git-dir")?);
+        let path = root.join(git_dir).join("unused").join("blame.json");
+        let prefix = Self::rev_parse(root, "--show-prefix")?;
+        let head = Self::rev_parse(root, "HEAD").unwrap_or_default();
+
+        let contents = fs::read_to_string(&path)
+            .ok()
+            .and_then(|c| serde_json::from_str::<CacheContents>(&c).ok())
+            .filter(|c| c.version == CACHE_VERSION && c.head == head)
+            .unwrap_or(CacheContents {
+                version: CACHE_VERSION,
+                head,
+                files: BTreeMap::new(),
+            });
+
+        Ok(BlameCache {
+            root: root.to_path_buf(),
+            path,
+            prefix,
+            contents,
+        })
+    }
+
+    /// Cached blame for lines (1-based) within a file relative to the root
+    ///
+    /// This is `None` unless every line is cached and the file is unchanged since.
+    pub fn get(&self, path: &Path, lines: &BTreeSet<usize>) -> Option<HashMap<usize, Blame>> {
+        let cached = self.contents.files.get(&self.key(path))?;
+
+        if Some((cached.modified, cached.len)) != self.stamp(path) {
+            return None;
+        }
+
+        lines
+            .iter()
+            .map(|line| cached.lines.get(line).map(|b| (*line, b.clone())))
+            .collect()
+    }
+
+    /// Blame lines (1-based) within a file relative to the root, without consulting the cache
+    pub fn blame(&self, path: &Path, lines: &BTreeSet<usize>) -> io::Result<HashMap<usize, Blame>> {
+        if lines.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let mut command = Command::new("git");
+        command
+            .current_dir(&self.root)
+            .args(["blame", "--line-porcelain"]);
+
+        for line in lines {
+            command.arg("-L").arg(format!("{},{}", line, line));
+        }
+
+        let output = command.arg("--").arg(path).output()?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "Unable to blame {}: {}",
+                path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(Self::parse(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Record blamed lines for a file relative to the root
+    pub fn insert(&mut self, path: &Path, blames: HashMap<usize, Blame>) {
+        let (modified, len) = match self.stamp(path) {
+            Some(stamp) => stamp,
+            None => return,
+        };
+        let cached = self.contents.files.entry(self.key(path)).or_default();
+
+        if (cached.modified, cached.len) != (modified, len) {
+            *cached = CachedFile {
+                modified,
+                len,
+                lines: BTreeMap::new(),
+            };
+        }
+
+        cached.lines.extend(blames);
+    }
+
+    /// Write the cache to the repository's git directory
+    pub fn save(&self) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string(&self.contents)?)?;
+        fs::rename(tmp, &self.path)
+    }
+
+    fn key(&self, path: &Path) -> String {
+        format!("{}{}", self.prefix, path.to_string_lossy())
+    }
+
+    fn stamp(&self, path: &Path) -> Option<(u64, u64)> {
+        let metadata = fs::metadata(self.root.join(path)).ok()?;
+        let modified = metadata
+            .modified()
+            .ok()?
+            .duration_since(UNIX_EPOCH)
+            .ok()?
+            .as_nanos() as u64;
+
+        Some((modified, metadata.len()))
+    }
+
+    fn rev_parse(root: &Path, arg: &str) -> io::Result<String> {
+        let output = Command::new("git")
+            .current_dir(root)
+            .args(["rev-parse", arg])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "Unable to read git repository: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    fn parse(porcelain: &str) -> HashMap<usize, Blame> {
+        let mut blames = HashMap::new();
+        let mut current: Option<(usize, Blame)> = None;
+
+        for line in porcelain.lines() {
+            if line.starts_with('\t') {
+                if let Some((number, blame)) = current.take() {
+                    blames.insert(number, blame);
+                }
+            } else if let Some((_, blame)) = current.as_mut() {
+                if let Some(author) = line.strip_prefix("author ") {
+                    blame.author = author.to_string();
+                } else if let Some(time) = line.strip_prefix("author-time ") {
+                    blame.timestamp = time.parse().unwrap_or_default();
+                }
+            } else {
+                let mut parts = line.split(' ');
+                let commit = parts.next().unwrap_or_default();
+                let number = parts.nth(1).and_then(|n| n.parse().ok());
+
+                if let Some(number) = number {
+                    current = Some((
+                        number,
+                        Blame {
+                            commit: commit.to_string(),
+                            author: String::new(),
+                            timestamp: 0,
+                        },
+                    ));
+                }
+            }
+        }
+
+        blames
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_line_porcelain() {
+        let porcelain = "\
+1b2c3d4e5f60718293a4b5c6d7e8f90112233445 3 4 1
+author Jane Doe
+author-mail <jane@example.com>
+author-time 1600000000
+author-tz +0000
+summary Add person
+filename app/models/person.rb
+\t  def name
+0000000000000000000000000000000000000000 9 12 1
+author Not Committed Yet
+author-time 1700000000
+filename app/models/person.rb
+\t  def age
+";
+        let blames = BlameCache::parse(porcelain);
+
+        assert_eq!(blames.len(), 2);
+        assert_eq!(
+            blames[&4],
+            Blame {
+                commit: "1b2c3d4e5f60718293a4b5c6d7e8f90112233445".to_string(),
+                author: "Jane Doe".to_string(),
+                timestamp: 1_600_000_000,
+            }
+        );
+        assert_eq!(blames[&4].short_commit(), "1b2c3d4e5f");
+        assert!(!blames[&12].is_committed());
+        assert_eq!(blames[&12].short_commit(), "uncommitted");
+    }
+
+    #[test]
+    fn blames_and_caches_lines_in_this_repository() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let path = Path::new("Cargo.toml");
+        let lines: BTreeSet<usize> = vec![1, 2].into_iter().collect();
+        let mut cache = BlameCache::within(root).unwrap();
+        let blames = cache.blame(path, &lines).unwrap();
+
+        assert_eq!(blames.len(), 2);
+        assert!(blames[&1].timestamp > 0);
+
+        cache.insert(path, blames.clone());
+        assert_eq!(cache.get(path, &lines), Some(blames));
+        assert_eq!(cache.get(path, &vec![3].into_iter().collect()), None);
+    }
+}
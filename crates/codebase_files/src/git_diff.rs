@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// What to compare against when computing changed lines
+#[derive(Clone, Debug, PartialEq)]
+pub enum DiffBase {
+    /// Changes in the working tree since the merge base of a git reference and `HEAD`
+    Since(String),
+    /// Changes staged in the git index
+    Staged,
+}
+
+impl std::fmt::Display for DiffBase {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DiffBase::Since(reference) => write!(f, "since {}", reference),
+            DiffBase::Staged => write!(f, "staged"),
+        }
+    }
+}
+
+/// Lines added or modified per file, relative to the directory the diff was computed within
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChangedLines {
+    pub files: HashMap<PathBuf, Vec<RangeInclusive<usize>>>,
+}
+
+impl ChangedLines {
+    /// Compute changed lines from the local repository
+    pub fn from_git(root: &Path, base: &DiffBase) -> io::Result<ChangedLines> {
+        let mut command = Command::new("git");
+        command.current_dir(root).args([
+            "-c",
+            "core.quotePath=false",
+            "diff",
+            "-U0",
+            "--no-color",
+            "--no-ext-diff",
+            "--relative",
+        ]);
+
+        match base {
+            DiffBase::Since(reference) => {
+                command.arg(Self::merge_base(root, reference)?);
+            }
+            DiffBase::Staged => {
+                command.arg("--cached");
+            }
+        }
+
+        let output = command.output()?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "Unable to compute changes {}: {}",
+                base,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let mut changes = Self::parse(&String::from_utf8_lossy(&output.stdout));
+
+        if let DiffBase::Since(_) = base {
+            for path in Self::untracked_files(root)? {
+                let lines = fs::read_to_string(root.join(&path))
+                    .map(|contents| contents.lines().count())
+                    .unwrap_or(0);
+                if lines > 0 {
+                    changes.files.insert(path, vec![1..=lines]);
+                }
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// Does the line (1-based) within the file fall within a change?
+    pub fn contains(&self, path: &Path, line: usize) -> bool {
+        self.files
+            .get(path)
+            .map(|ranges| ranges.iter().any(|r| r.contains(&line)))
+            .unwrap_or(false)
+    }
+
+    /// Does the file have any changes?
+    pub fn includes_file(&self, path: &Path) -> bool {
+        self.files.contains_key(path)
+    }
+
+    fn merge_base(root: &Path, reference: &str) -> io::Result<String> {
+        let output = Command::new("git")
+            .current_dir(root)
+            .args(["merge-base", reference, "HEAD"])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "Unable to find a common ancestor of {} and HEAD: {}",
+                reference,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Files not yet tracked by git, which are entirely new to the working tree
+    fn untracked_files(root: &Path) -> io::Result<Vec<PathBuf>> {
+        let output = Command::new("git")
+            .current_dir(root)
+            .args(["ls-files", "-z", "--others", "--exclude-standard"])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "Unable to list untracked files: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .collect())
+    }
+
+    /// Parse unified diff output with zero lines of context
+    ///
+    /// Each file starts with a `diff --git` header; its path comes from the `+++` line that
+    /// immediately follows `---` within that header, so added lines which happen to begin with
+    /// `++ ` are never mistaken for a new file.
+    fn parse(diff: &str) -> ChangedLines {
+        let mut files: HashMap<PathBuf, Vec<RangeInclusive<usize>>> = HashMap::new();
+        let mut current: Option<PathBuf> = None;
+        let mut in_header = false;
+        let mut after_removed_path = false;
+
+        for line in diff.lines() {
+            if line.starts_with("diff --git ") {
+                current = None;
+                in_header = true;
+                after_removed_path = false;
+            } else if in_header {
+                if line.starts_with("--- ") {
+                    after_removed_path = true;
+                    continue;
+                }
+                if let (true, Some(path)) = (after_removed_path, line.strip_prefix("+++ ")) {
+                    current = path.strip_prefix("b/").map(PathBuf::from);
+                    in_header = false;
+                } else if line.starts_with("@@ ") {
+                    in_header = false;
+                }
+                after_removed_path = false;
+            } else if let (Some(path), Some(hunk)) = (&current, line.strip_prefix("@@ ")) {
+                if let Some(range) = Self::parse_hunk(hunk) {
+                    files.entry(path.to_path_buf()).or_default().push(range);
+                }
+            }
+        }
+
+        ChangedLines { files }
+    }
+
+    fn parse_hunk(hunk: &str) -> Option<RangeInclusive<usize>> {
+        let added = hunk.split(' ').find_map(|part| part.strip_prefix('+'))?;
+        let mut parts = added.splitn(2, ',');
+        let start: usize = parts.next()?.parse().ok()?;
+        let count: usize = match parts.next() {
+            Some(count) => count.parse().ok()?,
+            None => 1,
+        };
+
+        if count == 0 {
+            None
+        } else {
+            Some(start..=start + count - 1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_added_and_modified_lines() {
+        let diff = "\
+diff --git a/app/models/person.rb b/app/models/person.rb
+index 1111111..2222222 100644
+--- a/app/models/person.rb
++++ b/app/models/person.rb
+@@ -3,0 +4,3 @@ class Person
++  def name
++  end
++
+@@ -10 +13 @@ class Person
+-  def old
++  def new
+@@ -20,2 +23,0 @@ class Person
+-  def removed
+-  end
+diff --git a/lib/gone.rb b/lib/gone.rb
+deleted file mode 100644
+--- a/lib/gone.rb
++++ /dev/null
+@@ -1,2 +0,0 @@
+-class Gone
+-end
+";
+        let changes = ChangedLines::parse(diff);
+        let person = Path::new("app/models/person.rb");
+
+        assert_eq!(changes.files.len(), 1);
+        assert!(!changes.contains(person, 3));
+        assert!(changes.contains(person, 4));
+        assert!(changes.contains(person, 6));
+        assert!(!changes.contains(person, 7));
+        assert!(changes.contains(person, 13));
+        assert!(!changes.contains(person, 23));
+        assert!(!changes.includes_file(Path::new("lib/gone.rb")));
+    }
+
+    #[test]
+    fn ignores_added_lines_that_look_like_file_headers() {
+        let diff = "\
+diff --git a/docs/notes.md b/docs/notes.md
+index 1111111..2222222 100644
+--- a/docs/notes.md
++++ b/docs/notes.md
+@@ -1,0 +2,2 @@
++++ b/app/models/person.rb
++--- a/app/models/person.rb
+@@ -8 +10 @@
+-old
++new
+diff --git a/bin/run b/bin/run
+old mode 100644
+new mode 100755
+@@ -1 +1 @@
+-echo
++exec
+";
+        let changes = ChangedLines::parse(diff);
+        let notes = Path::new("docs/notes.md");
+
+        assert_eq!(changes.files.len(), 1);
+        assert!(changes.contains(notes, 2));
+        assert!(changes.contains(notes, 3));
+        assert!(changes.contains(notes, 10));
+        assert!(!changes.includes_file(Path::new("app/models/person.rb")));
+        assert!(!changes.includes_file(Path::new("bin/run")));
+    }
+}
@@ -0,0 +1,51 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// List files in the git index under the provided directory, relative to it
+///
+/// Files removed from the working tree but still present in the index are skipped.
+pub fn tracked_files(root: &Path, include_untracked: bool) -> io::Result<Vec<PathBuf>> {
+    let mut command = Command::new("git");
+    command
+        .current_dir(root)
+        .args(["ls-files", "-z", "--cached"]);
+
+    if include_untracked {
+        command.args(["--others", "--exclude-standard"]);
+    }
+
+    let output = command.output()?;
+
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "Unable to list files tracked by git: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let mut paths: Vec<PathBuf> = output
+        .stdout
+        .split(|&b| b == 0)
+        .filter(|p| !p.is_empty())
+        .map(|p| PathBuf::from(String::from_utf8_lossy(p).into_owned()))
+        .filter(|p| root.join(p).is_file())
+        .collect();
+    paths.dedup();
+
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_files_relative_to_root() {
+        let paths = tracked_files(Path::new("src"), false).unwrap();
+
+        assert!(paths.contains(&PathBuf::from("lib.rs")));
+        assert!(paths.contains(&PathBuf::from("git_tree.rs")));
+        assert!(!paths.iter().any(|p| p.starts_with("src")));
+    }
+}
@@ -0,0 +1,159 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+
+/// The files tracked within a git tree-ish (branch, tag, or commit), read from the local repository
+#[derive(Clone, Debug, PartialEq)]
+pub struct GitTree {
+    pub reference: String,
+    pub root: PathBuf,
+    pub paths: Vec<PathBuf>,
+}
+
+impl GitTree {
+    /// List files in the provided reference, relative to the current directory
+    pub fn at(reference: &str) -> io::Result<GitTree> {
+        Self::within(Path::new("."), reference)
+    }
+
+    /// List files in the provided reference, relative to the provided directory
+    pub fn within(root: &Path, reference: &str) -> io::Result<GitTree> {
+        let output = Command::new("git")
+            .current_dir(root)
+            .args(["ls-tree", "-r", "-z", "--name-only", reference])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "Unable to read git ref {}: {}",
+                reference,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let mut paths: Vec<PathBuf> = output
+            .stdout
+            .split(|&b| b == 0)
+            .filter(|p| !p.is_empty())
+            .map(|p| PathBuf::from(String::from_utf8_lossy(p).into_owned()))
+            .collect();
+        paths.sort();
+
+        Ok(GitTree {
+            reference: reference.to_string(),
+            root: root.to_path_buf(),
+            paths,
+        })
+    }
+
+    /// Read the contents of each file in the tree, calling `f` with the path and contents
+    ///
+    /// Files which aren't valid UTF-8 are skipped.
+    pub fn each_file<F>(&self, mut f: F) -> io::Result<()>
+    where
+        F: FnMut(&PathBuf, &str),
+    {
+        let mut child = Command::new("git")
+            .current_dir(&self.root)
+            .args(["cat-file", "--batch"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let mut stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| io::Error::other("no stdin"))?;
+        let requests: Vec<String> = self
+            .paths
+            .iter()
+            .map(|p| format!("{}:./{}\n", self.reference, p.to_string_lossy()))
+            .collect();
+        let writer = thread::spawn(move || -> io::Result<()> {
+            for request in requests {
+                stdin.write_all(request.as_bytes())?;
+            }
+            Ok(())
+        });
+
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| io::Error::other("no stdout"))?;
+        let mut reader = BufReader::new(stdout);
+
+        for path in self.paths.iter() {
+            let mut header = String::new();
+            if reader.read_line(&mut header)? == 0 {
+                break;
+            }
+
+            if header.trim_end().ends_with(" missing") {
+                return Err(io::Error::other(format!(
+                    "Unable to read {} at git ref {}",
+                    path.display(),
+                    self.reference
+                )));
+            }
+
+            if let Some((object_type, size)) = Self::object_header(&header) {
+                let mut contents = vec![0; size + 1];
+                reader.read_exact(&mut contents)?;
+                contents.truncate(size);
+
+                if object_type == "blob" {
+                    if let Ok(contents) = String::from_utf8(contents) {
+                        f(path, &contents);
+                    }
+                }
+            }
+        }
+
+        writer
+            .join()
+            .map_err(|_| io::Error::other("failed writing to git cat-file"))??;
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(io::Error::other(format!(
+                "Unable to read files at git ref {}",
+                self.reference
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn object_header(header: &str) -> Option<(&str, usize)> {
+        match header.split_whitespace().collect::<Vec<_>>()[..] {
+            [_, object_type, size] => size.parse().ok().map(|size| (object_type, size)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_object_headers() {
+        assert_eq!(
+            GitTree::object_header("0ef11bcc3ab02556abf09c25f8aa2c6a35461017 blob 598\n"),
+            Some(("blob", 598))
+        );
+        assert_eq!(GitTree::object_header("HEAD:./missing missing\n"), None);
+    }
+
+    #[test]
+    fn fails_reading_files_missing_from_the_tree() {
+        let tree = GitTree {
+            reference: String::from("HEAD"),
+            root: PathBuf::from("."),
+            paths: vec![PathBuf::from("missing.rs")],
+        };
+
+        assert!(tree.each_file(|_, _| {}).is_err());
+    }
+}
@@ -1,7 +1,114 @@
+mod git_blame;
+mod git_diff;
+mod git_index;
+mod git_tree;
+
+pub use git_blame::{Blame, BlameCache};
+pub use git_diff::{ChangedLines, DiffBase};
+pub use git_tree::GitTree;
+
+use ignore::overrides::{Override, OverrideBuilder};
 use ignore::{WalkBuilder, WalkState};
+use std::fs;
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 
+pub use ignore::Error as WalkError;
+
+/// Where the list of files in a codebase comes from
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FileSource {
+    /// Walk the filesystem, respecting `.gitignore` and other ignore files
+    #[default]
+    Walk,
+    /// Files in the git index (`git ls-files`)
+    Git {
+        /// Also include untracked files which aren't ignored
+        include_untracked: bool,
+    },
+}
+
+impl std::fmt::Display for FileSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FileSource::Walk => write!(f, "walk"),
+            FileSource::Git {
+                include_untracked: false,
+            } => write!(f, "git"),
+            FileSource::Git {
+                include_untracked: true,
+            } => write!(f, "git-untracked"),
+        }
+    }
+}
+
+impl FromStr for FileSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_ref() {
+            "walk" => Ok(FileSource::Walk),
+            "git" => Ok(FileSource::Git {
+                include_untracked: false,
+            }),
+            "git-untracked" => Ok(FileSource::Git {
+                include_untracked: true,
+            }),
+            v => Err(format!("Unable to parse file source: {}", v)),
+        }
+    }
+}
+
+/// Options controlling how a codebase is walked
+#[derive(Clone, Debug, PartialEq)]
+pub struct WalkOptions {
+    /// Directory to walk; paths returned are relative to it
+    pub root: PathBuf,
+    /// Where files are listed from
+    ///
+    /// When unset, files are walked unless something else (e.g. a project configuration) says
+    /// otherwise. Only `globs` apply when listing files from git.
+    pub source: Option<FileSource>,
+    /// Follow symbolic links
+    pub follow_symlinks: bool,
+    /// Include hidden files and directories (`.git` is always excluded)
+    pub hidden: bool,
+    /// Additional per-directory ignore files, in `.gitignore` format
+    pub ignore_filenames: Vec<String>,
+    /// Maximum depth to descend from the root
+    pub max_depth: Option<usize>,
+    /// Globs (in `.gitignore` format) limiting which files are included
+    ///
+    /// Globs prefixed with `!` exclude matching files instead.
+    pub globs: Vec<String>,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        WalkOptions {
+            root: PathBuf::from("."),
+            source: None,
+            follow_symlinks: false,
+            hidden: true,
+            ignore_filenames: vec![String::from(".unusedignore")],
+            max_depth: None,
+            globs: vec![],
+        }
+    }
+}
+
+impl WalkOptions {
+    /// The same options, walking a different root
+    pub fn with_root(&self, root: &Path) -> Self {
+        WalkOptions {
+            root: root.to_path_buf(),
+            ..self.clone()
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct CodebaseFiles {
     pub paths: Vec<PathBuf>,
@@ -9,9 +116,66 @@ pub struct CodebaseFiles {
 
 impl CodebaseFiles {
     pub fn all() -> CodebaseFiles {
-        let mut builder = WalkBuilder::new("./");
-        builder.hidden(false);
-        builder.filter_entry(|entry| entry.path() != Path::new("./.git"));
+        Self::with_options(&WalkOptions::default()).unwrap_or(CodebaseFiles { paths: vec![] })
+    }
+
+    /// Files found in the codebase with the provided options, relative to its root
+    ///
+    /// This fails if any of the provided globs are invalid, or if files can't be listed from git.
+    pub fn with_options(options: &WalkOptions) -> Result<CodebaseFiles, WalkError> {
+        let mut paths: Vec<PathBuf> = match options.source.unwrap_or_default() {
+            FileSource::Walk => Self::walk(options)?
+                .into_iter()
+                .map(|path| {
+                    path.strip_prefix(&options.root)
+                        .unwrap_or(&path)
+                        .to_path_buf()
+                })
+                .collect(),
+            FileSource::Git { include_untracked } => Self::from_git(options, include_untracked)?,
+        };
+        paths.sort();
+
+        Ok(CodebaseFiles { paths })
+    }
+
+    fn from_git(options: &WalkOptions, include_untracked: bool) -> Result<Vec<PathBuf>, WalkError> {
+        let paths = git_index::tracked_files(&options.root, include_untracked)?;
+
+        if options.globs.is_empty() {
+            return Ok(paths);
+        }
+
+        let overrides = Self::overrides(options)?;
+
+        Ok(paths
+            .into_iter()
+            .filter(|path| !overrides.matched(path, false).is_ignore())
+            .collect())
+    }
+
+    fn overrides(options: &WalkOptions) -> Result<Override, WalkError> {
+        let mut overrides = OverrideBuilder::new(&options.root);
+        for glob in options.globs.iter() {
+            overrides.add(glob)?;
+        }
+        overrides.build()
+    }
+
+    fn walk(options: &WalkOptions) -> Result<Vec<PathBuf>, WalkError> {
+        let mut builder = WalkBuilder::new(&options.root);
+        builder.hidden(!options.hidden);
+        builder.follow_links(options.follow_symlinks);
+        builder.max_depth(options.max_depth);
+        builder.filter_entry(|entry| entry.file_name() != ".git");
+
+        for filename in options.ignore_filenames.iter() {
+            builder.add_custom_ignore_filename(filename);
+        }
+
+        if !options.globs.is_empty() {
+            builder.overrides(Self::overrides(options)?);
+        }
 
         let results = Arc::new(Mutex::new(vec![]));
 
@@ -20,9 +184,7 @@ impl CodebaseFiles {
                 if let Ok(entry) = result {
                     if entry.file_type().map(|ft| ft.is_file()).unwrap_or(false) {
                         let mut results = results.lock().unwrap();
-                        let path = entry.path().strip_prefix("./").unwrap_or(entry.path());
-
-                        results.push(path.to_path_buf());
+                        results.push(entry.path().to_path_buf());
                     }
                 }
 
@@ -30,24 +192,108 @@ impl CodebaseFiles {
             })
         });
 
-        let mut paths = results.lock().unwrap().to_vec();
-        paths.sort();
-
-        CodebaseFiles { paths }
+        let paths = results.lock().unwrap().to_vec();
+        Ok(paths)
     }
 }
 
+/// Replace a file's contents only once they're fully written, keeping its permissions
+///
+/// Contents are written to a temporary file alongside the original, which is then renamed over
+/// it. Files which don't exist yet are written directly, with default permissions.
+pub fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    let permissions = match fs::metadata(path) {
+        Ok(metadata) => metadata.permissions(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return fs::write(path, contents),
+        Err(e) => return Err(e),
+    };
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    let mut temporary = tempfile::NamedTempFile::new_in(directory)?;
+    temporary.write_all(contents.as_bytes())?;
+    temporary.as_file().set_permissions(permissions)?;
+    temporary.persist(path).map(|_| ()).map_err(|e| e.error)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn strips_prefix() {
+        let paths = CodebaseFiles::all().paths;
+
+        assert!(paths.contains(&PathBuf::from("Cargo.toml")));
+        assert!(paths.contains(&PathBuf::from("src/lib.rs")));
+        assert!(paths.iter().all(|p| p.is_relative() && !p.starts_with(".")));
+    }
+
+    #[test]
+    fn walks_from_root_with_options() {
+        let options = WalkOptions {
+            root: PathBuf::from("src"),
+            globs: vec![String::from("*.rs"), String::from("!git_*.rs")],
+            ..Default::default()
+        };
+
         assert_eq!(
-            CodebaseFiles::all(),
+            CodebaseFiles::with_options(&options).unwrap(),
             CodebaseFiles {
-                paths: vec![PathBuf::from("Cargo.toml"), PathBuf::from("src/lib.rs")]
+                paths: vec![PathBuf::from("lib.rs")]
             }
         );
+
+        let shallow = WalkOptions {
+            max_depth: Some(1),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            CodebaseFiles::with_options(&shallow).unwrap(),
+            CodebaseFiles {
+                paths: vec![PathBuf::from("Cargo.toml")]
+            }
+        );
+    }
+
+    #[test]
+    fn writes_atomically_keeping_permissions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("script");
+        let neighbour = dir.path().join("script.tmp");
+        fs::write(&neighbour, "kept").unwrap();
+
+        write_atomically(&path, "new").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+
+        let mut permissions = fs::metadata(&path).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&path, permissions.clone()).unwrap();
+
+        write_atomically(&path, "replaced").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "replaced");
+        assert_eq!(fs::metadata(&path).unwrap().permissions(), permissions);
+        assert_eq!(fs::read_to_string(&neighbour).unwrap(), "kept");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn lists_files_from_git() {
+        let options = WalkOptions {
+            root: PathBuf::from("src"),
+            source: Some(FileSource::Git {
+                include_untracked: false,
+            }),
+            globs: vec![String::from("!git_tree.rs")],
+            ..Default::default()
+        };
+
+        let paths = CodebaseFiles::with_options(&options).unwrap().paths;
+
+        assert!(paths.contains(&PathBuf::from("lib.rs")));
+        assert!(!paths.contains(&PathBuf::from("git_tree.rs")));
     }
 }
@@ -0,0 +1,416 @@
+use codebase_files::write_atomically;
+use read_ctags::TokenKind;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use token_search::Token;
+use yaml_rust::yaml::Hash;
+use yaml_rust::{EmitError, ScanError, Yaml, YamlEmitter, YamlLoader};
+
+/// Name of the allowlist file, read from the root of the project
+pub const ALLOWLIST_FILE_NAME: &str = ".unused-allowlist";
+
+const HEADER: &str = "# Tokens which unused should never report.\n# Manage with `unused ignore <token> --reason ...` and `unused ignore --prune`.\n";
+
+/// A token known to be used, optionally limited to a path and kind
+#[derive(Clone, Debug, PartialEq)]
+pub struct AllowlistEntry {
+    pub token: String,
+    /// Matches definitions at this path, or within this directory
+    pub path: Option<PathBuf>,
+    pub kind: Option<TokenKind>,
+    pub reason: String,
+    pub author: Option<String>,
+}
+
+/// Project-local list of tokens excluded from findings
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Allowlist {
+    entries: Vec<AllowlistEntry>,
+}
+
+#[derive(Debug)]
+pub enum AllowlistError {
+    Io(PathBuf, io::Error),
+    Yaml(PathBuf, ScanError),
+    Emit(PathBuf, EmitError),
+    Invalid(PathBuf, String),
+}
+
+impl AllowlistEntry {
+    /// Does the entry apply to the token?
+    pub fn matches(&self, token: &Token) -> bool {
+        token.token == self.token
+            && token.definitions.iter().any(|d| {
+                self.path
+                    .as_ref()
+                    .map(|p| d.file_path.starts_with(p))
+                    .unwrap_or(true)
+                    && self.kind.map(|k| d.kind == k).unwrap_or(true)
+            })
+    }
+
+    fn same_target(&self, other: &AllowlistEntry) -> bool {
+        self.token == other.token && self.path == other.path && self.kind == other.kind
+    }
+
+    fn parse(item: &Yaml) -> Result<Self, String> {
+        let string = |key: &str| item[key].as_str().map(|v| v.to_string());
+
+        Ok(AllowlistEntry {
+            token: string("token").ok_or("entry is missing a token")?,
+            path: string("path").map(PathBuf::from),
+            kind: string("kind").map(|k| k.parse::<TokenKind>()).transpose()?,
+            reason: string("reason").ok_or("entry is missing a reason")?,
+            author: string("author"),
+        })
+    }
+
+    fn to_yaml(&self) -> Yaml {
+        let mut hash = Hash::new();
+        let mut insert = |key: &str, value: String| {
+            hash.insert(Yaml::String(key.to_string()), Yaml::String(value));
+        };
+
+        insert("token", self.token.to_string());
+        if let Some(path) = &self.path {
+            insert("path", path.to_string_lossy().to_string());
+        }
+        if let Some(kind) = &self.kind {
+            insert("kind", kind.to_string());
+        }
+        insert("reason", self.reason.to_string());
+        if let Some(author) = &self.author {
+            insert("author", author.to_string());
+        }
+
+        Yaml::Hash(hash)
+    }
+}
+
+impl Allowlist {
+    /// Load an allowlist; a missing file is treated as empty
+    pub fn load(path: &Path) -> Result<Self, AllowlistError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents).map_err(|e| e.at(path)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(AllowlistError::Io(path.to_path_buf(), e)),
+        }
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, AllowlistError> {
+        let documents = YamlLoader::load_from_str(contents)
+            .map_err(|e| AllowlistError::Yaml(PathBuf::new(), e))?;
+        let entries = match documents.first() {
+            None | Some(Yaml::Null) => vec![],
+            Some(Yaml::Array(items)) => items
+                .iter()
+                .map(AllowlistEntry::parse)
+                .collect::<Result<_, _>>()
+                .map_err(|e| AllowlistError::Invalid(PathBuf::new(), e))?,
+            Some(_) => {
+                return Err(AllowlistError::Invalid(
+                    PathBuf::new(),
+                    String::from("expected a list of entries"),
+                ))
+            }
+        };
+
+        Ok(Allowlist { entries })
+    }
+
+    /// Write the allowlist, replacing the file only once the new contents are fully written
+    pub fn write(&self, path: &Path) -> Result<(), AllowlistError> {
+        let contents = self.to_yaml().map_err(|e| e.at(path))?;
+        Self::write_contents(path, &contents)
+    }
+
+    /// Add an entry for a new target to the allowlist at the path, appending it to the file's
+    /// existing text so comments and ordering are kept
+    pub fn append(&mut self, path: &Path, entry: AllowlistEntry) -> Result<(), AllowlistError> {
+        if self.entries.iter().any(|e| e.same_target(&entry)) {
+            return Err(AllowlistError::Invalid(
+                path.to_path_buf(),
+                format!(
+                    "{} is already allowlisted; edit the entry instead",
+                    entry.token
+                ),
+            ));
+        }
+
+        let existing = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(AllowlistError::Io(path.to_path_buf(), e)),
+        };
+        let preamble = if existing.trim().is_empty() {
+            HEADER.to_string()
+        } else if self.entries.is_empty() {
+            // keep comments, dropping an empty list (`[]`) the new entry can't follow
+            existing
+                .lines()
+                .filter(|line| line.trim().is_empty() || line.trim_start().starts_with('#'))
+                .map(|line| format!("{}\n", line))
+                .collect()
+        } else if existing.ends_with('\n') {
+            existing
+        } else {
+            existing + "\n"
+        };
+        let contents = format!(
+            "{}{}",
+            preamble,
+            Self::emit(std::slice::from_ref(&entry)).map_err(|e| e.at(path))?
+        );
+
+        self.entries.push(entry);
+        if Self::parse(&contents).ok().as_ref() != Some(self) {
+            return Err(AllowlistError::Invalid(
+                path.to_path_buf(),
+                String::from("the new entry couldn't be appended to the existing list"),
+            ));
+        }
+
+        Self::write_contents(path, &contents)
+    }
+
+    fn write_contents(path: &Path, contents: &str) -> Result<(), AllowlistError> {
+        write_atomically(path, contents).map_err(|e| AllowlistError::Io(path.to_path_buf(), e))
+    }
+
+    pub fn to_yaml(&self) -> Result<String, AllowlistError> {
+        if self.entries.is_empty() {
+            return Ok(format!("{}[]\n", HEADER));
+        }
+
+        Ok(format!("{}{}", HEADER, Self::emit(&self.entries)?))
+    }
+
+    /// Entries as a block sequence, without the document marker
+    fn emit(entries: &[AllowlistEntry]) -> Result<String, AllowlistError> {
+        let mut output = String::new();
+        YamlEmitter::new(&mut output)
+            .dump(&Yaml::Array(entries.iter().map(|e| e.to_yaml()).collect()))
+            .map_err(|e| AllowlistError::Emit(PathBuf::new(), e))?;
+
+        Ok(format!(
+            "{}\n",
+            output.trim_start_matches("---").trim_start()
+        ))
+    }
+
+    pub fn entries(&self) -> &[AllowlistEntry] {
+        &self.entries
+    }
+
+    /// Add an entry, replacing any existing entry for the same token, path, and kind
+    pub fn add(&mut self, entry: AllowlistEntry) {
+        match self.entries.iter_mut().find(|e| e.same_target(&entry)) {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    /// Remove entries which no longer match any token, returning them
+    pub fn prune(&mut self, tokens: &[Token]) -> Vec<AllowlistEntry> {
+        let (kept, removed) = self
+            .entries
+            .drain(..)
+            .partition(|e| tokens.iter().any(|t| e.matches(t)));
+        self.entries = kept;
+        removed
+    }
+
+    /// The entry allowing the token, if any
+    pub fn allows(&self, token: &Token) -> Option<&AllowlistEntry> {
+        self.entries.iter().find(|e| e.matches(token))
+    }
+}
+
+impl AllowlistError {
+    fn at(self, path: &Path) -> Self {
+        let path = path.to_path_buf();
+        match self {
+            AllowlistError::Io(_, e) => AllowlistError::Io(path, e),
+            AllowlistError::Yaml(_, e) => AllowlistError::Yaml(path, e),
+            AllowlistError::Emit(_, e) => AllowlistError::Emit(path, e),
+            AllowlistError::Invalid(_, e) => AllowlistError::Invalid(path, e),
+        }
+    }
+}
+
+impl Display for AllowlistError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            AllowlistError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            AllowlistError::Yaml(path, err) => write!(f, "{}: {}", path.display(), err),
+            AllowlistError::Emit(path, err) => write!(f, "{}: {:?}", path.display(), err),
+            AllowlistError::Invalid(path, err) => write!(f, "{}: {}", path.display(), err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use read_ctags::{CtagItem, Language};
+    use std::collections::BTreeMap;
+
+    fn build_token(name: &str, path: &str, kind: TokenKind) -> Token {
+        Token::new(
+            name.to_string(),
+            vec![CtagItem {
+                name: name.to_string(),
+                file_path: PathBuf::from(path),
+                address: String::from("1"),
+                language: Some(Language::Ruby),
+                tags: BTreeMap::new(),
+                kind,
+            }]
+            .into_iter()
+            .collect(),
+        )
+    }
+
+    fn build_entry(token: &str, path: Option<&str>, kind: Option<TokenKind>) -> AllowlistEntry {
+        AllowlistEntry {
+            token: token.to_string(),
+            path: path.map(PathBuf::from),
+            kind,
+            reason: String::from("called from cron"),
+            author: None,
+        }
+    }
+
+    #[test]
+    fn round_trips_through_yaml() {
+        let mut allowlist = Allowlist::default();
+        allowlist.add(build_entry(
+            "perform",
+            Some("app/jobs"),
+            Some(TokenKind::Method),
+        ));
+        allowlist.add(AllowlistEntry {
+            author: Some(String::from("Jane Doe")),
+            ..build_entry("legacy_helper", None, None)
+        });
+
+        let yaml = allowlist.to_yaml().unwrap();
+
+        assert!(yaml.starts_with(HEADER));
+        assert!(yaml.contains("- token: perform\n  path: app/jobs\n  kind: method\n"));
+        assert_eq!(Allowlist::parse(&yaml).unwrap(), allowlist);
+        assert_eq!(
+            Allowlist::parse(&Allowlist::default().to_yaml().unwrap()).unwrap(),
+            Allowlist::default()
+        );
+    }
+
+    #[test]
+    fn matches_by_token_path_and_kind() {
+        let perform = build_token("perform", "app/jobs/cleanup_job.rb", TokenKind::Method);
+        let mut allowlist = Allowlist::default();
+        allowlist.add(build_entry(
+            "perform",
+            Some("app/jobs"),
+            Some(TokenKind::Method),
+        ));
+
+        assert!(allowlist.allows(&perform).is_some());
+        assert!(allowlist
+            .allows(&build_token(
+                "perform",
+                "app/models/job.rb",
+                TokenKind::Method
+            ))
+            .is_none());
+        assert!(allowlist
+            .allows(&build_token(
+                "perform",
+                "app/jobs/cleanup_job.rb",
+                TokenKind::Class
+            ))
+            .is_none());
+    }
+
+    #[test]
+    fn adding_replaces_matching_entries_and_pruning_removes_missing_tokens() {
+        let mut allowlist = Allowlist::default();
+        allowlist.add(build_entry("perform", None, None));
+        allowlist.add(AllowlistEntry {
+            reason: String::from("used by sidekiq"),
+            ..build_entry("perform", None, None)
+        });
+        allowlist.add(build_entry("removed_helper", None, None));
+
+        assert_eq!(allowlist.entries().len(), 2);
+        assert_eq!(allowlist.entries()[0].reason, "used by sidekiq");
+
+        let removed = allowlist.prune(&[build_token(
+            "perform",
+            "app/jobs/cleanup_job.rb",
+            TokenKind::Method,
+        )]);
+
+        assert_eq!(removed, vec![build_entry("removed_helper", None, None)]);
+        assert_eq!(allowlist.entries().len(), 1);
+    }
+
+    #[test]
+    fn appending_keeps_existing_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(ALLOWLIST_FILE_NAME);
+        let existing = "# jobs\n- token: perform\n  reason: cron # nightly\n";
+        fs::write(&path, existing).unwrap();
+
+        let mut allowlist = Allowlist::load(&path).unwrap();
+        allowlist
+            .append(&path, build_entry("legacy_helper", None, None))
+            .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!(
+                "{}- token: legacy_helper\n  reason: called from cron\n",
+                existing
+            )
+        );
+        assert_eq!(Allowlist::load(&path).unwrap(), allowlist);
+        assert!(allowlist
+            .append(&path, build_entry("perform", None, None))
+            .is_err());
+    }
+
+    #[test]
+    fn appending_to_an_empty_allowlist_keeps_comments() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(ALLOWLIST_FILE_NAME);
+
+        let mut missing = Allowlist::default();
+        missing
+            .append(&path, build_entry("perform", None, None))
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!("{}- token: perform\n  reason: called from cron\n", HEADER)
+        );
+
+        fs::write(&path, "# nothing yet\n[]\n").unwrap();
+        let mut empty = Allowlist::load(&path).unwrap();
+        empty
+            .append(&path, build_entry("perform", None, None))
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# nothing yet\n- token: perform\n  reason: called from cron\n"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_entries() {
+        assert!(Allowlist::parse("- token: perform\n").is_err());
+        assert!(Allowlist::parse("token: perform\n").is_err());
+        assert!(Allowlist::parse("- token: perform\n  reason: cron\n  kind: nope\n").is_err());
+    }
+}
@@ -1,7 +1,11 @@
+mod allowlist;
 mod loader;
 mod project_configuration;
+mod scoring;
 mod value_assertion;
 
 pub use crate::project_configuration::{PathPrefix, ProjectConfiguration};
-pub use loader::ProjectConfigurations;
+pub use allowlist::{Allowlist, AllowlistEntry, AllowlistError, ALLOWLIST_FILE_NAME};
+pub use loader::{ConfigurationError, ProjectConfigurations};
+pub use scoring::{ScoreThresholds, ScoreWeights, Scoring};
 pub use value_assertion::{Assertion, AssertionConflict, ValueMatcher};
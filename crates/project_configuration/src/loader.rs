@@ -1,8 +1,12 @@
 use super::project_configuration::*;
+use super::scoring::{ScoreThresholds, ScoreWeights, Scoring};
 use super::value_assertion::{Assertion, ValueMatcher};
+use globset::Glob;
+use read_ctags::Language;
 use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
 use std::include_str;
-use token_search::TokenSearchResults;
+use token_search::{FileCategory, FileRestriction, LanguageRestriction, Token, TokenSearchResults};
 use yaml_rust::{Yaml, YamlLoader};
 
 const PATH_STARTS_WITH: &str = "path_starts_with";
@@ -14,7 +18,9 @@ const TOKEN_STARTS_WITH: &str = "token_starts_with";
 const TOKEN_ENDS_WITH: &str = "token_ends_with";
 const CLASS_OR_MODULE: &str = "class_or_module";
 const ALLOWED_TOKENS: &str = "allowed_tokens";
-const SUPPORTED_ASSERTIONS: [&'static str; 9] = [
+const KIND: &str = "kind";
+const LANGUAGE: &str = "language";
+const SUPPORTED_ASSERTIONS: [&str; 11] = [
     PATH_STARTS_WITH,
     PATH_ENDS_WITH,
     PATH_EQUALS,
@@ -24,12 +30,35 @@ const SUPPORTED_ASSERTIONS: [&'static str; 9] = [
     TOKEN_ENDS_WITH,
     CLASS_OR_MODULE,
     ALLOWED_TOKENS,
+    KIND,
+    LANGUAGE,
 ];
 
 pub struct ProjectConfigurations {
     configs: HashMap<String, ProjectConfiguration>,
 }
 
+/// A configuration entry which couldn't be parsed
+#[derive(Debug, PartialEq)]
+pub struct ConfigurationError {
+    /// Name of the project configuration containing the entry
+    pub configuration: String,
+    /// Key of the entry, e.g. `search_exclusions.globs`
+    pub key: String,
+    pub value: String,
+    pub reason: String,
+}
+
+impl Display for ConfigurationError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: invalid {} entry {:?} ({})",
+            self.configuration, self.key, self.value, self.reason
+        )
+    }
+}
+
 impl ProjectConfigurations {
     pub fn default_yaml() -> String {
         include_str!("default_config.yml").to_string()
@@ -39,12 +68,12 @@ impl ProjectConfigurations {
         self.configs.get(name)
     }
 
-    pub fn parse(contents: &str) -> Self {
+    pub fn parse(contents: &str) -> Result<Self, ConfigurationError> {
         let configs = match YamlLoader::load_from_str(contents) {
-            Ok(results) => Self::parse_all_from_yaml(&results),
+            Ok(results) => Self::parse_all_from_yaml(&results)?,
             _ => HashMap::new(),
         };
-        ProjectConfigurations { configs }
+        Ok(ProjectConfigurations { configs })
     }
 
     pub fn project_config_names(&self) -> Vec<String> {
@@ -59,6 +88,14 @@ impl ProjectConfigurations {
             .map(|(_, v)| v.clone())
     }
 
+    pub fn best_match_for_tokens(&self, tokens: &[&Token]) -> Option<ProjectConfiguration> {
+        self.configs
+            .iter()
+            .filter(|(_, config)| config.codebase_config_match_tokens(tokens))
+            .nth(0)
+            .map(|(_, v)| v.clone())
+    }
+
     pub fn assertion_to_key(assertion: &Assertion) -> Option<&str> {
         match assertion {
             Assertion::TokenAssertion(ValueMatcher::StartsWith(_)) => Some(TOKEN_STARTS_WITH),
@@ -73,34 +110,148 @@ impl ProjectConfigurations {
             Assertion::PathAssertion(ValueMatcher::ExactMatchOnAnyOf(_)) => None,
             Assertion::PathAssertion(ValueMatcher::StartsWithCapital) => None,
             Assertion::PathAssertion(ValueMatcher::Contains(_)) => Some(PATH_CONTAINS),
+            Assertion::KindAssertion(_) => Some(KIND),
+            Assertion::LanguageAssertion(_) => Some(LANGUAGE),
         }
     }
 
-    fn parse_all_from_yaml(contents: &[Yaml]) -> HashMap<String, ProjectConfiguration> {
+    fn parse_all_from_yaml(
+        contents: &[Yaml],
+    ) -> Result<HashMap<String, ProjectConfiguration>, ConfigurationError> {
         match contents {
-            [Yaml::Array(items)] => items.iter().filter(|i| !i["name"].is_badvalue()).fold(
-                HashMap::new(),
-                |mut acc, doc_with_name| {
+            [Yaml::Array(items)] => items
+                .iter()
+                .filter(|i| !i["name"].is_badvalue())
+                .map(|doc_with_name| {
                     let config_name = doc_with_name["name"].as_str().unwrap_or("").to_string();
-                    acc.insert(
-                        config_name.to_string(),
-                        Self::parse_from_yaml(&config_name, &doc_with_name),
-                    );
-                    acc
-                },
-            ),
-            _ => HashMap::new(),
+                    let config = Self::parse_from_yaml(&config_name, doc_with_name)?;
+                    Ok((config_name, config))
+                })
+                .collect(),
+            _ => Ok(HashMap::new()),
         }
     }
 
-    fn parse_from_yaml(config_name: &str, contents: &Yaml) -> ProjectConfiguration {
-        ProjectConfiguration {
+    fn parse_from_yaml(
+        config_name: &str,
+        contents: &Yaml,
+    ) -> Result<ProjectConfiguration, ConfigurationError> {
+        let invalid = |key: &str, value: &str, reason: String| ConfigurationError {
+            configuration: config_name.to_string(),
+            key: key.to_string(),
+            value: value.to_string(),
+            reason,
+        };
+
+        Ok(ProjectConfiguration {
             name: String::from(config_name),
             application_file: Self::parse_path_prefixes("application_files", contents),
             test_file: Self::parse_path_prefixes("test_files", contents),
             config_file: Self::parse_path_prefixes("config_files", contents),
             low_likelihood: Self::parse_low_likelihoods(contents),
             matches_if: Self::parse_matches_if(contents),
+            searched_files: Self::parse_search_exclusions(contents, invalid)?,
+            file_source: contents["file_source"]
+                .as_str()
+                .map(|v| v.parse().map_err(|e| invalid("file_source", v, e)))
+                .transpose()?,
+            scoring: Self::parse_scoring(&contents["scoring"]),
+        })
+    }
+
+    fn parse_scoring(contents: &Yaml) -> Scoring {
+        let weights = &contents["weights"];
+        let thresholds = &contents["thresholds"];
+        let default_weights = ScoreWeights::default();
+        let default_thresholds = ScoreThresholds::default();
+        let weight = |key: &str, default: u32| Self::parse_u32(&weights[key]).unwrap_or(default);
+
+        Scoring {
+            weights: ScoreWeights {
+                application: weight("application", default_weights.application),
+                config: weight("config", default_weights.config),
+                test: weight("test", default_weights.test),
+                unknown: weight("unknown", default_weights.unknown),
+                same_file: weight("same_file", default_weights.same_file),
+                other_definitions: weight("other_definitions", default_weights.other_definitions),
+                rule: weight("rule", default_weights.rule),
+                other_refs: weight("other_refs", default_weights.other_refs),
+                consumers: weight("consumers", default_weights.consumers),
+                public: weight("public", default_weights.public),
+                private: weight("private", default_weights.private),
+                test_only: weight("test_only", default_weights.test_only),
+                config_only: weight("config_only", default_weights.config_only),
+                kinds: match &weights["kinds"] {
+                    Yaml::Hash(kinds) => kinds
+                        .iter()
+                        .filter_map(|(k, v)| {
+                            Some((k.as_str()?.to_lowercase(), Self::parse_u32(v)?))
+                        })
+                        .collect(),
+                    _ => default_weights.kinds,
+                },
+            },
+            thresholds: ScoreThresholds {
+                high: Self::parse_u32(&thresholds["high"]).unwrap_or(default_thresholds.high),
+                medium: Self::parse_u32(&thresholds["medium"]).unwrap_or(default_thresholds.medium),
+            },
+        }
+    }
+
+    fn parse_u32(contents: &Yaml) -> Option<u32> {
+        contents
+            .as_i64()
+            .and_then(|v| std::convert::TryFrom::try_from(v).ok())
+    }
+
+    fn parse_search_exclusions<F>(
+        contents: &Yaml,
+        invalid: F,
+    ) -> Result<FileRestriction, ConfigurationError>
+    where
+        F: Fn(&str, &str, String) -> ConfigurationError,
+    {
+        let exclusions = &contents["search_exclusions"];
+
+        if exclusions.is_badvalue() {
+            return Ok(ProjectConfiguration::default_searched_files());
+        }
+
+        let languages: HashSet<Language> = Self::parse_strings("filetypes", exclusions)
+            .iter()
+            .map(|v| {
+                v.parse()
+                    .map_err(|e| invalid("search_exclusions.filetypes", v, e))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(FileRestriction {
+            languages: if languages.is_empty() {
+                LanguageRestriction::NoRestriction
+            } else {
+                LanguageRestriction::Except(languages)
+            },
+            excluded_globs: Self::parse_strings("globs", exclusions)
+                .iter()
+                .map(|v| {
+                    Glob::new(v)
+                        .map_err(|e| invalid("search_exclusions.globs", v, e.kind().to_string()))
+                })
+                .collect::<Result<_, _>>()?,
+            excluded_categories: Self::parse_strings("categories", exclusions)
+                .iter()
+                .map(|v| {
+                    v.parse::<FileCategory>()
+                        .map_err(|e| invalid("search_exclusions.categories", v, e))
+                })
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn parse_strings<'a>(key: &str, contents: &'a Yaml) -> Vec<&'a str> {
+        match &contents[key] {
+            Yaml::Array(items) => items.iter().filter_map(|v| v.as_str()).collect(),
+            _ => vec![],
         }
     }
 
@@ -108,9 +259,8 @@ impl ProjectConfigurations {
         match &contents[key] {
             Yaml::Array(items) => items
                 .iter()
-                .map(|v| v.as_str())
-                .filter_map(|v| v)
-                .map(|v| PathPrefix::new(v))
+                .filter_map(|v| v.as_str())
+                .map(PathPrefix::new)
                 .collect(),
             _ => vec![],
         }
@@ -120,8 +270,7 @@ impl ProjectConfigurations {
         match &contents["auto_low_likelihood"] {
             Yaml::Array(items) => items
                 .iter()
-                .map(|i| Self::parse_low_likelihood_item(i))
-                .filter_map(|i| i)
+                .filter_map(Self::parse_low_likelihood_item)
                 .collect(),
             _ => vec![],
         }
@@ -131,7 +280,7 @@ impl ProjectConfigurations {
         match &contents["matches_if"] {
             Yaml::Array(items) => items
                 .iter()
-                .flat_map(|i| Self::parse_individual_matches_if(i))
+                .flat_map(Self::parse_individual_matches_if)
                 .collect(),
             _ => vec![],
         }
@@ -140,11 +289,10 @@ impl ProjectConfigurations {
     fn parse_individual_matches_if(contents: &Yaml) -> Vec<Assertion> {
         SUPPORTED_ASSERTIONS
             .iter()
-            .map(|&k| match &contents[k] {
-                Yaml::String(v) => Self::parse_single_assertion(k, &v),
+            .filter_map(|&k| match &contents[k] {
+                Yaml::String(v) => Self::parse_single_assertion(k, v),
                 _ => None,
             })
-            .filter_map(|a| a)
             .collect()
     }
 
@@ -154,8 +302,7 @@ impl ProjectConfigurations {
                 name: name.to_string(),
                 matchers: SUPPORTED_ASSERTIONS
                     .iter()
-                    .map(|a| Self::parse_assertion_row(a, contents))
-                    .filter_map(|a| a)
+                    .filter_map(|a| Self::parse_assertion_row(a, contents))
                     .collect(),
             }),
             _ => None,
@@ -199,6 +346,12 @@ impl ProjectConfigurations {
             TOKEN_EQUALS => Some(Assertion::TokenAssertion(ValueMatcher::Equals(
                 val.to_string(),
             ))),
+            KIND => Some(Assertion::KindAssertion(ValueMatcher::Equals(
+                val.to_lowercase(),
+            ))),
+            LANGUAGE => Some(Assertion::LanguageAssertion(ValueMatcher::Equals(
+                val.to_lowercase(),
+            ))),
             _ => None,
         }
     }
@@ -211,6 +364,12 @@ impl ProjectConfigurations {
                     values,
                 )))
             }
+            KIND => Some(Assertion::KindAssertion(ValueMatcher::ExactMatchOnAnyOf(
+                val.iter().map(|v| v.to_lowercase()).collect(),
+            ))),
+            LANGUAGE => Some(Assertion::LanguageAssertion(
+                ValueMatcher::ExactMatchOnAnyOf(val.iter().map(|v| v.to_lowercase()).collect()),
+            )),
             _ => None,
         }
     }
@@ -228,6 +387,7 @@ impl ProjectConfigurations {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use codebase_files::FileSource;
     use totems::assert_contains;
 
     fn yaml_contents() -> String {
@@ -244,6 +404,28 @@ mod tests {
   - test/
   config_files:
   - priv/
+  file_source: git
+  auto_low_likelihood:
+    - name: Callbacks
+      language: Elixir
+      kind:
+      - callback
+      - Macro
+  scoring:
+    weights:
+      test: 40
+      kinds:
+        function: 10
+    thresholds:
+      high: 95
+  search_exclusions:
+    filetypes:
+    - json
+    globs:
+    - deps/**
+    categories:
+    - documentation
+    - fixtures
 - name: Rails
   application_files:
   - app/
@@ -281,7 +463,7 @@ mod tests {
 
     #[test]
     fn config_loads_from_yaml() {
-        let configs = ProjectConfigurations::parse(&yaml_contents());
+        let configs = ProjectConfigurations::parse(&yaml_contents()).unwrap();
 
         let rails_config = configs.get("Rails").unwrap();
         assert_eq!(
@@ -327,7 +509,7 @@ mod tests {
                 matchers: vec![
                     Assertion::PathAssertion(ValueMatcher::EndsWith(String::from(".rb"))),
                     Assertion::TokenAssertion(ValueMatcher::ExactMatchOnAnyOf(
-                        vec![
+                        [
                             String::from("new?"),
                             String::from("index?"),
                             String::from("show?")
@@ -374,5 +556,79 @@ mod tests {
         assert_eq!(phoenix_config.test_file, vec![PathPrefix::new("test/"),]);
 
         assert_eq!(phoenix_config.config_file, vec![PathPrefix::new("priv/"),]);
+
+        assert_eq!(
+            phoenix_config.low_likelihood,
+            vec![LowLikelihoodConfig {
+                name: String::from("Callbacks"),
+                matchers: vec![
+                    Assertion::KindAssertion(ValueMatcher::ExactMatchOnAnyOf(
+                        [String::from("callback"), String::from("macro")]
+                            .iter()
+                            .cloned()
+                            .collect()
+                    )),
+                    Assertion::LanguageAssertion(ValueMatcher::Equals(String::from("elixir"))),
+                ]
+            }]
+        );
+
+        assert_eq!(
+            phoenix_config.searched_files.to_string(),
+            "except JSON; excluding documentation, fixtures, deps/**"
+        );
+
+        assert_eq!(
+            phoenix_config.file_source,
+            Some(FileSource::Git {
+                include_untracked: false
+            })
+        );
+
+        assert_eq!(rails_config.file_source, None);
+
+        assert_eq!(phoenix_config.scoring.weights.test, 40);
+        assert_eq!(phoenix_config.scoring.weights.application, 60);
+        assert_eq!(
+            phoenix_config.scoring.weights.kinds.get("function"),
+            Some(&10)
+        );
+        assert_eq!(phoenix_config.scoring.thresholds.high, 95);
+        assert_eq!(phoenix_config.scoring.thresholds.medium, 50);
+        assert_eq!(rails_config.scoring, Scoring::default());
+
+        assert_eq!(
+            rails_config.searched_files,
+            ProjectConfiguration::default_searched_files()
+        );
+    }
+
+    #[test]
+    fn invalid_search_exclusions_name_the_entry() {
+        let parse = |exclusions: &str| {
+            ProjectConfigurations::parse(&format!(
+                "- name: Phoenix\n  search_exclusions:\n    {}\n",
+                exclusions
+            ))
+            .err()
+            .map(|e| e.to_string())
+        };
+
+        assert_eq!(
+            parse("globs: ['deps/[']"),
+            Some(String::from(
+                "Phoenix: invalid search_exclusions.globs entry \"deps/[\" (unclosed character class; missing ']')"
+            ))
+        );
+        assert_eq!(
+            parse("categories: [fixture]"),
+            Some(String::from(
+                "Phoenix: invalid search_exclusions.categories entry \"fixture\" (Unable to parse file category: fixture)"
+            ))
+        );
+        assert!(parse("filetypes: [notalanguage]")
+            .unwrap()
+            .starts_with("Phoenix: invalid search_exclusions.filetypes entry \"notalanguage\""));
+        assert_eq!(parse("globs: ['deps/**']"), None);
     }
 }
@@ -1,7 +1,9 @@
+use super::scoring::Scoring;
 use super::value_assertion::{Assertion, AssertionConflict};
+use codebase_files::FileSource;
 use std::default::Default;
 use std::path::Path;
-use token_search::{TokenSearchResult, TokenSearchResults};
+use token_search::{FileCategory, FileRestriction, Token, TokenSearchResult, TokenSearchResults};
 
 #[derive(Clone)]
 pub struct ProjectConfiguration {
@@ -11,6 +13,9 @@ pub struct ProjectConfiguration {
     pub config_file: Vec<PathPrefix>,
     pub low_likelihood: Vec<LowLikelihoodConfig>,
     pub matches_if: Vec<Assertion>,
+    pub searched_files: FileRestriction,
+    pub file_source: Option<FileSource>,
+    pub scoring: Scoring,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -43,7 +48,7 @@ impl LowLikelihoodConfig {
             Self::build_conflicts(self.token_assertions()).map(AssertionConflict::TokenConflict),
         ]
         .into_iter()
-        .filter_map(|v| v)
+        .flatten()
         .collect()
     }
 
@@ -58,7 +63,7 @@ impl LowLikelihoodConfig {
             .filter(|m| !m.matcher().full_equals())
             .collect();
 
-        if equals_assertions.len() > 0 && partial_equals_assertions.len() > 0 {
+        if !equals_assertions.is_empty() && !partial_equals_assertions.is_empty() {
             let mut results = equals_assertions.clone();
             results.extend(partial_equals_assertions.clone());
             Some(results.into_iter().map(|v| v.to_owned()).collect())
@@ -72,7 +77,9 @@ impl LowLikelihoodConfig {
             .iter()
             .filter(|m| match &m {
                 Assertion::PathAssertion(_) => true,
-                Assertion::TokenAssertion(_) => false,
+                Assertion::TokenAssertion(_)
+                | Assertion::KindAssertion(_)
+                | Assertion::LanguageAssertion(_) => false,
             })
             .collect::<Vec<&Assertion>>()
     }
@@ -82,7 +89,9 @@ impl LowLikelihoodConfig {
             .iter()
             .filter(|m| match &m {
                 Assertion::TokenAssertion(_) => true,
-                Assertion::PathAssertion(_) => false,
+                Assertion::PathAssertion(_)
+                | Assertion::KindAssertion(_)
+                | Assertion::LanguageAssertion(_) => false,
             })
             .collect::<Vec<&Assertion>>()
     }
@@ -97,11 +106,20 @@ impl Default for ProjectConfiguration {
             config_file: vec![],
             low_likelihood: vec![],
             matches_if: vec![],
+            searched_files: Self::default_searched_files(),
+            file_source: None,
+            scoring: Scoring::default(),
         }
     }
 }
 
 impl ProjectConfiguration {
+    /// Is the path a test file? Application file prefixes take precedence over test file prefixes
+    pub fn is_test_file(&self, path: &Path) -> bool {
+        !self.application_file.iter().any(|p| p.compare(path))
+            && self.test_file.iter().any(|p| p.compare(path))
+    }
+
     pub fn low_likelihood_match(
         &self,
         token_search_result: &TokenSearchResult,
@@ -112,12 +130,21 @@ impl ProjectConfiguration {
     }
 
     pub fn codebase_config_match(&self, results: &TokenSearchResults) -> bool {
-        self.matches_if.iter().all(|assertion| {
-            results
-                .value()
-                .iter()
-                .any(|result| assertion.matches(result))
-        })
+        let tokens: Vec<&Token> = results.value().iter().map(|r| &r.token).collect();
+        self.codebase_config_match_tokens(&tokens)
+    }
+
+    pub fn codebase_config_match_tokens(&self, tokens: &[&Token]) -> bool {
+        self.matches_if
+            .iter()
+            .all(|assertion| tokens.iter().any(|token| assertion.matches_token(token)))
+    }
+
+    pub fn default_searched_files() -> FileRestriction {
+        FileRestriction {
+            excluded_categories: [FileCategory::Documentation].iter().cloned().collect(),
+            ..Default::default()
+        }
     }
 }
 
@@ -157,7 +184,7 @@ mod tests {
         let ends_with = ValueMatcher::EndsWith("o".to_string());
         let equals = ValueMatcher::Equals("foo".to_string());
         let exact_match = ValueMatcher::ExactMatchOnAnyOf(
-            vec![String::from("foo"), String::from("bar")]
+            [String::from("foo"), String::from("bar")]
                 .iter()
                 .cloned()
                 .collect(),
@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+/// Points subtracted from a token's score (out of 100) for each signal of usage
+///
+/// `private` is the exception: those points are added to private tokens with no usage outside
+/// their definitions, since those are almost certainly dead.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScoreWeights {
+    pub application: u32,
+    pub config: u32,
+    pub test: u32,
+    pub unknown: u32,
+    pub same_file: u32,
+    /// Subtracted for each definition beyond the first
+    pub other_definitions: u32,
+    pub rule: u32,
+    pub other_refs: u32,
+    pub consumers: u32,
+    pub public: u32,
+    pub private: u32,
+    /// Subtracted once, in place of per-occurrence weights, when a token is only used in tests
+    pub test_only: u32,
+    /// Subtracted once, in place of per-occurrence weights, when a token is only used in config
+    pub config_only: u32,
+    pub kinds: HashMap<String, u32>,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        ScoreWeights {
+            application: 60,
+            config: 60,
+            test: 30,
+            unknown: 60,
+            same_file: 60,
+            other_definitions: 60,
+            rule: 100,
+            other_refs: 30,
+            consumers: 100,
+            public: 0,
+            private: 10,
+            test_only: 30,
+            config_only: 60,
+            kinds: HashMap::new(),
+        }
+    }
+}
+
+/// Minimum scores for high and medium likelihood; anything lower is low likelihood
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScoreThresholds {
+    pub high: u32,
+    pub medium: u32,
+}
+
+impl Default for ScoreThresholds {
+    fn default() -> Self {
+        ScoreThresholds {
+            high: 90,
+            medium: 50,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Scoring {
+    pub weights: ScoreWeights,
+    pub thresholds: ScoreThresholds,
+}
@@ -1,22 +1,35 @@
 use std::collections::HashSet;
-use token_search::TokenSearchResult;
+use token_search::{Token, TokenSearchResult};
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Assertion {
     PathAssertion(ValueMatcher),
     TokenAssertion(ValueMatcher),
+    KindAssertion(ValueMatcher),
+    LanguageAssertion(ValueMatcher),
 }
 
 impl Assertion {
     pub fn matches(&self, token_search_result: &TokenSearchResult) -> bool {
+        self.matches_token(&token_search_result.token)
+    }
+
+    pub fn matches_token(&self, token: &Token) -> bool {
         match self {
-            Assertion::PathAssertion(matcher) => token_search_result
-                .token
+            Assertion::PathAssertion(matcher) => token
                 .defined_paths
                 .iter()
                 .filter_map(|path| path.to_str())
                 .any(|path| matcher.check(path)),
-            Assertion::TokenAssertion(matcher) => matcher.check(&token_search_result.token.token),
+            Assertion::TokenAssertion(matcher) => matcher.check(&token.token),
+            Assertion::KindAssertion(matcher) => token
+                .definitions
+                .iter()
+                .any(|d| matcher.check(&d.kind.to_string())),
+            Assertion::LanguageAssertion(matcher) => token
+                .languages()
+                .iter()
+                .any(|l| matcher.check(&l.to_string().to_lowercase())),
         }
     }
 
@@ -24,6 +37,8 @@ impl Assertion {
         match self {
             Assertion::PathAssertion(matcher) => matcher,
             Assertion::TokenAssertion(matcher) => matcher,
+            Assertion::KindAssertion(matcher) => matcher,
+            Assertion::LanguageAssertion(matcher) => matcher,
         }
     }
 }
@@ -66,17 +81,19 @@ impl ValueMatcher {
     }
 
     pub fn full_equals(&self) -> bool {
-        match self {
-            ValueMatcher::Equals(_) => true,
-            ValueMatcher::ExactMatchOnAnyOf(_) => true,
-            _ => false,
-        }
+        matches!(
+            self,
+            ValueMatcher::Equals(_) | ValueMatcher::ExactMatchOnAnyOf(_)
+        )
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use read_ctags::{CtagItem, Language, TokenKind};
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
 
     fn foo() -> String {
         String::from("foo")
@@ -88,39 +105,73 @@ mod tests {
 
     #[test]
     fn matches_starts_with() {
-        assert!(ValueMatcher::StartsWith(foo()).check(&"foobar"));
-        assert!(!ValueMatcher::StartsWith(bar()).check(&"foobar"));
+        assert!(ValueMatcher::StartsWith(foo()).check("foobar"));
+        assert!(!ValueMatcher::StartsWith(bar()).check("foobar"));
     }
 
     #[test]
     fn matches_ends_with() {
-        assert!(ValueMatcher::EndsWith(bar()).check(&"foobar"));
-        assert!(!ValueMatcher::EndsWith(foo()).check(&"foobar"));
+        assert!(ValueMatcher::EndsWith(bar()).check("foobar"));
+        assert!(!ValueMatcher::EndsWith(foo()).check("foobar"));
     }
 
     #[test]
     fn matches_contains() {
-        assert!(ValueMatcher::Contains(bar()).check(&"barar"));
-        assert!(ValueMatcher::Contains(bar()).check(&"bar"));
-        assert!(ValueMatcher::Contains(bar()).check(&" bar"));
-        assert!(!ValueMatcher::Contains(bar()).check(&" "));
-        assert!(!ValueMatcher::Contains(bar()).check(&"nope"));
-        assert!(!ValueMatcher::Contains(bar()).check(&"ar"));
+        assert!(ValueMatcher::Contains(bar()).check("barar"));
+        assert!(ValueMatcher::Contains(bar()).check("bar"));
+        assert!(ValueMatcher::Contains(bar()).check(" bar"));
+        assert!(!ValueMatcher::Contains(bar()).check(" "));
+        assert!(!ValueMatcher::Contains(bar()).check("nope"));
+        assert!(!ValueMatcher::Contains(bar()).check("ar"));
     }
 
     #[test]
     fn matches_any_of() {
-        let values: HashSet<_> = vec![foo(), bar()].iter().cloned().collect();
+        let values: HashSet<_> = [foo(), bar()].iter().cloned().collect();
 
-        assert!(ValueMatcher::ExactMatchOnAnyOf(values.clone()).check(&"foo"));
-        assert!(ValueMatcher::ExactMatchOnAnyOf(values.clone()).check(&"bar"));
-        assert!(!ValueMatcher::ExactMatchOnAnyOf(values.clone()).check(&"foobar"));
+        assert!(ValueMatcher::ExactMatchOnAnyOf(values.clone()).check("foo"));
+        assert!(ValueMatcher::ExactMatchOnAnyOf(values.clone()).check("bar"));
+        assert!(!ValueMatcher::ExactMatchOnAnyOf(values.clone()).check("foobar"));
     }
 
     #[test]
     fn matches_capital() {
-        assert!(ValueMatcher::StartsWithCapital.check(&"Foo"));
-        assert!(!ValueMatcher::StartsWithCapital.check(&"foo"));
+        assert!(ValueMatcher::StartsWithCapital.check("Foo"));
+        assert!(!ValueMatcher::StartsWithCapital.check("foo"));
+    }
+
+    #[test]
+    fn matches_kind_and_language() {
+        let token = Token::new(
+            String::from("handle_info"),
+            vec![CtagItem {
+                name: String::from("handle_info"),
+                file_path: PathBuf::from("lib/app/worker.ex"),
+                address: String::from("1"),
+                language: Some(Language::Elixir),
+                tags: BTreeMap::new(),
+                kind: TokenKind::Callback,
+            }]
+            .into_iter()
+            .collect(),
+        );
+
+        assert!(
+            Assertion::KindAssertion(ValueMatcher::Equals(String::from("callback")))
+                .matches_token(&token)
+        );
+        assert!(
+            !Assertion::KindAssertion(ValueMatcher::Equals(String::from("field")))
+                .matches_token(&token)
+        );
+        assert!(
+            Assertion::LanguageAssertion(ValueMatcher::Equals(String::from("elixir")))
+                .matches_token(&token)
+        );
+        assert!(
+            !Assertion::LanguageAssertion(ValueMatcher::Equals(String::from("ruby")))
+                .matches_token(&token)
+        );
     }
 
     #[test]
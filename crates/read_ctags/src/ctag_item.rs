@@ -4,6 +4,7 @@ use super::tag_program::TagProgram;
 use super::tags::Tags;
 use super::tags_file::TagsFile;
 use super::token_kind::TokenKind;
+use super::visibility::Visibility;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
@@ -77,6 +78,71 @@ impl CtagItem {
         }
     }
 
+    /// Visibility of the tag, if known
+    pub fn visibility(&self) -> Option<Visibility> {
+        Visibility::from_ctag(self)
+    }
+
+    /// The 1-based line number of the tag, if known without reading the file
+    ///
+    /// This uses the `line` field (present when ctags is run with `--fields=+n`) or a numeric
+    /// address.
+    pub fn line(&self) -> Option<usize> {
+        self.tags
+            .get("line")
+            .and_then(|v| v.parse().ok())
+            .or_else(|| self.address.parse().ok())
+    }
+
+    /// The 1-based last line of the tag's definition, from the `end` field (present when ctags
+    /// is run with `--fields=+e`)
+    pub fn end_line(&self) -> Option<usize> {
+        self.tags.get("end").and_then(|v| v.parse().ok())
+    }
+
+    /// The 1-based line number of the tag, searching the file's contents for pattern addresses
+    pub fn resolve_line(&self, contents: &str) -> Option<usize> {
+        self.line().or_else(|| {
+            self.address_pattern()?;
+
+            contents
+                .lines()
+                .position(|line| self.address_matches(line))
+                .map(|idx| idx + 1)
+        })
+    }
+
+    /// Does the line match the tag's pattern address? Numeric addresses match any line.
+    pub fn address_matches(&self, line: &str) -> bool {
+        let (anchored_start, pattern, anchored_end) = match self.address_pattern() {
+            Some(pattern) => pattern,
+            None => return true,
+        };
+
+        match (anchored_start, anchored_end) {
+            (true, true) => line == pattern,
+            (true, false) => line.starts_with(&pattern),
+            (false, true) => line.ends_with(&pattern),
+            (false, false) => line.contains(&pattern),
+        }
+    }
+
+    /// Pattern address, unescaped, along with whether it's anchored to the start and end
+    pub(crate) fn address_pattern(&self) -> Option<(bool, String, bool)> {
+        let pattern = self.address.strip_prefix('/')?.strip_suffix('/')?;
+        let pattern = pattern.replace("\\/", "/").replace("\\\\", "\\");
+        let (anchored_start, pattern) = match pattern.strip_prefix('^') {
+            Some(rest) => (true, rest),
+            None => (false, pattern.as_str()),
+        };
+        let (anchored_end, pattern) = match pattern.strip_suffix('$') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+
+        Some((anchored_start, pattern.to_string(), anchored_end))
+    }
+
     /// encode a `CtagItem` into its line representation within a tags file
     pub fn encode(&self) -> String {
         let tags = self
@@ -134,3 +200,42 @@ fn bidirectional_encoding() {
         );
     }
 }
+
+#[test]
+fn resolves_lines() {
+    let parse = |line: &str| {
+        CtagItem::parse_input(line)
+            .unwrap()
+            .1
+            .into_iter()
+            .next()
+            .unwrap()
+    };
+    let contents = "class Person\n  def name\n  end\n\n  def path\n    \"a/b\"\n  end\nend\n";
+
+    assert_eq!(parse("Person\tapp/person.rb\t1;\"\tc").line(), Some(1));
+    assert_eq!(
+        parse("Person\tapp/person.rb\t1;\"\tc\tend:8").end_line(),
+        Some(8)
+    );
+    assert_eq!(parse("Person\tapp/person.rb\t1;\"\tc").end_line(), None);
+    assert_eq!(
+        parse("name\tapp/person.rb\t/^  def name$/;\"\tf\tline:2").line(),
+        Some(2)
+    );
+    assert_eq!(
+        parse("path\tapp/person.rb\t/^  def path$/;\"\tf").resolve_line(contents),
+        Some(5)
+    );
+    assert_eq!(
+        parse("ab\tapp/person.rb\t/^    \"a\\/b\"$/;\"\tv").resolve_line(contents),
+        Some(6)
+    );
+    assert_eq!(
+        parse("gone\tapp/person.rb\t/^  def gone$/;\"\tf").resolve_line(contents),
+        None
+    );
+    assert!(parse("path\tapp/person.rb\t/^  def path$/;\"\tf").address_matches("  def path"));
+    assert!(!parse("path\tapp/person.rb\t/^  def path$/;\"\tf").address_matches("  def paths"));
+    assert!(parse("Person\tapp/person.rb\t1;\"\tc").address_matches("anything"));
+}
@@ -55,6 +55,20 @@ impl Language {
         }
     }
 
+    /// Markers which begin a comment within a line of source
+    pub fn comment_markers(&self) -> &'static [&'static str] {
+        match self {
+            Language::CSS => &["/*"],
+            Language::Elixir | Language::Python | Language::Ruby | Language::Sh => &["#"],
+            Language::Elm => &["--"],
+            Language::HTML | Language::Markdown | Language::SVG | Language::XML => &["<!--"],
+            Language::JSON => &[],
+            Language::JavaScript | Language::Rust | Language::SCSS | Language::TypeScript => {
+                &["//", "/*"]
+            }
+        }
+    }
+
     /// All file extensions supported
     pub fn extensions() -> Vec<&'static str> {
         vec![
@@ -112,10 +126,7 @@ impl FromStr for Language {
             "tsx" => Ok(Language::TypeScript),
             "xml" => Ok(Language::XML),
             "" => Ok(Language::Sh),
-            ext => Err(String::from(format!(
-                "Unable to parse file extension: {}",
-                ext
-            ))),
+            ext => Err(format!("Unable to parse file extension: {}", ext)),
         }
     }
 }
@@ -27,6 +27,7 @@ mod tags;
 mod tags_file;
 mod tags_reader;
 mod token_kind;
+mod visibility;
 
 pub use self::ctag_item::*;
 pub use self::language::*;
@@ -34,3 +35,4 @@ pub use self::tags::*;
 pub use self::tags_file::*;
 pub use self::tags_reader::*;
 pub use self::token_kind::*;
+pub use self::visibility::*;
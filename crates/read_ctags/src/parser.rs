@@ -44,13 +44,10 @@ fn tags_body(input: &str) -> IResult<&str, HashSet<CtagItem>> {
 }
 
 fn is_kind(field: &ParsedField) -> bool {
-    match field {
-        ParsedField::KindField(_) => true,
-        _ => false,
-    }
+    matches!(field, ParsedField::KindField(_))
 }
 
-fn key_value_parser(input: &str) -> IResult<&str, ParsedField> {
+fn key_value_parser(input: &str) -> IResult<&str, ParsedField<'_>> {
     map(
         separated_pair(
             alphanumeric1,
@@ -61,21 +58,21 @@ fn key_value_parser(input: &str) -> IResult<&str, ParsedField> {
     )(input)
 }
 
-fn kind_parser(input: &str) -> IResult<&str, ParsedField> {
+fn kind_parser(input: &str) -> IResult<&str, ParsedField<'_>> {
     map(anychar, ParsedField::KindField)(input)
 }
 
-fn fields_parser(input: &str) -> IResult<&str, Vec<ParsedField>> {
+fn fields_parser(input: &str) -> IResult<&str, Vec<ParsedField<'_>>> {
     let field_parser = alt((key_value_parser, kind_parser));
     preceded(tag("\t"), separated_list(tag("\t"), field_parser))(input)
 }
 
-fn address_and_fields_parser(input: &str) -> IResult<&str, (String, Vec<ParsedField>)> {
+fn address_and_fields_parser(input: &str) -> IResult<&str, (String, Vec<ParsedField<'_>>)> {
     alt((
         tuple((tag_address_parser, fields_parser)),
         tuple((
             tag_address_without_fields_parser,
-            internal::succeed(|| vec![]),
+            internal::succeed(Vec::new),
         )),
     ))(input)
 }
@@ -133,7 +130,7 @@ fn build_kind_and_fields<'a>(
         };
     }
 
-    match (kind.len(), kind.get(0)) {
+    match (kind.len(), kind.first()) {
         (1, Some(ParsedField::KindField(c))) => (TokenKind::from_ctag(language, *c), hash),
         (_, _) => (TokenKind::Undefined, hash),
     }
@@ -141,7 +138,7 @@ fn build_kind_and_fields<'a>(
 
 #[test]
 fn parses_without_metadata() {
-    let result: Tags = vec![CtagItem {
+    let result: Tags = [CtagItem {
         name: String::from("withInfo"),
         file_path: PathBuf::from("path/to/file.rb"),
         address: String::from("45"),
@@ -215,7 +212,7 @@ fn parses_multiple_lines() {
             "",
             (
                 TagProgram::default(),
-                vec![
+                [
                     CtagItem {
                         name: String::from("first"),
                         file_path: PathBuf::from("path/to/file.rb"),
@@ -2,12 +2,11 @@ use super::CtagItem;
 use serde::{Deserialize, Serialize};
 use std::collections::hash_set::{IntoIter, Iter};
 use std::collections::HashSet;
-use std::default::Default;
 use std::iter::FromIterator;
 use std::path::PathBuf;
 
 /// Wrapper for tags values
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct Tags(HashSet<CtagItem>);
 
 impl Tags {
@@ -17,7 +16,7 @@ impl Tags {
     }
 
     /// Carry iter() from HashSet
-    pub fn iter(&self) -> Iter<CtagItem> {
+    pub fn iter(&self) -> Iter<'_, CtagItem> {
         self.0.iter()
     }
 
@@ -26,6 +25,11 @@ impl Tags {
         self.0.len()
     }
 
+    /// Delegate is_empty() to HashSet
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     /// Remove all tags associated to a specific path
     pub fn remove_at_path(&mut self, path: &PathBuf) {
         self.0.retain(|item| &item.file_path != path)
@@ -44,12 +48,6 @@ impl Tags {
     }
 }
 
-impl Default for Tags {
-    fn default() -> Self {
-        Tags(HashSet::new())
-    }
-}
-
 impl IntoIterator for Tags {
     type Item = CtagItem;
     type IntoIter = IntoIter<Self::Item>;
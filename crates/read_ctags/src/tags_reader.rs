@@ -7,7 +7,7 @@ use std::fs::File;
 use std::io;
 use std::io::prelude::*;
 use std::io::Error;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 /// TagsReader provides a mechanism for attempting to read multiple ctags files until the first is
@@ -51,8 +51,9 @@ impl Display for ReadCtagsError {
     }
 }
 
-fn git_path() -> Option<PathBuf> {
+fn git_path(root: &Path) -> Option<PathBuf> {
     match Command::new("git")
+        .current_dir(root)
         .arg("rev-parse")
         .arg("--git-dir")
         .output()
@@ -76,30 +77,37 @@ fn cwd_tags_paths(cwd: PathBuf) -> Vec<PathBuf> {
 
 impl Default for TagsReader {
     fn default() -> Self {
+        match current_dir() {
+            Ok(current_dir) => Self::within(&current_dir),
+            Err(_) => TagsReader { filenames: vec![] },
+        }
+    }
+}
+
+impl TagsReader {
+    /// Look for tags files as if run from within the provided directory
+    pub fn within(root: &Path) -> Self {
+        let root = &root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
         let mut filenames = vec![];
 
-        if let Ok(current_dir) = current_dir() {
-            if let Some(app_git_path) = git_path() {
-                if app_git_path == PathBuf::from(".git") {
-                    filenames.push(current_dir.join(app_git_path).join("tags"));
-                    filenames.extend(cwd_tags_paths(current_dir));
-                } else {
-                    filenames.extend(cwd_tags_paths(current_dir));
-                    filenames.push(app_git_path.join("tags"));
-                    filenames.push(app_git_path.join("../tags"));
-                    filenames.push(app_git_path.join("../tmp/tags"));
-                }
+        if let Some(app_git_path) = git_path(root) {
+            if app_git_path == Path::new(".git") {
+                filenames.push(root.join(app_git_path).join("tags"));
+                filenames.extend(cwd_tags_paths(root.to_path_buf()));
             } else {
-                filenames.push(current_dir.join("tags"));
-                filenames.push(current_dir.join("tmp/tags"));
+                let app_git_path = root.join(app_git_path);
+                filenames.extend(cwd_tags_paths(root.to_path_buf()));
+                filenames.push(app_git_path.join("tags"));
+                filenames.push(app_git_path.join("../tags"));
+                filenames.push(app_git_path.join("../tmp/tags"));
             }
+        } else {
+            filenames.extend(cwd_tags_paths(root.to_path_buf()));
         }
 
         TagsReader { filenames }
     }
-}
 
-impl TagsReader {
     /// Loads and parses the first tags file it finds
     pub fn load(&self) -> Result<TagsFile, ReadCtagsError> {
         self.read().and_then(|(ctags_path, contents)| {
@@ -116,7 +124,7 @@ impl TagsReader {
     fn read(&self) -> Result<(PathBuf, String), ReadCtagsError> {
         Self::first_success(
             &self.filenames,
-            Error::new(io::ErrorKind::Other, "No file provided"),
+            Error::other("No file provided"),
             read_to_string_lossy,
         )
         .map_err(|e| ReadCtagsError::NoCtagsFile(self.filenames.clone(), e))
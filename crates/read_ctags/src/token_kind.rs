@@ -1,5 +1,6 @@
 use super::language::Language;
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
 /// TokenKind is an enum which represents different types of tokens
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
@@ -216,6 +217,47 @@ impl TokenKind {
             })
     }
 
+    /// How specifically the kind identifies a definition, where lower is more specific
+    ///
+    /// Types rank ahead of callables, which rank ahead of other named values; variables and
+    /// unrecognized kinds rank last.
+    pub fn specificity(&self) -> u8 {
+        match self {
+            TokenKind::Class
+            | TokenKind::Module
+            | TokenKind::Struct
+            | TokenKind::Protocol
+            | TokenKind::Interface
+            | TokenKind::Enum
+            | TokenKind::Exception
+            | TokenKind::Record
+            | TokenKind::Type
+            | TokenKind::Typedef
+            | TokenKind::Namespace
+            | TokenKind::Implementation
+            | TokenKind::Mixin => 0,
+            TokenKind::Method
+            | TokenKind::SingletonMethod
+            | TokenKind::Function
+            | TokenKind::Macro
+            | TokenKind::Callback
+            | TokenKind::Delegate
+            | TokenKind::Guard
+            | TokenKind::Getter
+            | TokenKind::Setter
+            | TokenKind::Generator
+            | TokenKind::Constructor
+            | TokenKind::Operator => 1,
+            TokenKind::Variable
+            | TokenKind::Local
+            | TokenKind::Parameter
+            | TokenKind::Undefined
+            | TokenKind::MissingLanguageToken(_, _)
+            | TokenKind::Unknown(_) => 3,
+            _ => 2,
+        }
+    }
+
     /// Calculate the character given an optional language and kind
     pub fn to_token_char(&self, lang: Option<Language>) -> char {
         match *self {
@@ -234,6 +276,92 @@ impl TokenKind {
     }
 }
 
+impl std::fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TokenKind::Class => write!(f, "class"),
+            TokenKind::Id => write!(f, "id"),
+            TokenKind::Selector => write!(f, "selector"),
+            TokenKind::Macro => write!(f, "macro"),
+            TokenKind::Callback => write!(f, "callback"),
+            TokenKind::Delegate => write!(f, "delegate"),
+            TokenKind::Exception => write!(f, "exception"),
+            TokenKind::Function => write!(f, "function"),
+            TokenKind::Guard => write!(f, "guard"),
+            TokenKind::Implementation => write!(f, "implementation"),
+            TokenKind::Module => write!(f, "module"),
+            TokenKind::Operator => write!(f, "operator"),
+            TokenKind::Protocol => write!(f, "protocol"),
+            TokenKind::Record => write!(f, "record"),
+            TokenKind::Test => write!(f, "test"),
+            TokenKind::Type => write!(f, "type"),
+            TokenKind::Alias => write!(f, "alias"),
+            TokenKind::Constructor => write!(f, "constructor"),
+            TokenKind::Namespace => write!(f, "namespace"),
+            TokenKind::Port => write!(f, "port"),
+            TokenKind::Stylesheet => write!(f, "stylesheet"),
+            TokenKind::Script => write!(f, "script"),
+            TokenKind::Anchor => write!(f, "anchor"),
+            TokenKind::Heading1 => write!(f, "heading1"),
+            TokenKind::Heading2 => write!(f, "heading2"),
+            TokenKind::Heading3 => write!(f, "heading3"),
+            TokenKind::Array => write!(f, "array"),
+            TokenKind::Boolean => write!(f, "boolean"),
+            TokenKind::Number => write!(f, "number"),
+            TokenKind::Object => write!(f, "object"),
+            TokenKind::String => write!(f, "string"),
+            TokenKind::Null => write!(f, "null"),
+            TokenKind::Constant => write!(f, "constant"),
+            TokenKind::Getter => write!(f, "getter"),
+            TokenKind::Setter => write!(f, "setter"),
+            TokenKind::Generator => write!(f, "generator"),
+            TokenKind::Method => write!(f, "method"),
+            TokenKind::Property => write!(f, "property"),
+            TokenKind::Variable => write!(f, "variable"),
+            TokenKind::Subsection => write!(f, "subsection"),
+            TokenKind::L4Subsection => write!(f, "l4_subsection"),
+            TokenKind::Chapter => write!(f, "chapter"),
+            TokenKind::Section => write!(f, "section"),
+            TokenKind::SubSubsection => write!(f, "sub_subsection"),
+            TokenKind::L5Subsection => write!(f, "l5_subsection"),
+            TokenKind::Member => write!(f, "member"),
+            TokenKind::SingletonMethod => write!(f, "singleton_method"),
+            TokenKind::RSpecDescribe => write!(f, "rspec_describe"),
+            TokenKind::Field => write!(f, "field"),
+            TokenKind::Struct => write!(f, "struct"),
+            TokenKind::Typedef => write!(f, "typedef"),
+            TokenKind::Placeholder => write!(f, "placeholder"),
+            TokenKind::Mixin => write!(f, "mixin"),
+            TokenKind::Parameter => write!(f, "parameter"),
+            TokenKind::Heredoc => write!(f, "heredoc"),
+            TokenKind::Def => write!(f, "def"),
+            TokenKind::Enumerator => write!(f, "enumerator"),
+            TokenKind::Enum => write!(f, "enum"),
+            TokenKind::Interface => write!(f, "interface"),
+            TokenKind::Local => write!(f, "local"),
+            TokenKind::NSPrefix => write!(f, "ns_prefix"),
+            TokenKind::Root => write!(f, "root"),
+            TokenKind::Undefined => write!(f, "undefined"),
+            TokenKind::MissingLanguageToken(_, _) | TokenKind::Unknown(_) => write!(f, "unknown"),
+        }
+    }
+}
+
+impl FromStr for TokenKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.to_lowercase();
+
+        LOOKUP
+            .iter()
+            .map(|(_, _, kind)| *kind)
+            .chain(std::iter::once(TokenKind::Undefined))
+            .find(|kind| kind.to_string() == name)
+            .ok_or_else(|| format!("Unable to parse token kind: {}", s))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -247,4 +375,15 @@ mod tests {
             assert_eq!(c, TokenKind::from_ctag(None, c).to_token_char(None));
         }
     }
+
+    #[test]
+    fn names_round_trip() {
+        for (_, _, kind) in LOOKUP.iter() {
+            assert_eq!(kind.to_string().parse::<TokenKind>(), Ok(*kind));
+        }
+
+        assert_eq!(TokenKind::SingletonMethod.to_string(), "singleton_method");
+        assert_eq!("Field".parse::<TokenKind>(), Ok(TokenKind::Field));
+        assert!("bogus".parse::<TokenKind>().is_err());
+    }
 }
@@ -0,0 +1,174 @@
+use super::ctag_item::CtagItem;
+use super::language::Language;
+use super::token_kind::TokenKind;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// Visibility of a tag, when ctags (or language convention) provides it
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum Visibility {
+    /// Accessible from anywhere
+    Public,
+    /// Accessible from the defining type and its descendants
+    Protected,
+    /// Accessible only from where it's defined
+    Private,
+}
+
+impl Visibility {
+    /// Determine visibility from the `access` field or, without it, language convention
+    ///
+    /// Python names with a single leading underscore are treated as private. Rust declarations
+    /// are read from the pattern address: `pub` items are public and other items private, except
+    /// restricted visibility (`pub(crate)`), enum variants, and items scoped to a trait or an
+    /// `impl` block, where a missing `pub` doesn't mean the item is private.
+    pub fn from_ctag(item: &CtagItem) -> Option<Visibility> {
+        match item.tags.get("access").map(|a| a.as_str()) {
+            Some("public") | Some("export") => Some(Visibility::Public),
+            Some("protected") => Some(Visibility::Protected),
+            Some("private") => Some(Visibility::Private),
+            _ => match item.language {
+                Some(Language::Python)
+                    if item.name.starts_with('_') && !item.name.starts_with("__") =>
+                {
+                    Some(Visibility::Private)
+                }
+                Some(Language::Rust) => Self::from_rust_declaration(item),
+                _ => None,
+            },
+        }
+    }
+
+    fn from_rust_declaration(item: &CtagItem) -> Option<Visibility> {
+        let (_, pattern, _) = item.address_pattern()?;
+        let declaration = pattern.trim_start();
+
+        if declaration.starts_with("pub(") {
+            None
+        } else if declaration.starts_with("pub ") {
+            Some(Visibility::Public)
+        } else if item.kind == TokenKind::Enumerator
+            || item.tags.contains_key("interface")
+            || item.tags.contains_key("implementation")
+        {
+            None
+        } else {
+            Some(Visibility::Private)
+        }
+    }
+}
+
+impl Display for Visibility {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Visibility::Public => write!(f, "public"),
+            Visibility::Protected => write!(f, "protected"),
+            Visibility::Private => write!(f, "private"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    fn build_item(name: &str, language: Language, access: Option<&str>) -> CtagItem {
+        let mut tags = BTreeMap::new();
+        if let Some(access) = access {
+            tags.insert(String::from("access"), access.to_string());
+        }
+
+        CtagItem {
+            name: name.to_string(),
+            file_path: PathBuf::from("src/lib"),
+            address: String::from("1"),
+            language: Some(language),
+            tags,
+            kind: TokenKind::Method,
+        }
+    }
+
+    #[test]
+    fn reads_access_field_and_language_conventions() {
+        assert_eq!(
+            Visibility::from_ctag(&build_item("name", Language::Ruby, Some("private"))),
+            Some(Visibility::Private)
+        );
+        assert_eq!(
+            Visibility::from_ctag(&build_item("name", Language::TypeScript, Some("public"))),
+            Some(Visibility::Public)
+        );
+        assert_eq!(
+            Visibility::from_ctag(&build_item("_name", Language::Python, None)),
+            Some(Visibility::Private)
+        );
+        assert_eq!(
+            Visibility::from_ctag(&build_item("__init__", Language::Python, None)),
+            None
+        );
+        assert_eq!(
+            Visibility::from_ctag(&build_item("name", Language::Ruby, None)),
+            None
+        );
+    }
+
+    #[test]
+    fn reads_rust_visibility_from_the_declaration() {
+        let rust_item = |declaration: &str, kind: TokenKind, scope: Option<(&str, &str)>| {
+            let mut item = build_item("name", Language::Rust, None);
+            item.address = format!("/^{}$/", declaration);
+            item.kind = kind;
+            if let Some((key, value)) = scope {
+                item.tags.insert(key.to_string(), value.to_string());
+            }
+            Visibility::from_ctag(&item)
+        };
+
+        assert_eq!(
+            rust_item("pub fn name() {}", TokenKind::Function, None),
+            Some(Visibility::Public)
+        );
+        assert_eq!(
+            rust_item(
+                "    pub fn name(&self) {}",
+                TokenKind::Method,
+                Some(("implementation", "W"))
+            ),
+            Some(Visibility::Public)
+        );
+        assert_eq!(
+            rust_item("fn name() {}", TokenKind::Function, None),
+            Some(Visibility::Private)
+        );
+        assert_eq!(
+            rust_item("    name: usize,", TokenKind::Field, Some(("struct", "W"))),
+            Some(Visibility::Private)
+        );
+        assert_eq!(
+            rust_item("pub(crate) fn name() {}", TokenKind::Function, None),
+            None
+        );
+        assert_eq!(
+            rust_item(
+                "    fn name(&self) {}",
+                TokenKind::Method,
+                Some(("implementation", "W"))
+            ),
+            None
+        );
+        assert_eq!(
+            rust_item(
+                "    fn name(&self);",
+                TokenKind::Method,
+                Some(("interface", "T"))
+            ),
+            None
+        );
+        assert_eq!(
+            rust_item("    Name,", TokenKind::Enumerator, Some(("enum", "E"))),
+            None
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const DAY: u64 = 86_400;
+
+/// A length of time, written as a number and a unit: `d` (days), `w` (weeks), `m` (30-day
+/// months), or `y` (365-day years), e.g. `180d`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Age {
+    amount: u64,
+    unit: char,
+}
+
+impl Age {
+    pub fn seconds(&self) -> u64 {
+        let unit = match self.unit {
+            'w' => 7 * DAY,
+            'm' => 30 * DAY,
+            'y' => 365 * DAY,
+            _ => DAY,
+        };
+
+        self.amount.saturating_mul(unit)
+    }
+
+    /// Was the provided time (in seconds since the epoch) at least this long ago?
+    pub fn has_passed_since(&self, timestamp: u64) -> bool {
+        timestamp.saturating_add(self.seconds()) <= now()
+    }
+}
+
+/// Seconds since the epoch
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Seconds since the epoch, formatted as an RFC 3339 date and time in UTC
+pub fn format_timestamp(timestamp: u64) -> String {
+    humantime::format_rfc3339_seconds(UNIX_EPOCH + Duration::from_secs(timestamp)).to_string()
+}
+
+impl FromStr for Age {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        let unit = s.chars().last().filter(|c| "dwmy".contains(*c));
+
+        match unit.and_then(|u| s[..s.len() - 1].parse().ok().map(|amount| (amount, u))) {
+            Some((amount, unit)) => Ok(Age { amount, unit }),
+            None => Err(format!(
+                "Unable to parse age: {} (expected e.g. 180d, 6w, 3m, or 2y)",
+                s
+            )),
+        }
+    }
+}
+
+impl Display for Age {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}{}", self.amount, self.unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ages_with_units() {
+        assert_eq!("180d".parse::<Age>().map(|a| a.seconds()), Ok(180 * DAY));
+        assert_eq!("2W".parse::<Age>().map(|a| a.seconds()), Ok(14 * DAY));
+        assert_eq!("1y".parse::<Age>().map(|a| a.to_string()), Ok("1y".into()));
+        assert!("180".parse::<Age>().is_err());
+        assert!("d".parse::<Age>().is_err());
+        assert!("-3d".parse::<Age>().is_err());
+
+        let age: Age = "30d".parse().unwrap();
+        assert!(age.has_passed_since(now() - 31 * DAY));
+        assert!(!age.has_passed_since(now() - 29 * DAY));
+    }
+
+    #[test]
+    fn formats_timestamps() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(1_577_836_800), "2020-01-01T00:00:00Z");
+    }
+}
@@ -1,5 +1,11 @@
-use super::usage_likelihood::UsageLikelihoodStatus;
-use project_configuration::{Assertion, ValueMatcher};
+use super::age::Age;
+use super::baseline::Baseline;
+use super::token_usage::TokenUsage;
+use super::usage_likelihood::{UsageLikelihood, UsageLikelihoodStatus};
+use super::usage_likelihood_reason::UsageScope;
+use project_configuration::{Allowlist, Assertion, ValueMatcher};
+use read_ctags::TokenKind;
+use std::collections::HashSet;
 use std::default::Default;
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
@@ -7,25 +13,42 @@ use token_search::TokenSearchResult;
 
 pub struct AnalysisFilter {
     pub usage_likelihood_filter: Vec<UsageLikelihoodStatus>,
-    pub sort_order: SortOrder,
+    /// Sort keys, applied in order; ties are broken by token name
+    pub sort_order: Vec<SortOrder>,
+    /// When non-empty, limit findings to tokens used only within these scopes
+    pub used_only_in: Vec<UsageScope>,
+    /// When non-empty, limit findings to tokens defined as one of these kinds
+    pub kinds: Vec<TokenKind>,
+    /// Include tokens suppressed by a comment at their definition
+    pub include_suppressed: bool,
+    /// Limit findings to tokens whose definitions were last modified at least this long ago
+    pub older_than: Option<Age>,
     ignored_by_path: Vec<Assertion>,
+    limited_to_tokens: Option<HashSet<String>>,
+    baseline: Option<Baseline>,
+    allowlist: Allowlist,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum SortOrder {
     Ascending(OrderField),
     Descending(OrderField),
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
 pub enum OrderField {
+    #[default]
     Token,
     File,
-}
-
-impl Default for OrderField {
-    fn default() -> Self {
-        OrderField::Token
-    }
+    Directory,
+    Occurrences,
+    Likelihood,
+    Kind,
+    Language,
+    Definitions,
+    Score,
+    Age,
+    Size,
 }
 
 impl FromStr for OrderField {
@@ -35,33 +58,63 @@ impl FromStr for OrderField {
         match s.to_lowercase().as_ref() {
             "file" => Ok(OrderField::File),
             "token" => Ok(OrderField::Token),
-            val => Err(String::from(format!("Unable to parse order: {}", val))),
+            "directory" | "dir" => Ok(OrderField::Directory),
+            "occurrences" => Ok(OrderField::Occurrences),
+            "likelihood" => Ok(OrderField::Likelihood),
+            "kind" => Ok(OrderField::Kind),
+            "language" => Ok(OrderField::Language),
+            "definitions" => Ok(OrderField::Definitions),
+            "score" => Ok(OrderField::Score),
+            "age" => Ok(OrderField::Age),
+            "size" | "lines" => Ok(OrderField::Size),
+            val => Err(format!("Unable to parse order: {}", val)),
         }
     }
 }
 
-impl AnalysisFilter {
-    pub fn set_order_field(&mut self, field: OrderField) {
-        match self.sort_order {
-            SortOrder::Ascending(_) => self.sort_order = SortOrder::Ascending(field),
-            SortOrder::Descending(_) => self.sort_order = SortOrder::Descending(field),
+impl SortOrder {
+    pub fn field(&self) -> OrderField {
+        match self {
+            SortOrder::Ascending(field) | SortOrder::Descending(field) => *field,
         }
     }
 
-    pub fn set_order_ascending(&mut self) {
-        match &self.sort_order {
-            SortOrder::Descending(field) => self.sort_order = SortOrder::Ascending(*field),
-            _ => (),
+    pub fn reversed(&self) -> SortOrder {
+        match self {
+            SortOrder::Ascending(field) => SortOrder::Descending(*field),
+            SortOrder::Descending(field) => SortOrder::Ascending(*field),
+        }
+    }
+}
+
+/// Parses `field`, `field:asc`, or `field:desc`
+impl FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, ':');
+        let field = parts.next().unwrap_or_default().parse()?;
+
+        match parts.next().map(|d| d.to_lowercase()).as_deref() {
+            None | Some("asc") => Ok(SortOrder::Ascending(field)),
+            Some("desc") => Ok(SortOrder::Descending(field)),
+            Some(val) => Err(format!("Unable to parse sort direction: {}", val)),
         }
     }
+}
 
-    pub fn set_order_descending(&mut self) {
-        match &self.sort_order {
-            SortOrder::Ascending(field) => self.sort_order = SortOrder::Descending(*field),
-            _ => (),
+impl AnalysisFilter {
+    pub fn set_sort_order(&mut self, sort_order: Vec<SortOrder>) {
+        if !sort_order.is_empty() {
+            self.sort_order = sort_order;
         }
     }
 
+    /// Flip the direction of every sort key
+    pub fn reverse_sort_order(&mut self) {
+        self.sort_order = self.sort_order.iter().map(|s| s.reversed()).collect();
+    }
+
     pub fn set_ignored(&mut self, substrings: Vec<String>) {
         self.ignored_by_path = substrings
             .into_iter()
@@ -69,8 +122,85 @@ impl AnalysisFilter {
             .collect()
     }
 
+    pub fn set_limited_to_tokens(&mut self, tokens: HashSet<String>) {
+        self.limited_to_tokens = Some(tokens);
+    }
+
+    /// Exclude findings recorded in the baseline
+    pub fn set_baseline(&mut self, baseline: Baseline) {
+        self.baseline = Some(baseline);
+    }
+
+    pub fn baseline(&self) -> Option<&Baseline> {
+        self.baseline.as_ref()
+    }
+
+    pub fn excludes_baselined(&self, usage: &TokenUsage) -> bool {
+        match &self.baseline {
+            Some(baseline) => !baseline.contains(usage),
+            None => true,
+        }
+    }
+
+    /// Exclude tokens listed in the project's allowlist
+    pub fn set_allowlist(&mut self, allowlist: Allowlist) {
+        self.allowlist = allowlist;
+    }
+
+    pub fn allowlist(&self) -> &Allowlist {
+        &self.allowlist
+    }
+
+    pub fn excludes_allowlisted(&self, result: &TokenSearchResult) -> bool {
+        self.allowlist.allows(&result.token).is_none()
+    }
+
+    pub fn includes_suppressed(&self, usage: &TokenUsage) -> bool {
+        self.include_suppressed || usage.suppression.is_none()
+    }
+
+    /// Tokens without blame, or only modified in the working tree, are never old enough
+    pub fn includes_age(&self, usage: &TokenUsage) -> bool {
+        match (&self.older_than, &usage.blame) {
+            (None, _) => true,
+            (Some(age), Some(blame)) => {
+                blame.is_committed() && age.has_passed_since(blame.timestamp)
+            }
+            (Some(_), None) => false,
+        }
+    }
+
+    /// Do findings need blame to be filtered or sorted?
+    pub fn requires_blame(&self) -> bool {
+        self.older_than.is_some() || self.sort_order.iter().any(|s| s.field() == OrderField::Age)
+    }
+
+    pub fn includes_token(&self, result: &TokenSearchResult) -> bool {
+        match &self.limited_to_tokens {
+            Some(tokens) => tokens.contains(&result.token.token),
+            None => true,
+        }
+    }
+
+    pub fn includes_usage_scope(&self, usage_likelihood: &UsageLikelihood) -> bool {
+        self.used_only_in.is_empty()
+            || usage_likelihood
+                .usage_scope
+                .map(|scope| self.used_only_in.contains(&scope))
+                .unwrap_or(false)
+    }
+
+    pub fn includes_kind(&self, result: &TokenSearchResult) -> bool {
+        self.kinds.is_empty()
+            || result
+                .token
+                .definitions
+                .iter()
+                .any(|d| self.kinds.contains(&d.kind))
+    }
+
     pub fn ignores_path(&self, result: &TokenSearchResult) -> bool {
-        if self.ignored_by_path.len() > 0 {
+        if !self.ignored_by_path.is_empty() {
             !self.ignored_by_path.iter().any(|a| a.matches(result))
         } else {
             true
@@ -82,8 +212,15 @@ impl Default for AnalysisFilter {
     fn default() -> Self {
         AnalysisFilter {
             usage_likelihood_filter: vec![UsageLikelihoodStatus::High],
-            sort_order: SortOrder::Ascending(OrderField::Token),
+            sort_order: vec![SortOrder::Ascending(OrderField::Token)],
+            used_only_in: vec![],
+            kinds: vec![],
+            include_suppressed: false,
+            older_than: None,
             ignored_by_path: vec![],
+            limited_to_tokens: None,
+            baseline: None,
+            allowlist: Allowlist::default(),
         }
     }
 }
@@ -93,6 +230,15 @@ impl Display for OrderField {
         match self {
             OrderField::Token => write!(f, "token"),
             OrderField::File => write!(f, "file"),
+            OrderField::Directory => write!(f, "directory"),
+            OrderField::Occurrences => write!(f, "occurrences"),
+            OrderField::Likelihood => write!(f, "likelihood"),
+            OrderField::Kind => write!(f, "kind"),
+            OrderField::Language => write!(f, "language"),
+            OrderField::Definitions => write!(f, "definitions"),
+            OrderField::Score => write!(f, "score"),
+            OrderField::Age => write!(f, "age"),
+            OrderField::Size => write!(f, "size"),
         }
     }
 }
@@ -105,3 +251,26 @@ impl Display for SortOrder {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sort_order_with_direction() {
+        assert_eq!(
+            "likelihood".parse(),
+            Ok(SortOrder::Ascending(OrderField::Likelihood))
+        );
+        assert_eq!(
+            "file:desc".parse(),
+            Ok(SortOrder::Descending(OrderField::File))
+        );
+        assert_eq!(
+            "kind:ASC".parse(),
+            Ok(SortOrder::Ascending(OrderField::Kind))
+        );
+        assert!("file:sideways".parse::<SortOrder>().is_err());
+        assert!("nope".parse::<SortOrder>().is_err());
+    }
+}
@@ -0,0 +1,187 @@
+use super::token_usage::TokenUsage;
+use codebase_files::write_atomically;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const VERSION: u32 = 1;
+
+/// A finding recorded in a baseline, keyed by token and the path of one of its definitions
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct BaselineEntry {
+    pub token: String,
+    pub path: PathBuf,
+}
+
+impl BaselineEntry {
+    /// An entry for each path the usage's token is defined in
+    fn all_for(usage: &TokenUsage) -> impl Iterator<Item = BaselineEntry> + '_ {
+        usage
+            .result
+            .token
+            .defined_paths
+            .iter()
+            .map(move |path| BaselineEntry {
+                token: usage.result.token.token.to_string(),
+                path: path.to_path_buf(),
+            })
+    }
+}
+
+/// Known findings which shouldn't be reported again
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Baseline {
+    version: u32,
+    #[serde(serialize_with = "sorted")]
+    findings: HashSet<BaselineEntry>,
+}
+
+#[derive(Debug)]
+pub enum BaselineError {
+    Io(PathBuf, io::Error),
+    Json(PathBuf, serde_json::Error),
+}
+
+impl Baseline {
+    /// Record every finding provided, at each path its token is defined in
+    pub fn from_findings(findings: &[&TokenUsage]) -> Self {
+        Baseline {
+            version: VERSION,
+            findings: findings
+                .iter()
+                .flat_map(|usage| BaselineEntry::all_for(usage))
+                .collect(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, BaselineError> {
+        let contents =
+            fs::read_to_string(path).map_err(|e| BaselineError::Io(path.to_path_buf(), e))?;
+        serde_json::from_str(&contents).map_err(|e| BaselineError::Json(path.to_path_buf(), e))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), BaselineError> {
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| BaselineError::Json(path.to_path_buf(), e))?;
+        write_atomically(path, &(contents + "\n"))
+            .map_err(|e| BaselineError::Io(path.to_path_buf(), e))
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &BaselineEntry> {
+        self.findings.iter().sorted()
+    }
+
+    pub fn len(&self) -> usize {
+        self.findings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Is the usage already recorded in the baseline, at every one of its token's defined paths?
+    ///
+    /// A token gaining a definition elsewhere is reported as a new finding.
+    pub fn contains(&self, usage: &TokenUsage) -> bool {
+        BaselineEntry::all_for(usage).all(|entry| self.findings.contains(&entry))
+    }
+
+    /// Entries which no longer match any of the provided findings, and can be pruned
+    pub fn stale_entries(&self, findings: &[&TokenUsage]) -> Vec<&BaselineEntry> {
+        let current: HashSet<BaselineEntry> = findings
+            .iter()
+            .flat_map(|usage| BaselineEntry::all_for(usage))
+            .collect();
+
+        self.entries()
+            .filter(|entry| !current.contains(entry))
+            .collect()
+    }
+}
+
+fn sorted<S>(findings: &HashSet<BaselineEntry>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.collect_seq(findings.iter().sorted())
+}
+
+impl Display for BaselineError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            BaselineError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            BaselineError::Json(path, err) => write!(f, "{}: {}", path.display(), err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_helpers::{build_usage, build_usage_defined_in};
+    use super::*;
+    use read_ctags::TokenKind;
+
+    #[test]
+    fn matches_findings_by_token_and_path() {
+        let full_name = build_usage("full_name", "app/models/person.rb", TokenKind::Method);
+        let age = build_usage("age", "app/models/person.rb", TokenKind::Method);
+        let moved_age = build_usage("age", "app/models/user.rb", TokenKind::Method);
+        let baseline = Baseline::from_findings(&[&full_name, &age]);
+
+        assert!(baseline.contains(&full_name));
+        assert!(baseline.contains(&age));
+        assert!(!baseline.contains(&moved_age));
+        assert_eq!(
+            baseline.stale_entries(&[&full_name, &moved_age]),
+            vec![&BaselineEntry {
+                token: String::from("age"),
+                path: PathBuf::from("app/models/person.rb"),
+            }]
+        );
+    }
+
+    #[test]
+    fn records_and_matches_every_defined_path() {
+        let person = "app/models/person.rb";
+        let user = "app/models/user.rb";
+        let shared = build_usage_defined_in("age", &[person, user]);
+        let baseline = Baseline::from_findings(&[&shared]);
+
+        assert_eq!(baseline.len(), 2);
+        assert!(baseline.contains(&shared));
+
+        let partial = Baseline::from_findings(&[&build_usage_defined_in("age", &[person])]);
+        assert!(partial.contains(&build_usage_defined_in("age", &[person])));
+        assert!(!partial.contains(&shared));
+    }
+
+    #[test]
+    fn writes_and_loads_from_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.json");
+        let full_name = build_usage("full_name", "app/models/person.rb", TokenKind::Method);
+        let baseline = Baseline::from_findings(&[&full_name]);
+
+        baseline.write(&path).unwrap();
+
+        assert_eq!(Baseline::load(&path).unwrap(), baseline);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let full_name = build_usage("full_name", "app/models/person.rb", TokenKind::Method);
+        let baseline = Baseline::from_findings(&[&full_name]);
+        let json = serde_json::to_string(&baseline).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"version":1,"findings":[{"token":"full_name","path":"app/models/person.rb"}]}"#
+        );
+        assert_eq!(serde_json::from_str::<Baseline>(&json).unwrap(), baseline);
+    }
+}
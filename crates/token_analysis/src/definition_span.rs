@@ -0,0 +1,207 @@
+use super::source_files::SourceFiles;
+use super::token_usage::TokenUsage;
+use read_ctags::CtagItem;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use token_search::TokenSearchConfig;
+
+/// Lines (1-based, inclusive) spanned by a definition
+///
+/// The end is the tag's `end` field when ctags provides one, otherwise the line before the next
+/// tag in the same file. The last tag in a file without an `end` field only spans its own line.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DefinitionSpan {
+    pub path: PathBuf,
+    pub start: usize,
+    pub end: usize,
+    /// Whether the end is the tag's `end` field, rather than estimated from other tags
+    #[serde(skip)]
+    pub tagged_end: bool,
+}
+
+impl DefinitionSpan {
+    /// Calculate the span of each usage's definitions, along with its removable lines
+    pub(crate) fn detect<'a, I>(spanned_files: &SpannedFiles, usages: I)
+    where
+        I: IntoIterator<Item = &'a mut TokenUsage>,
+    {
+        for usage in usages {
+            usage.spans = usage
+                .result
+                .token
+                .sorted_definitions()
+                .into_iter()
+                .filter_map(|definition| spanned_files.span(definition))
+                .collect();
+            usage.removable_lines = Self::line_count(usage.spans.iter());
+        }
+    }
+
+    /// Count lines covered by the spans, counting overlapping lines once
+    pub fn line_count<'a, I>(spans: I) -> usize
+    where
+        I: IntoIterator<Item = &'a DefinitionSpan>,
+    {
+        let mut by_path: BTreeMap<&Path, Vec<(usize, usize)>> = BTreeMap::new();
+        for span in spans {
+            by_path
+                .entry(&span.path)
+                .or_default()
+                .push((span.start, span.end));
+        }
+
+        by_path
+            .into_values()
+            .map(|mut ranges| {
+                ranges.sort_unstable();
+
+                let mut count = 0;
+                let mut covered_through = 0;
+                for (start, end) in ranges {
+                    let start = start.max(covered_through + 1);
+                    if end >= start {
+                        count += end - start + 1;
+                        covered_through = end;
+                    }
+                }
+                count
+            })
+            .sum()
+    }
+}
+
+/// Start lines of every tag per file, to span definitions without an `end` field
+pub(crate) struct SpannedFiles<'a> {
+    files: &'a SourceFiles,
+    tag_starts: HashMap<&'a Path, BTreeSet<usize>>,
+}
+
+impl<'a> SpannedFiles<'a> {
+    pub(crate) fn new(config: &'a TokenSearchConfig, files: &'a SourceFiles) -> Self {
+        let mut tag_starts: HashMap<&Path, BTreeSet<usize>> = HashMap::new();
+
+        for definition in config.tokens.iter().flat_map(|t| t.definitions.iter()) {
+            if let Some(line) = files.line(definition) {
+                tag_starts
+                    .entry(definition.file_path.as_path())
+                    .or_default()
+                    .insert(line);
+            }
+        }
+
+        SpannedFiles { files, tag_starts }
+    }
+
+    /// The definition's span, when its line can be determined
+    pub(crate) fn span(&self, definition: &CtagItem) -> Option<DefinitionSpan> {
+        let path = definition.file_path.as_path();
+        let start = self.files.line(definition)?;
+        let tagged_end = definition.end_line().filter(|end| *end >= start);
+        let end = tagged_end
+            .or_else(|| self.next_tag_start(path, start).map(|next| next - 1))
+            .unwrap_or(start);
+
+        Some(DefinitionSpan {
+            path: path.to_path_buf(),
+            start,
+            end,
+            tagged_end: tagged_end.is_some(),
+        })
+    }
+
+    fn next_tag_start(&self, path: &Path, line: usize) -> Option<usize> {
+        self.tag_starts.get(path)?.range(line + 1..).next().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use project_configuration::ProjectConfiguration;
+    use read_ctags::{Language, TokenKind};
+    use token_search::{Token, TokenSearchResult};
+
+    fn build_token(name: &str, line: usize, end: Option<usize>) -> Token {
+        let mut tags = BTreeMap::new();
+        tags.insert("line".to_string(), line.to_string());
+        if let Some(end) = end {
+            tags.insert("end".to_string(), end.to_string());
+        }
+
+        Token::new(
+            name.to_string(),
+            vec![CtagItem {
+                name: name.to_string(),
+                file_path: PathBuf::from("app/models/person.rb"),
+                address: format!("/^  def {}$/", name),
+                language: Some(Language::Ruby),
+                tags,
+                kind: TokenKind::Method,
+            }]
+            .into_iter()
+            .collect(),
+        )
+    }
+
+    fn span(path: &str, start: usize, end: usize) -> DefinitionSpan {
+        DefinitionSpan {
+            path: PathBuf::from(path),
+            start,
+            end,
+            tagged_end: true,
+        }
+    }
+
+    #[test]
+    fn counts_overlapping_lines_once() {
+        let spans = vec![
+            span("app/models/person.rb", 1, 20),
+            span("app/models/person.rb", 4, 6),
+            span("app/models/person.rb", 18, 25),
+            span("app/models/person.rb", 30, 30),
+            span("lib/widget.rb", 4, 6),
+        ];
+
+        assert_eq!(DefinitionSpan::line_count(&spans), 25 + 1 + 3);
+        assert_eq!(DefinitionSpan::line_count(&[]), 0);
+    }
+
+    #[test]
+    fn spans_definitions_to_their_end_the_next_tag_or_their_line() {
+        let config = TokenSearchConfig {
+            tokens: vec![
+                build_token("name", 2, Some(4)),
+                build_token("age", 6, None),
+                build_token("email", 11, None),
+            ],
+            root: PathBuf::from("missing"),
+            ..Default::default()
+        };
+        let mut usages: Vec<TokenUsage> = config
+            .tokens
+            .iter()
+            .map(|token| {
+                TokenUsage::new(
+                    &ProjectConfiguration::default(),
+                    TokenSearchResult {
+                        token: token.clone(),
+                        occurrences: HashMap::new(),
+                        occurrence_lines: HashMap::new(),
+                        ref_occurrences: BTreeMap::new(),
+                        consumer_occurrences: BTreeMap::new(),
+                    },
+                )
+            })
+            .collect();
+
+        let files = SourceFiles::read(&config);
+        DefinitionSpan::detect(&SpannedFiles::new(&config, &files), &mut usages);
+
+        let spans: Vec<_> = usages
+            .iter()
+            .map(|u| (u.spans[0].start, u.spans[0].end, u.removable_lines))
+            .collect();
+        assert_eq!(spans, vec![(2, 4, 3), (6, 10, 5), (11, 11, 1)]);
+    }
+}
@@ -0,0 +1,245 @@
+use super::token_usage::TokenUsage;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Default name of the history file, read from the root of the project
+pub const HISTORY_FILE_NAME: &str = ".unused-history.jsonl";
+
+/// A compact record of one run's findings
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Snapshot {
+    /// Seconds since the Unix epoch
+    pub recorded_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub total: usize,
+    pub likelihoods: BTreeMap<String, usize>,
+    pub languages: BTreeMap<String, usize>,
+    pub directories: BTreeMap<String, usize>,
+    /// Each finding, as `path#token` based on its primary definition
+    pub tokens: BTreeSet<String>,
+}
+
+/// How a count changed between two snapshots
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct CountChange {
+    pub before: usize,
+    pub after: usize,
+}
+
+/// Differences between two snapshots
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SnapshotDiff {
+    pub total: CountChange,
+    pub likelihoods: BTreeMap<String, CountChange>,
+    pub languages: BTreeMap<String, CountChange>,
+    pub directories: BTreeMap<String, CountChange>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// Snapshots stored one per line, oldest first
+#[derive(Debug, Default)]
+pub struct History {
+    snapshots: Vec<Snapshot>,
+}
+
+#[derive(Debug)]
+pub enum HistoryError {
+    Io(PathBuf, io::Error),
+    Json(PathBuf, usize, serde_json::Error),
+}
+
+impl Snapshot {
+    pub fn new(findings: &[&TokenUsage], recorded_at: u64, label: Option<String>) -> Self {
+        let mut likelihoods = BTreeMap::new();
+        let mut languages = BTreeMap::new();
+        let mut directories = BTreeMap::new();
+        let mut tokens = BTreeSet::new();
+
+        for finding in findings {
+            let token = &finding.result.token;
+            let path = token.first_path();
+
+            *likelihoods
+                .entry(finding.usage_likelihood.status.to_string())
+                .or_default() += 1;
+            *languages
+                .entry(
+                    token
+                        .primary_definition()
+                        .and_then(|d| d.language)
+                        .map(|l| l.to_string())
+                        .unwrap_or_else(|| String::from("unknown")),
+                )
+                .or_default() += 1;
+            *directories.entry(finding.directory()).or_default() += 1;
+            tokens.insert(format!("{}#{}", path.display(), token.token));
+        }
+
+        Snapshot {
+            recorded_at,
+            label,
+            total: findings.len(),
+            likelihoods,
+            languages,
+            directories,
+            tokens,
+        }
+    }
+
+    /// Changes from this snapshot to a later one
+    pub fn diff(&self, later: &Snapshot) -> SnapshotDiff {
+        SnapshotDiff {
+            total: CountChange {
+                before: self.total,
+                after: later.total,
+            },
+            likelihoods: Self::count_changes(&self.likelihoods, &later.likelihoods),
+            languages: Self::count_changes(&self.languages, &later.languages),
+            directories: Self::count_changes(&self.directories, &later.directories),
+            added: later.tokens.difference(&self.tokens).cloned().collect(),
+            removed: self.tokens.difference(&later.tokens).cloned().collect(),
+        }
+    }
+
+    fn count_changes(
+        before: &BTreeMap<String, usize>,
+        after: &BTreeMap<String, usize>,
+    ) -> BTreeMap<String, CountChange> {
+        before
+            .keys()
+            .chain(after.keys())
+            .map(|key| {
+                let change = CountChange {
+                    before: before.get(key).copied().unwrap_or_default(),
+                    after: after.get(key).copied().unwrap_or_default(),
+                };
+                (key.to_string(), change)
+            })
+            .filter(|(_, change)| change.before != change.after)
+            .collect()
+    }
+}
+
+impl CountChange {
+    pub fn delta(&self) -> i64 {
+        self.after as i64 - self.before as i64
+    }
+}
+
+impl History {
+    /// Load a history file; a missing file is treated as empty
+    pub fn load(path: &Path) -> Result<Self, HistoryError> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(HistoryError::Io(path.to_path_buf(), e)),
+        };
+
+        let snapshots = contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                serde_json::from_str(line)
+                    .map_err(|e| HistoryError::Json(path.to_path_buf(), idx + 1, e))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(History { snapshots })
+    }
+
+    /// Append a snapshot to the history file, creating it if needed
+    pub fn append(path: &Path, snapshot: &Snapshot) -> Result<(), HistoryError> {
+        let line = serde_json::to_string(snapshot)
+            .map_err(|e| HistoryError::Json(path.to_path_buf(), 0, e))?;
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| writeln!(file, "{}", line))
+            .map_err(|e| HistoryError::Io(path.to_path_buf(), e))
+    }
+
+    pub fn snapshots(&self) -> &[Snapshot] {
+        &self.snapshots
+    }
+}
+
+impl Display for HistoryError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            HistoryError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            HistoryError::Json(path, line, err) => {
+                write!(f, "{} (line {}): {}", path.display(), line, err)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_helpers::build_usage;
+    use super::*;
+    use read_ctags::TokenKind;
+
+    #[test]
+    fn diffs_counts_and_tokens_between_snapshots() {
+        let full_name = build_usage("full_name", "app/models/person.rb", TokenKind::Method);
+        let age = build_usage("age", "app/models/person.rb", TokenKind::Method);
+        let perform = build_usage("perform", "app/jobs/cleanup_job.rb", TokenKind::Method);
+
+        let before = Snapshot::new(&[&full_name, &age], 100, Some(String::from("v1")));
+        let after = Snapshot::new(&[&age, &perform], 200, None);
+        let diff = before.diff(&after);
+
+        assert_eq!(before.likelihoods.get("high"), Some(&2));
+        assert_eq!(before.languages.get("Ruby"), Some(&2));
+        assert_eq!(
+            diff.total,
+            CountChange {
+                before: 2,
+                after: 2
+            }
+        );
+        assert_eq!(
+            diff.directories.get("app/models/"),
+            Some(&CountChange {
+                before: 2,
+                after: 1
+            })
+        );
+        assert_eq!(
+            diff.directories.get("app/jobs/"),
+            Some(&CountChange {
+                before: 0,
+                after: 1
+            })
+        );
+        assert!(diff.languages.is_empty());
+        assert_eq!(diff.added, vec!["app/jobs/cleanup_job.rb#perform"]);
+        assert_eq!(diff.removed, vec!["app/models/person.rb#full_name"]);
+    }
+
+    #[test]
+    fn appends_and_loads_snapshots() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join(".unused-history.jsonl");
+        let age = build_usage("age", "app/models/person.rb", TokenKind::Method);
+        let first = Snapshot::new(&[&age], 100, None);
+        let second = Snapshot::new(&[], 200, Some(String::from("cleanup")));
+
+        assert!(History::load(&path).unwrap().snapshots().is_empty());
+
+        History::append(&path, &first).unwrap();
+        History::append(&path, &second).unwrap();
+
+        assert_eq!(History::load(&path).unwrap().snapshots(), &[first, second]);
+    }
+}
@@ -1,8 +1,29 @@
+pub mod age;
 mod analysis_filter;
+mod baseline;
+mod definition_span;
+mod history;
 mod occurrence_count;
+mod reference_graph;
+mod removal;
+mod source_files;
+mod suppression;
+#[cfg(test)]
+mod test_helpers;
 mod token_usage;
+mod triage;
 mod usage_likelihood;
+mod usage_likelihood_reason;
 
+pub use age::Age;
 pub use analysis_filter::*;
+pub use baseline::{Baseline, BaselineEntry, BaselineError};
+pub use definition_span::DefinitionSpan;
+pub use history::{CountChange, History, HistoryError, Snapshot, SnapshotDiff, HISTORY_FILE_NAME};
+pub use reference_graph::DeadCluster;
+pub use removal::{FileRemoval, RemovalPlan, SkippedDefinition};
+pub use suppression::Suppression;
 pub use token_usage::{TokenUsage, TokenUsageResults};
+pub use triage::{Triage, TriageDecision, TriageError, TRIAGE_FILE_NAME};
 pub use usage_likelihood::*;
+pub use usage_likelihood_reason::{UsageLikelihoodReason, UsageScope};
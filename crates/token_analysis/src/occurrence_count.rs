@@ -5,13 +5,14 @@ use std::ops::Add;
 use std::path::{Path, PathBuf};
 use token_search::TokenSearchResult;
 
-#[derive(Clone, Copy, Serialize)]
+#[derive(Clone, Copy, Serialize, Default)]
 pub struct Counts {
     pub file_count: usize,
     pub occurrence_count: usize,
 }
 
 #[derive(PartialEq)]
+#[allow(clippy::enum_variant_names)]
 pub enum FileType {
     ApplicationFile,
     TestFile,
@@ -19,15 +20,6 @@ pub enum FileType {
     UnknownFile,
 }
 
-impl Default for Counts {
-    fn default() -> Self {
-        Self {
-            file_count: 0,
-            occurrence_count: 0,
-        }
-    }
-}
-
 impl Add for Counts {
     type Output = Self;
 
@@ -48,7 +40,7 @@ impl Counts {
     }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Default)]
 pub struct FileTypeCounts {
     pub app: Counts,
     pub config: Counts,
@@ -56,17 +48,6 @@ pub struct FileTypeCounts {
     pub unknown: Counts,
 }
 
-impl Default for FileTypeCounts {
-    fn default() -> Self {
-        Self {
-            app: Counts::default(),
-            config: Counts::default(),
-            test: Counts::default(),
-            unknown: Counts::default(),
-        }
-    }
-}
-
 impl FileTypeCounts {
     pub fn new(
         project_configuration: &ProjectConfiguration,
@@ -104,7 +85,7 @@ impl FileTypeCounts {
     }
 
     pub fn total(&self) -> Counts {
-        vec![self.app, self.config, self.test, self.unknown]
+        [self.app, self.config, self.test, self.unknown]
             .iter()
             .fold(Counts::default(), |mut acc, o| {
                 acc = acc + *o;
@@ -128,7 +109,10 @@ impl FileTypeCounts {
         Self::file_type(project_configuration, path) == FileType::UnknownFile
     }
 
-    fn file_type(project_configuration: &ProjectConfiguration, path: &PathBuf) -> FileType {
+    pub(crate) fn file_type(
+        project_configuration: &ProjectConfiguration,
+        path: &PathBuf,
+    ) -> FileType {
         if Self::compare_file(path, &project_configuration.application_file) {
             FileType::ApplicationFile
         } else if Self::compare_file(path, &project_configuration.test_file) {
@@ -0,0 +1,476 @@
+use super::definition_span::SpannedFiles;
+use super::occurrence_count::{FileType, FileTypeCounts};
+use super::token_usage::TokenUsage;
+use project_configuration::{Allowlist, ProjectConfiguration};
+use read_ctags::TokenKind;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::Path;
+use token_search::TokenSearchConfig;
+
+/// Tokens which are only used by one another, with no usage reaching them from live code
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DeadCluster {
+    /// Every token in the cluster, sorted
+    pub tokens: Vec<String>,
+    /// For each token used within the cluster, the chain of references leading to it from an
+    /// unused token
+    pub chains: Vec<Vec<String>>,
+}
+
+struct DefinitionRange<'a> {
+    start: usize,
+    end: usize,
+    token: &'a str,
+}
+
+/// References between analyzed tokens
+///
+/// Each occurrence of a token is attributed to the innermost definition enclosing it, based on
+/// each definition's `DefinitionSpan`.
+///
+/// Occurrences outside any analyzed token's definition mark the token as a root, as do entry
+/// points: `main`, tokens defined in test files, callbacks, tokens matching a low-likelihood rule,
+/// suppressed or allowlisted tokens, and tokens used by consumers or on other git references.
+pub struct ReferenceGraph {
+    references: BTreeMap<String, BTreeSet<String>>,
+    referenced_by: BTreeMap<String, BTreeSet<String>>,
+    roots: BTreeSet<String>,
+    tokens: BTreeSet<String>,
+}
+
+impl ReferenceGraph {
+    pub fn build(
+        config: &TokenSearchConfig,
+        project_configuration: &ProjectConfiguration,
+        allowlist: &Allowlist,
+        spanned_files: &SpannedFiles,
+        usages: &[TokenUsage],
+    ) -> Self {
+        let tokens: BTreeSet<String> = usages
+            .iter()
+            .map(|u| u.result.token.token.to_string())
+            .collect();
+        let ranges = Self::definition_ranges(config, spanned_files, usages);
+        let mut references: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        let mut roots = BTreeSet::new();
+
+        for usage in usages {
+            let token = usage.result.token.token.as_str();
+
+            if Self::is_entry_point(project_configuration, allowlist, usage) {
+                roots.insert(token.to_string());
+            }
+
+            for (path, lines) in &usage.result.occurrence_lines {
+                let file_ranges = ranges.get(path.as_path()).map(|r| &r[..]).unwrap_or(&[]);
+
+                for &line in lines {
+                    let containing: Vec<&DefinitionRange> = file_ranges
+                        .iter()
+                        .filter(|r| r.start <= line && line <= r.end)
+                        .collect();
+
+                    if containing.iter().any(|r| r.token == token) {
+                        continue;
+                    }
+
+                    match containing
+                        .into_iter()
+                        .max_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)))
+                    {
+                        Some(enclosing) if tokens.contains(enclosing.token) => {
+                            references
+                                .entry(enclosing.token.to_string())
+                                .or_default()
+                                .insert(token.to_string());
+                        }
+                        _ => {
+                            roots.insert(token.to_string());
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut referenced_by: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for (caller, callees) in &references {
+            for callee in callees {
+                referenced_by
+                    .entry(callee.to_string())
+                    .or_default()
+                    .insert(caller.to_string());
+            }
+        }
+
+        ReferenceGraph {
+            references,
+            referenced_by,
+            roots,
+            tokens,
+        }
+    }
+
+    /// Tokens not reachable from any root
+    pub fn dead_tokens(&self) -> BTreeSet<String> {
+        let mut live: HashSet<&str> = HashSet::new();
+        let mut queue: VecDeque<&str> = self.roots.iter().map(|r| r.as_str()).collect();
+
+        while let Some(token) = queue.pop_front() {
+            if live.insert(token) {
+                if let Some(callees) = self.references.get(token) {
+                    queue.extend(callees.iter().map(|c| c.as_str()));
+                }
+            }
+        }
+
+        self.tokens
+            .iter()
+            .filter(|t| !live.contains(t.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    /// Tokens whose definitions reference the provided token
+    pub fn referenced_by(&self, token: &str) -> Vec<String> {
+        self.referenced_by
+            .get(token)
+            .map(|callers| callers.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Groups of dead tokens connected by references
+    pub fn dead_clusters(&self) -> Vec<DeadCluster> {
+        let dead = self.dead_tokens();
+        let mut visited: HashSet<&str> = HashSet::new();
+        let mut clusters = vec![];
+
+        for start in dead.iter() {
+            if visited.contains(start.as_str()) {
+                continue;
+            }
+
+            let mut component = BTreeSet::new();
+            let mut queue = VecDeque::from(vec![start.as_str()]);
+
+            while let Some(token) = queue.pop_front() {
+                if !visited.insert(token) {
+                    continue;
+                }
+                component.insert(token);
+
+                for neighbor in self.neighbors(token) {
+                    if dead.contains(neighbor) {
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+
+            if component.len() > 1 {
+                clusters.push(self.cluster(&component));
+            }
+        }
+
+        clusters
+    }
+
+    fn cluster(&self, component: &BTreeSet<&str>) -> DeadCluster {
+        let mut predecessors: HashMap<&str, &str> = HashMap::new();
+        let mut reached: HashSet<&str> = HashSet::new();
+        let seeds: Vec<&str> = component
+            .iter()
+            .filter(|t| self.referenced_by(t).is_empty())
+            .cloned()
+            .collect();
+
+        for seed in seeds
+            .iter()
+            .chain(component.iter())
+            .cloned()
+            .collect::<Vec<_>>()
+        {
+            if reached.contains(seed) {
+                continue;
+            }
+
+            let mut queue = VecDeque::from(vec![seed]);
+            reached.insert(seed);
+
+            while let Some(token) = queue.pop_front() {
+                for callee in self.references.get(token).into_iter().flatten() {
+                    let callee = callee.as_str();
+                    if component.contains(callee) && reached.insert(callee) {
+                        predecessors.insert(callee, token);
+                        queue.push_back(callee);
+                    }
+                }
+            }
+        }
+
+        let chains = component
+            .iter()
+            .filter(|t| predecessors.contains_key(*t))
+            .map(|token| {
+                let mut chain = vec![token.to_string()];
+                let mut current = *token;
+                while let Some(previous) = predecessors.get(current) {
+                    chain.push(previous.to_string());
+                    current = previous;
+                }
+                chain.reverse();
+                chain
+            })
+            .collect();
+
+        DeadCluster {
+            tokens: component.iter().map(|t| t.to_string()).collect(),
+            chains,
+        }
+    }
+
+    fn is_entry_point(
+        project_configuration: &ProjectConfiguration,
+        allowlist: &Allowlist,
+        usage: &TokenUsage,
+    ) -> bool {
+        let result = &usage.result;
+
+        result.token.token == "main"
+            || result
+                .token
+                .definitions
+                .iter()
+                .any(|d| d.kind == TokenKind::Callback)
+            || result.token.defined_paths.iter().any(|path| {
+                FileTypeCounts::file_type(project_configuration, path) == FileType::TestFile
+            })
+            || project_configuration.low_likelihood_match(result).is_some()
+            || usage.suppression.is_some()
+            || allowlist.allows(&result.token).is_some()
+            || !result.consumers_with_usage().is_empty()
+            || !result.refs_with_usage().is_empty()
+    }
+
+    fn neighbors<'a>(&'a self, token: &str) -> impl Iterator<Item = &'a str> {
+        self.references
+            .get(token)
+            .into_iter()
+            .chain(self.referenced_by.get(token))
+            .flatten()
+            .map(|t| t.as_str())
+    }
+
+    fn definition_ranges<'a>(
+        config: &'a TokenSearchConfig,
+        spanned_files: &SpannedFiles,
+        usages: &[TokenUsage],
+    ) -> HashMap<&'a Path, Vec<DefinitionRange<'a>>> {
+        let occurred_paths: HashSet<&Path> = usages
+            .iter()
+            .flat_map(|u| u.result.occurrence_lines.keys().map(|p| p.as_path()))
+            .collect();
+        let mut ranges: HashMap<&Path, Vec<DefinitionRange>> = HashMap::new();
+
+        for token in config.tokens.iter() {
+            for definition in token.definitions.iter() {
+                let path = definition.file_path.as_path();
+                if !occurred_paths.contains(path) {
+                    continue;
+                }
+
+                if let Some(span) = spanned_files.span(definition) {
+                    ranges.entry(path).or_default().push(DefinitionRange {
+                        start: span.start,
+                        end: span.end,
+                        token: token.token.as_str(),
+                    });
+                }
+            }
+        }
+
+        ranges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source_files::SourceFiles;
+    use crate::Suppression;
+    use project_configuration::{AllowlistEntry, ProjectConfiguration};
+    use read_ctags::{CtagItem, Language, TokenKind};
+    use std::path::PathBuf;
+    use token_search::{Token, TokenSearchResult};
+
+    fn build_method(name: &str, line: usize) -> Token {
+        build_tag(name, line, Some(line + 2))
+    }
+
+    fn build_tag(name: &str, line: usize, end: Option<usize>) -> Token {
+        let mut tags = BTreeMap::new();
+        if let Some(end) = end {
+            tags.insert(String::from("end"), end.to_string());
+        }
+
+        Token::new(
+            name.to_string(),
+            vec![CtagItem {
+                name: name.to_string(),
+                file_path: PathBuf::from("app/models/person.rb"),
+                address: line.to_string(),
+                language: Some(Language::Ruby),
+                tags,
+                kind: TokenKind::Method,
+            }]
+            .into_iter()
+            .collect(),
+        )
+    }
+
+    fn build_usage(token: &Token, lines: Vec<usize>) -> TokenUsage {
+        let mut occurrence_lines = HashMap::new();
+        occurrence_lines.insert(PathBuf::from("app/models/person.rb"), lines);
+
+        TokenUsage::new(
+            &ProjectConfiguration::default(),
+            TokenSearchResult {
+                token: token.clone(),
+                occurrences: occurrence_lines
+                    .iter()
+                    .map(|(path, lines)| (path.clone(), lines.len()))
+                    .collect(),
+                occurrence_lines,
+                ref_occurrences: BTreeMap::new(),
+                consumer_occurrences: BTreeMap::new(),
+            },
+        )
+    }
+
+    #[test]
+    fn finds_tokens_only_reachable_from_unused_tokens() {
+        // Each method spans three lines
+        // 1: def full_name      (called from outside any definition, on line 20)
+        // 5: def helper_b       (unused)
+        // 10: def helper_a      (called from helper_b on line 6)
+        // 15: def helper_c      (called from helper_a on line 11)
+        let full_name = build_method("full_name", 1);
+        let helper_b = build_method("helper_b", 5);
+        let helper_a = build_method("helper_a", 10);
+        let helper_c = build_method("helper_c", 15);
+        let config = TokenSearchConfig {
+            tokens: vec![
+                full_name.clone(),
+                helper_b.clone(),
+                helper_a.clone(),
+                helper_c.clone(),
+            ],
+            files: vec![],
+            ..Default::default()
+        };
+        let usages = vec![
+            build_usage(&full_name, vec![1, 20]),
+            build_usage(&helper_b, vec![5]),
+            build_usage(&helper_a, vec![6, 10]),
+            build_usage(&helper_c, vec![11, 15]),
+        ];
+
+        let graph = ReferenceGraph::build(
+            &config,
+            &ProjectConfiguration::default(),
+            &Allowlist::default(),
+            &SpannedFiles::new(&config, &SourceFiles::read(&config)),
+            &usages,
+        );
+
+        assert_eq!(
+            graph.dead_tokens(),
+            ["helper_a", "helper_b", "helper_c"]
+                .iter()
+                .map(|t| t.to_string())
+                .collect()
+        );
+        assert_eq!(graph.referenced_by("helper_c"), vec!["helper_a"]);
+        assert_eq!(
+            graph.dead_clusters(),
+            vec![DeadCluster {
+                tokens: vec![
+                    String::from("helper_a"),
+                    String::from("helper_b"),
+                    String::from("helper_c")
+                ],
+                chains: vec![
+                    vec![String::from("helper_b"), String::from("helper_a")],
+                    vec![
+                        String::from("helper_b"),
+                        String::from("helper_a"),
+                        String::from("helper_c")
+                    ],
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn entry_points_are_roots_and_last_tags_only_span_their_line() {
+        // 1: def helper         (called from main on line 6)
+        // 5: def main           (never called)
+        // 8: def setup          (one line, called on line 12)
+        // 9: def suppressed      (one line, marked to be ignored, and never called)
+        // 10: def allowlisted    (one line, in the allowlist, and never called)
+        // 11: trailing          (no end field, and the last tag in the file)
+        let helper = build_method("helper", 1);
+        let main = build_method("main", 5);
+        let setup = build_tag("setup", 8, Some(8));
+        let suppressed = build_tag("suppressed", 9, Some(9));
+        let allowlisted = build_tag("allowlisted", 10, Some(10));
+        let trailing = build_tag("trailing", 11, None);
+        let config = TokenSearchConfig {
+            tokens: vec![
+                helper.clone(),
+                main.clone(),
+                setup.clone(),
+                suppressed.clone(),
+                allowlisted.clone(),
+                trailing.clone(),
+            ],
+            files: vec![],
+            ..Default::default()
+        };
+        let mut suppressed_usage = build_usage(&suppressed, vec![9]);
+        suppressed_usage.suppression = Some(Suppression {
+            path: PathBuf::from("app/models/person.rb"),
+            line: 9,
+            reason: None,
+        });
+        let usages = vec![
+            build_usage(&helper, vec![1, 6]),
+            build_usage(&main, vec![5]),
+            build_usage(&setup, vec![8, 12]),
+            suppressed_usage,
+            build_usage(&allowlisted, vec![10]),
+            build_usage(&trailing, vec![11]),
+        ];
+        let mut allowlist = Allowlist::default();
+        allowlist.add(AllowlistEntry {
+            token: String::from("allowlisted"),
+            path: None,
+            kind: None,
+            reason: String::from("Called via reflection"),
+            author: None,
+        });
+
+        let graph = ReferenceGraph::build(
+            &config,
+            &ProjectConfiguration::default(),
+            &allowlist,
+            &SpannedFiles::new(&config, &SourceFiles::read(&config)),
+            &usages,
+        );
+
+        assert_eq!(
+            graph.dead_tokens(),
+            vec![String::from("trailing")].into_iter().collect()
+        );
+        assert!(graph.referenced_by("setup").is_empty());
+    }
+}
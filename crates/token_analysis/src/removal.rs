@@ -0,0 +1,768 @@
+use super::definition_span::DefinitionSpan;
+use super::token_usage::TokenUsage;
+use super::usage_likelihood_reason::UsageLikelihoodReason;
+use codebase_files::write_atomically;
+use read_ctags::Language;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const CONTEXT: usize = 3;
+
+/// Elixir module attributes documenting or annotating the definition below them
+const ELIXIR_ATTRIBUTES: &[&str] = &["@doc", "@spec", "@impl", "@typedoc", "@deprecated"];
+
+/// A definition left in place by a `RemovalPlan`, and why
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SkippedDefinition {
+    pub token: String,
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+impl Display for SkippedDefinition {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} ({})",
+            self.token,
+            self.path.display(),
+            self.reason
+        )
+    }
+}
+
+/// Lines removed from a single file
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileRemoval {
+    pub path: PathBuf,
+    /// Tokens whose definitions are removed, sorted
+    pub tokens: Vec<String>,
+    lines: Vec<String>,
+    removed: Vec<bool>,
+}
+
+/// Removes the definitions of findings from their files
+///
+/// Each definition is removed through its `DefinitionSpan`; definitions without an `end` field, or
+/// whose tags no longer match the file, are skipped rather than guessed at. Tokens whose name has
+/// several definitions, and tokens only used by other unused tokens, are skipped unless
+/// explicitly included. Comments and attributes directly above a definition are removed with it,
+/// as are enclosing blocks left empty which can't be referenced by name (inherent Rust `impl`
+/// blocks and Ruby `class << self`). Python definitions are skipped when removing them would leave an empty
+/// block.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RemovalPlan {
+    pub files: Vec<FileRemoval>,
+    pub skipped: Vec<SkippedDefinition>,
+}
+
+/// A definition's lines (0-based, inclusive) within its file
+struct PlannedDefinition<'a> {
+    token: &'a str,
+    start: usize,
+    end: usize,
+}
+
+impl RemovalPlan {
+    pub fn new(root: &Path, findings: &[&TokenUsage], include_ambiguous: bool) -> RemovalPlan {
+        let mut plan = RemovalPlan::default();
+        let mut by_path: BTreeMap<&Path, Vec<(&TokenUsage, &DefinitionSpan)>> = BTreeMap::new();
+
+        for usage in findings {
+            let token = &usage.result.token;
+            let reason = match Self::ambiguity(usage) {
+                Some(reason) if !include_ambiguous => Some(reason),
+                _ if usage.spans.len() < token.definitions.len() => {
+                    Some(String::from("tags are out of date"))
+                }
+                _ => None,
+            };
+
+            match reason {
+                Some(reason) => {
+                    for definition in token.sorted_definitions() {
+                        plan.skip(&token.token, &definition.file_path, &reason);
+                    }
+                }
+                None => {
+                    for span in usage.spans.iter() {
+                        by_path.entry(&span.path).or_default().push((usage, span));
+                    }
+                }
+            }
+        }
+
+        for (path, spans) in by_path {
+            let contents = match fs::read_to_string(root.join(path)) {
+                Ok(contents) => contents,
+                Err(_) => {
+                    for (usage, _) in spans {
+                        plan.skip(&usage.result.token.token, path, "file could not be read");
+                    }
+                    continue;
+                }
+            };
+            let lines: Vec<String> = contents.split_inclusive('\n').map(String::from).collect();
+            let language = spans
+                .iter()
+                .flat_map(|(usage, _)| usage.result.token.definitions.iter())
+                .filter(|d| d.file_path == path)
+                .find_map(|d| d.language)
+                .or_else(|| Language::from_path(path));
+
+            let mut planned = vec![];
+            for (usage, span) in spans {
+                let token = usage.result.token.token.as_str();
+                match Self::lines(usage, span, &lines) {
+                    Ok((start, end)) => planned.push(PlannedDefinition { token, start, end }),
+                    Err(reason) => plan.skip(token, path, reason),
+                }
+            }
+
+            loop {
+                let removal = FileRemoval::new(path, language, lines.clone(), &planned);
+
+                let emptied_block = match language {
+                    Some(Language::Python) => removal.empty_indented_block(),
+                    _ => None,
+                };
+                let (emptied, kept): (Vec<_>, Vec<_>) = match emptied_block {
+                    Some((opener, next)) => planned
+                        .into_iter()
+                        .partition(|d| d.start > opener && d.end < next),
+                    None => (vec![], planned),
+                };
+                planned = kept;
+
+                if emptied.is_empty() {
+                    if removal.removed_lines() > 0 {
+                        plan.files.push(removal);
+                    }
+                    break;
+                }
+
+                for definition in emptied {
+                    plan.skip(definition.token, path, "would leave an empty block");
+                }
+            }
+        }
+
+        plan
+    }
+
+    /// Total lines removed across every file
+    pub fn removed_lines(&self) -> usize {
+        self.files.iter().map(|f| f.removed_lines()).sum()
+    }
+
+    /// Unified diff of every file's removals, suitable for `git apply`
+    pub fn diff(&self) -> String {
+        self.files.iter().map(|f| f.unified_diff()).collect()
+    }
+
+    /// Write each file's remaining contents, replacing each file once fully written and keeping
+    /// its permissions
+    pub fn apply(&self, root: &Path) -> io::Result<()> {
+        for file in self.files.iter() {
+            write_atomically(&root.join(&file.path), &file.contents())?;
+        }
+
+        Ok(())
+    }
+
+    /// Why removing the token might remove code which is used
+    fn ambiguity(usage: &TokenUsage) -> Option<String> {
+        let definitions = usage.result.token.definitions.len();
+
+        if definitions > 1 {
+            Some(format!("{} definitions share its name", definitions))
+        } else if let UsageLikelihoodReason::OnlyUsedByUnusedTokens { .. } =
+            usage.usage_likelihood.reason
+        {
+            Some(String::from("only used by unused tokens"))
+        } else {
+            None
+        }
+    }
+
+    /// The span's lines (0-based, inclusive), once checked against the file
+    fn lines(
+        usage: &TokenUsage,
+        span: &DefinitionSpan,
+        lines: &[String],
+    ) -> Result<(usize, usize), &'static str> {
+        if !span.tagged_end {
+            return Err("no end line; generate tags with --fields=+ne");
+        }
+
+        let in_bounds = span.start >= 1 && span.start <= span.end && span.end <= lines.len();
+        if !in_bounds
+            || !usage.result.token.definitions.iter().any(|d| {
+                d.file_path == span.path && d.address_matches(trim_newline(&lines[span.start - 1]))
+            })
+        {
+            return Err("tags are out of date");
+        }
+
+        Ok((span.start - 1, span.end - 1))
+    }
+
+    fn skip(&mut self, token: &str, path: &Path, reason: &str) {
+        self.skipped.push(SkippedDefinition {
+            token: token.to_string(),
+            path: path.to_path_buf(),
+            reason: reason.to_string(),
+        });
+    }
+}
+
+impl FileRemoval {
+    fn new(
+        path: &Path,
+        language: Option<Language>,
+        lines: Vec<String>,
+        definitions: &[PlannedDefinition],
+    ) -> FileRemoval {
+        let mut removal = FileRemoval {
+            path: path.to_path_buf(),
+            tokens: definitions.iter().map(|d| d.token.to_string()).collect(),
+            removed: vec![false; lines.len()],
+            lines,
+        };
+        removal.tokens.sort();
+        removal.tokens.dedup();
+
+        for definition in definitions {
+            removal.remove(language, definition.start, definition.end);
+        }
+        removal.remove_empty_blocks(language);
+        removal.collapse_blank_lines();
+
+        removal
+    }
+
+    pub fn removed_lines(&self) -> usize {
+        self.removed.iter().filter(|r| **r).count()
+    }
+
+    /// The file's contents once lines are removed
+    pub fn contents(&self) -> String {
+        self.lines
+            .iter()
+            .zip(self.removed.iter())
+            .filter(|(_, removed)| !**removed)
+            .map(|(line, _)| line.as_str())
+            .collect()
+    }
+
+    /// Unified diff of the removed lines, with paths prefixed by `a/` and `b/`
+    pub fn unified_diff(&self) -> String {
+        let path = self.path.to_string_lossy();
+        let mut diff = format!("--- a/{}\n+++ b/{}\n", path, path);
+
+        for (start, end) in self.hunks() {
+            let removed_before = self.removed[..start].iter().filter(|r| **r).count();
+            let removed_within = self.removed[start..end].iter().filter(|r| **r).count();
+            let old_len = end - start;
+            let new_len = old_len - removed_within;
+            let new_start = start + 1 - removed_before;
+
+            diff.push_str(&format!(
+                "@@ -{},{} +{},{} @@\n",
+                start + 1,
+                old_len,
+                if new_len == 0 {
+                    new_start - 1
+                } else {
+                    new_start
+                },
+                new_len
+            ));
+
+            for idx in start..end {
+                diff.push(if self.removed[idx] { '-' } else { ' ' });
+                diff.push_str(&self.lines[idx]);
+
+                if !self.lines[idx].ends_with('\n') {
+                    diff.push_str("\n\\ No newline at end of file\n");
+                }
+            }
+        }
+
+        diff
+    }
+
+    /// Line ranges (0-based, exclusive end) of each hunk, including context
+    fn hunks(&self) -> Vec<(usize, usize)> {
+        let mut hunks: Vec<(usize, usize)> = vec![];
+
+        for (idx, _) in self.removed.iter().enumerate().filter(|(_, r)| **r) {
+            let start = idx.saturating_sub(CONTEXT);
+            let end = (idx + 1 + CONTEXT).min(self.lines.len());
+
+            match hunks.last_mut() {
+                Some(last) if start <= last.1 => last.1 = end,
+                _ => hunks.push((start, end)),
+            }
+        }
+
+        hunks
+    }
+
+    /// Remove lines, along with comments and attributes directly above them
+    fn remove(&mut self, language: Option<Language>, start: usize, end: usize) {
+        let mut start = start;
+        while start > 0 && !self.removed[start - 1] {
+            match self.attached_start(language, start - 1) {
+                Some(attached) => start = attached,
+                None => break,
+            }
+        }
+
+        for removed in self.removed[start..=end].iter_mut() {
+            *removed = true;
+        }
+    }
+
+    fn remove_empty_blocks(&mut self, language: Option<Language>) {
+        let mut changed = true;
+
+        while changed {
+            changed = false;
+
+            for opener in 0..self.lines.len() {
+                if self.removed[opener] {
+                    continue;
+                }
+
+                let closer = match self.next_remaining(opener) {
+                    Some(closer) => closer,
+                    None => continue,
+                };
+                let emptied = self.removed[opener + 1..closer].iter().any(|r| *r);
+
+                if emptied && is_removable_block(language, &self.lines[opener], &self.lines[closer])
+                {
+                    self.remove(language, opener, closer);
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    /// Avoid leaving blank lines at the start or end of a block, or consecutive blank lines
+    fn collapse_blank_lines(&mut self) {
+        let mut idx = 0;
+
+        while idx < self.lines.len() {
+            if !self.removed[idx] {
+                idx += 1;
+                continue;
+            }
+
+            let run_start = idx;
+            let mut run_end = idx;
+            while run_end < self.lines.len() && self.removed[run_end] {
+                run_end += 1;
+            }
+
+            let run_indentation = self.lines[run_start..run_end]
+                .iter()
+                .find(|l| !is_blank(l))
+                .map_or(0, |l| indentation(l));
+            let previous = run_start.checked_sub(1).map(|i| self.lines[i].as_str());
+            let next = self.lines.get(run_end).map(|l| l.as_str());
+            // Lines less indented than the removed run open or close its enclosing block
+            let is_boundary = |line: Option<&str>| {
+                line.is_none_or(|l| !is_blank(l) && indentation(l) < run_indentation)
+            };
+            let is_blank_line = |line: Option<&str>| line.is_some_and(is_blank);
+
+            if is_blank_line(previous) && (is_boundary(next) || is_blank_line(next)) {
+                self.removed[run_start - 1] = true;
+                idx = run_start - 1;
+            } else if is_blank_line(next) && is_boundary(previous) {
+                self.removed[run_end] = true;
+            } else {
+                idx = run_end;
+            }
+        }
+    }
+
+    /// A remaining block opener ending in `:` (and the next remaining line, or the end of the
+    /// file) whose indented body was entirely removed
+    fn empty_indented_block(&self) -> Option<(usize, usize)> {
+        (0..self.lines.len())
+            .filter(|idx| !self.removed[*idx])
+            .filter(|idx| {
+                let line = trim_newline(&self.lines[*idx]);
+                line.trim_end().ends_with(':') && !line.trim_start().starts_with('#')
+            })
+            .find_map(|opener| {
+                let next = self.next_remaining(opener).unwrap_or(self.lines.len());
+                let emptied = self.removed[opener + 1..next].iter().any(|r| *r);
+                let body_remains = next < self.lines.len()
+                    && indentation(&self.lines[next]) > indentation(&self.lines[opener]);
+
+                if emptied && !body_remains {
+                    Some((opener, next))
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// Where the comment or attribute ending on the line starts, if it belongs to the definition
+    /// below it; Elixir attributes may be heredocs spanning several lines
+    fn attached_start(&self, language: Option<Language>, idx: usize) -> Option<usize> {
+        let line = self.lines[idx].trim();
+
+        if is_attached(language, line) {
+            return Some(idx);
+        }
+        if language != Some(Language::Elixir) || line != "\"\"\"" {
+            return None;
+        }
+
+        (0..idx)
+            .rev()
+            .take_while(|i| !self.removed[*i])
+            .find(|i| self.lines[*i].trim().ends_with("\"\"\""))
+            .filter(|i| is_attached(language, &self.lines[*i]))
+    }
+
+    /// The next line which isn't removed or blank
+    fn next_remaining(&self, idx: usize) -> Option<usize> {
+        (idx + 1..self.lines.len()).find(|i| !self.removed[*i] && !is_blank(&self.lines[*i]))
+    }
+}
+
+/// Comments and attributes directly above a definition belong to it
+fn is_attached(language: Option<Language>, line: &str) -> bool {
+    let line = line.trim();
+    let markers = language.map(|l| l.comment_markers()).unwrap_or(&[]);
+
+    if line.starts_with("//!") || line.starts_with("/*!") {
+        return false;
+    }
+
+    markers.iter().any(|m| line.starts_with(m))
+        || (markers.contains(&"/*") && line.starts_with('*'))
+        || match language {
+            Some(Language::Rust) => line.starts_with("#["),
+            Some(Language::Elixir) => ELIXIR_ATTRIBUTES.iter().any(|a| line.starts_with(a)),
+            Some(Language::Python) | Some(Language::JavaScript) | Some(Language::TypeScript) => {
+                line.starts_with('@')
+            }
+            _ => false,
+        }
+}
+
+/// Blocks which can't be referenced by name, so are safe to remove once empty
+///
+/// Rust trait implementations are kept, since an empty one may still be required.
+fn is_removable_block(language: Option<Language>, opener: &str, closer: &str) -> bool {
+    if indentation(opener) != indentation(closer) {
+        return false;
+    }
+
+    let (opener, closer) = (opener.trim(), closer.trim());
+
+    match language {
+        Some(Language::Rust) => {
+            (opener.starts_with("impl ") || opener.starts_with("impl<"))
+                && !opener.contains(" for ")
+                && opener.ends_with('{')
+                && closer == "}"
+        }
+        Some(Language::Ruby) => opener == "class << self" && closer == "end",
+        _ => false,
+    }
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+fn trim_newline(line: &str) -> &str {
+    line.trim_end_matches('\n').trim_end_matches('\r')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_helpers::{build_usage, build_usage_defined_in};
+    use super::*;
+    use read_ctags::{CtagItem, TokenKind};
+
+    /// Usage of a method whose definition spans the lines (1-based, inclusive)
+    fn spanned(name: &str, path: &str, lines: (usize, usize), tagged_end: bool) -> TokenUsage {
+        let mut usage = build_usage(name, path, TokenKind::Method);
+        usage.result.token.definitions = usage
+            .result
+            .token
+            .definitions
+            .iter()
+            .map(|d| CtagItem {
+                language: Language::from_path(Path::new(path)),
+                ..d.clone()
+            })
+            .collect();
+        usage.spans = vec![DefinitionSpan {
+            path: PathBuf::from(path),
+            start: lines.0,
+            end: lines.1,
+            tagged_end,
+        }];
+        usage
+    }
+
+    fn skipped(plan: &RemovalPlan) -> Vec<(&str, &str)> {
+        plan.skipped
+            .iter()
+            .map(|s| (s.token.as_str(), s.reason.as_str()))
+            .collect()
+    }
+
+    fn plan(
+        path: &str,
+        contents: &str,
+        definitions: &[(&'static str, usize, usize)],
+    ) -> FileRemoval {
+        let planned: Vec<_> = definitions
+            .iter()
+            .map(|(token, start, end)| PlannedDefinition {
+                token,
+                start: start - 1,
+                end: end - 1,
+            })
+            .collect();
+
+        FileRemoval::new(
+            Path::new(path),
+            Language::from_path(Path::new(path)),
+            contents.split_inclusive('\n').map(String::from).collect(),
+            &planned,
+        )
+    }
+
+    #[test]
+    fn plans_removals_from_spans_and_skips_what_it_cannot_check() {
+        let root = tempfile::tempdir().unwrap();
+        let path = "app/models/person.rb";
+        fs::create_dir_all(root.path().join("app/models")).unwrap();
+        fs::write(
+            root.path().join(path),
+            "class Person\n  def name\n  end\n\n  def age\n  end\n\n  def email\n  end\nend\n",
+        )
+        .unwrap();
+
+        let age = spanned("age", path, (5, 6), true);
+        let name = spanned("name", path, (2, 3), false);
+        let mut moved = spanned("email", path, (8, 9), true);
+        moved.result.token.definitions = moved
+            .result
+            .token
+            .definitions
+            .iter()
+            .map(|d| CtagItem {
+                address: String::from("/^  def moved_email$/"),
+                ..d.clone()
+            })
+            .collect();
+        let unspanned = build_usage("title", path, TokenKind::Method);
+        let unreadable = spanned("phone", "app/models/missing.rb", (1, 2), true);
+
+        let plan = RemovalPlan::new(
+            root.path(),
+            &[&age, &name, &moved, &unspanned, &unreadable],
+            false,
+        );
+
+        assert_eq!(plan.files.len(), 1);
+        assert_eq!(plan.files[0].tokens, vec!["age"]);
+        assert_eq!(
+            plan.files[0].contents(),
+            "class Person\n  def name\n  end\n\n  def email\n  end\nend\n"
+        );
+        assert_eq!(
+            skipped(&plan),
+            vec![
+                ("title", "tags are out of date"),
+                ("phone", "file could not be read"),
+                ("name", "no end line; generate tags with --fields=+ne"),
+                ("email", "tags are out of date"),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_ambiguous_tokens_and_emptied_python_blocks() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join("a.rb"),
+            "def shared
+end
+",
+        )
+        .unwrap();
+        fs::write(
+            root.path().join("b.rb"),
+            "def shared
+end
+",
+        )
+        .unwrap();
+        fs::write(
+            root.path().join("person.py"),
+            "class Person:\n    def name(self):\n        pass\n",
+        )
+        .unwrap();
+
+        let mut shared = build_usage_defined_in("shared", &["a.rb", "b.rb"]);
+        shared.spans = ["a.rb", "b.rb"]
+            .iter()
+            .map(|path| DefinitionSpan {
+                path: PathBuf::from(path),
+                start: 1,
+                end: 2,
+                tagged_end: true,
+            })
+            .collect();
+        let name = spanned("name", "person.py", (2, 3), true);
+
+        let plan = RemovalPlan::new(root.path(), &[&shared, &name], false);
+        assert!(plan.files.is_empty());
+        assert_eq!(
+            skipped(&plan),
+            vec![
+                ("shared", "2 definitions share its name"),
+                ("shared", "2 definitions share its name"),
+                ("name", "would leave an empty block"),
+            ]
+        );
+
+        let plan = RemovalPlan::new(root.path(), &[&shared], true);
+        assert_eq!(plan.files.len(), 2);
+        assert!(plan.files.iter().all(|f| f.contents().is_empty()));
+        assert!(plan.skipped.is_empty());
+    }
+
+    #[test]
+    fn removes_definitions_with_attached_comments_and_blank_lines() {
+        let removal = plan(
+            "app/models/person.rb",
+            "class Person\n  def name\n  end\n\n  # The age\n  def age\n  end\nend\n",
+            &[("age", 6, 7)],
+        );
+
+        assert_eq!(removal.tokens, vec!["age"]);
+        assert_eq!(removal.removed_lines(), 4);
+        assert_eq!(removal.contents(), "class Person\n  def name\n  end\nend\n");
+        assert_eq!(
+            removal.unified_diff(),
+            "\
+--- a/app/models/person.rb
++++ b/app/models/person.rb
+@@ -1,8 +1,4 @@
+ class Person
+   def name
+   end
+-
+-  # The age
+-  def age
+-  end
+ end
+"
+        );
+    }
+
+    #[test]
+    fn removes_emptied_blocks_which_cannot_be_referenced() {
+        let removal = plan(
+            "src/person.rs",
+            "struct Person;\n\nimpl Person {\n    /// Name\n    fn name() {}\n}\n\nfn main() {}\n",
+            &[("name", 5, 5)],
+        );
+        assert_eq!(removal.contents(), "struct Person;\n\nfn main() {}\n");
+
+        let removal = plan(
+            "src/person.rs",
+            "struct Person;\n\nimpl Named for Person {\n    fn name() {}\n}\n",
+            &[("name", 4, 4)],
+        );
+        assert_eq!(
+            removal.contents(),
+            "struct Person;\n\nimpl Named for Person {\n}\n"
+        );
+
+        let removal = plan(
+            "app/models/person.rb",
+            "class Person\n  class << self\n    def build\n    end\n  end\nend\n",
+            &[("build", 3, 4)],
+        );
+        assert_eq!(removal.contents(), "class Person\nend\n");
+
+        let removal = plan(
+            "app/models/person.rb",
+            "class Person\n  def name\n  end\nend\n",
+            &[("name", 2, 3)],
+        );
+        assert_eq!(removal.contents(), "class Person\nend\n");
+    }
+
+    #[test]
+    fn removes_elixir_docs_and_specs() {
+        let removal = plan(
+            "lib/person.ex",
+            "defmodule Person do\n  @doc \"\"\"\n  The name\n  \"\"\"\n  @spec name() :: String.t()\n  def name, do: \"\"\nend\n",
+            &[("name", 6, 6)],
+        );
+        assert_eq!(removal.contents(), "defmodule Person do\nend\n");
+
+        let removal = plan(
+            "lib/person.ex",
+            "defmodule Person do\n  @timeout 5000\n  @doc false\n  def name, do: \"\"\nend\n",
+            &[("name", 4, 4)],
+        );
+        assert_eq!(
+            removal.contents(),
+            "defmodule Person do\n  @timeout 5000\nend\n"
+        );
+    }
+
+    #[test]
+    fn detects_python_blocks_left_empty() {
+        let removal = plan(
+            "app/person.py",
+            "class Person:\n    def name(self):\n        pass\n\nx = 1\n",
+            &[("name", 2, 3)],
+        );
+        assert_eq!(removal.empty_indented_block(), Some((0, 4)));
+
+        let removal = plan(
+            "app/person.py",
+            "class Person:\n    def name(self):\n        pass\n\n    def age(self):\n        pass\n",
+            &[("name", 2, 3)],
+        );
+        assert_eq!(removal.empty_indented_block(), None);
+    }
+
+    #[test]
+    fn marks_missing_trailing_newlines_in_diffs() {
+        let removal = plan("lib/widget.rb", "a\nb", &[("b", 2, 2)]);
+
+        assert_eq!(removal.contents(), "a\n");
+        assert_eq!(
+            removal.unified_diff(),
+            "--- a/lib/widget.rb\n+++ b/lib/widget.rb\n@@ -1,2 +1,1 @@\n a\n-b\n\\ No newline at end of file\n"
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use rayon::prelude::*;
+use read_ctags::CtagItem;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use token_search::TokenSearchConfig;
+
+/// Contents of every file defining a token, read once in parallel and shared between analyses
+///
+/// Files which can't be read (e.g. tags referencing deleted files) have no contents.
+pub(crate) struct SourceFiles {
+    contents: HashMap<PathBuf, Option<String>>,
+}
+
+impl SourceFiles {
+    pub(crate) fn read(config: &TokenSearchConfig) -> Self {
+        let paths: HashSet<&Path> = config
+            .tokens
+            .iter()
+            .flat_map(|token| token.definitions.iter().map(|d| d.file_path.as_path()))
+            .collect();
+
+        SourceFiles {
+            contents: paths
+                .into_par_iter()
+                .map(|path| {
+                    (
+                        path.to_path_buf(),
+                        fs::read_to_string(config.root.join(path)).ok(),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub(crate) fn contents(&self, path: &Path) -> Option<&str> {
+        self.contents.get(path)?.as_deref()
+    }
+
+    /// The definition's line (1-based), from its tag or by resolving its address
+    pub(crate) fn line(&self, definition: &CtagItem) -> Option<usize> {
+        definition.line().or_else(|| {
+            self.contents(&definition.file_path)
+                .and_then(|c| definition.resolve_line(c))
+        })
+    }
+}
@@ -0,0 +1,173 @@
+use super::source_files::SourceFiles;
+use super::token_usage::TokenUsage;
+use rayon::prelude::*;
+use read_ctags::{CtagItem, Language};
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+const IGNORE: &str = "unused:ignore";
+const IGNORE_NEXT_LINE: &str = "unused:ignore-next-line";
+
+/// A comment at a definition marking the token as intentionally unused
+///
+/// `unused:ignore` applies to the line it's on, and `unused:ignore-next-line` to the line
+/// following it. Either may provide `reason="..."`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Suppression {
+    pub path: PathBuf,
+    pub line: usize,
+    pub reason: Option<String>,
+}
+
+impl Suppression {
+    /// Mark usages whose definitions carry a suppression comment
+    pub(crate) fn detect(files: &SourceFiles, usages: &mut [TokenUsage]) {
+        usages.par_iter_mut().for_each(|usage| {
+            usage.suppression = usage
+                .result
+                .token
+                .sorted_definitions()
+                .into_iter()
+                .find_map(|definition| {
+                    files
+                        .contents(&definition.file_path)
+                        .filter(|c| c.contains(IGNORE))
+                        .and_then(|c| Self::find(definition, c))
+                });
+        });
+    }
+
+    /// Find a suppression comment for a definition within its file's contents
+    pub fn find(definition: &CtagItem, contents: &str) -> Option<Suppression> {
+        let line = definition.resolve_line(contents)?;
+        let markers = definition
+            .language
+            .or_else(|| Language::from_path(&definition.file_path))
+            .map(|l| l.comment_markers())
+            .unwrap_or(&[]);
+        let lines: Vec<&str> = contents.lines().collect();
+
+        let same_line = lines
+            .get(line.checked_sub(1)?)
+            .and_then(|l| Self::directive(l, markers))
+            .filter(|(next_line, _)| !next_line);
+        let previous_line = line
+            .checked_sub(2)
+            .and_then(|idx| lines.get(idx))
+            .and_then(|l| Self::directive(l, markers))
+            .filter(|(next_line, _)| *next_line);
+
+        same_line.or(previous_line).map(|(_, reason)| Suppression {
+            path: definition.file_path.to_path_buf(),
+            line,
+            reason,
+        })
+    }
+
+    /// Parse a directive within a comment, returning whether it applies to the next line and its
+    /// reason
+    fn directive(line: &str, markers: &[&str]) -> Option<(bool, Option<String>)> {
+        markers.iter().find_map(|marker| {
+            let comment = &line[line.find(marker)? + marker.len()..];
+            let start = comment.find(IGNORE)?;
+            let rest = &comment[start..];
+
+            let (next_line, rest) = match rest.strip_prefix(IGNORE_NEXT_LINE) {
+                Some(rest) => (true, rest),
+                None => (false, rest.strip_prefix(IGNORE)?),
+            };
+
+            if rest.starts_with(|c: char| c.is_alphanumeric() || c == '-' || c == '_') {
+                return None;
+            }
+
+            Some((next_line, Self::reason(rest)))
+        })
+    }
+
+    fn reason(rest: &str) -> Option<String> {
+        let start = rest.find("reason=\"")? + "reason=\"".len();
+        let length = rest[start..].find('"')?;
+
+        Some(rest[start..start + length].to_string())
+    }
+}
+
+impl Display for Suppression {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}:{}", self.path.display(), self.line)?;
+
+        if let Some(reason) = &self.reason {
+            write!(f, " ({})", reason)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_helpers;
+    use super::*;
+    use read_ctags::TokenKind;
+
+    fn build_definition(path: &str, address: &str, language: Language) -> CtagItem {
+        CtagItem {
+            address: address.to_string(),
+            language: Some(language),
+            ..test_helpers::build_definition("name", path, TokenKind::Method)
+        }
+    }
+
+    #[test]
+    fn finds_suppressions_on_or_before_the_definition() {
+        let ruby = "class Person\n  def name # unused:ignore\n  end\n\n  # unused:ignore-next-line reason=\"called from cron\"\n  def age\n  end\nend\n";
+
+        assert_eq!(
+            Suppression::find(
+                &build_definition(
+                    "app/models/person.rb",
+                    "/^  def name # unused:ignore$/",
+                    Language::Ruby
+                ),
+                ruby
+            ),
+            Some(Suppression {
+                path: PathBuf::from("app/models/person.rb"),
+                line: 2,
+                reason: None,
+            })
+        );
+        assert_eq!(
+            Suppression::find(
+                &build_definition("app/models/person.rb", "6", Language::Ruby),
+                ruby
+            ),
+            Some(Suppression {
+                path: PathBuf::from("app/models/person.rb"),
+                line: 6,
+                reason: Some(String::from("called from cron")),
+            })
+        );
+        assert_eq!(
+            Suppression::find(
+                &build_definition("app/models/person.rb", "1", Language::Ruby),
+                ruby
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn uses_comment_syntax_for_the_language() {
+        let javascript = "export const name = () => {}; // unused:ignore\n";
+        let mismatched = "export const name = () => {}; # unused:ignore\n";
+        let unrelated = "export const name = () => {}; // unused:ignored\n";
+        let definition = build_definition("src/name.js", "1", Language::JavaScript);
+
+        assert!(Suppression::find(&definition, javascript).is_some());
+        assert_eq!(Suppression::find(&definition, mismatched), None);
+        assert_eq!(Suppression::find(&definition, unrelated), None);
+    }
+}
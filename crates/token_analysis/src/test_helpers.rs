@@ -0,0 +1,66 @@
+use super::token_usage::TokenUsage;
+use project_configuration::ProjectConfiguration;
+use read_ctags::{CtagItem, Language, TokenKind};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use token_search::{Token, TokenSearchResult};
+
+/// A Ruby definition on the first line of the file
+pub fn build_definition(name: &str, path: &str, kind: TokenKind) -> CtagItem {
+    CtagItem {
+        name: name.to_string(),
+        file_path: PathBuf::from(path),
+        address: String::from("1"),
+        language: Some(Language::Ruby),
+        tags: BTreeMap::new(),
+        kind,
+    }
+}
+
+pub fn build_token(name: &str, path: &str, kind: TokenKind) -> Token {
+    Token::new(
+        name.to_string(),
+        vec![build_definition(name, path, kind)]
+            .into_iter()
+            .collect(),
+    )
+}
+
+/// Usage of a token whose only occurrence is its definition
+pub fn build_usage(name: &str, path: &str, kind: TokenKind) -> TokenUsage {
+    let mut occurrences = HashMap::new();
+    occurrences.insert(PathBuf::from(path), 1);
+
+    TokenUsage::new(
+        &ProjectConfiguration::default(),
+        TokenSearchResult {
+            token: build_token(name, path, kind),
+            occurrences,
+            occurrence_lines: HashMap::new(),
+            ref_occurrences: BTreeMap::new(),
+            consumer_occurrences: BTreeMap::new(),
+        },
+    )
+}
+
+/// Usage of a method defined once in each path, whose only occurrences are its definitions
+pub fn build_usage_defined_in(name: &str, paths: &[&str]) -> TokenUsage {
+    let token = Token::new(
+        name.to_string(),
+        paths
+            .iter()
+            .map(|path| build_definition(name, path, TokenKind::Method))
+            .collect(),
+    );
+
+    TokenUsage::new(
+        &ProjectConfiguration::default(),
+        TokenSearchResult {
+            token,
+            occurrences: paths.iter().map(|path| (PathBuf::from(path), 1)).collect(),
+            occurrence_lines: HashMap::new(),
+            ref_occurrences: BTreeMap::new(),
+            consumer_occurrences: BTreeMap::new(),
+        },
+    )
+}
@@ -1,18 +1,38 @@
 use super::analysis_filter::{AnalysisFilter, OrderField, SortOrder};
+use super::baseline::BaselineEntry;
+use super::definition_span::{DefinitionSpan, SpannedFiles};
 use super::occurrence_count::FileTypeCounts;
-use super::usage_likelihood::UsageLikelihood;
-use indicatif::ParallelProgressIterator;
-use itertools::{rev, Itertools};
+use super::reference_graph::{DeadCluster, ReferenceGraph};
+use super::source_files::SourceFiles;
+use super::suppression::Suppression;
+use super::usage_likelihood::{UsageLikelihood, UsageLikelihoodStatus};
+use codebase_files::{Blame, BlameCache};
+use itertools::Itertools;
 use project_configuration::ProjectConfiguration;
 use rayon::prelude::*;
+use read_ctags::CtagItem;
 use serde::Serialize;
-use token_search::{TokenSearchConfig, TokenSearchResult, TokenSearchResults};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use token_search::{ProgressPhase, TokenSearchConfig, TokenSearchResult, TokenSearchResults};
 
 #[derive(Serialize)]
 pub struct TokenUsage {
     pub file_type_counts: FileTypeCounts,
     pub usage_likelihood: UsageLikelihood,
     pub result: TokenSearchResult,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suppression: Option<Suppression>,
+    /// The most recent commit to modify any of the token's definitions, when blamed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blame: Option<Blame>,
+    /// Lines spanned by each definition
+    pub spans: Vec<DefinitionSpan>,
+    /// Estimated lines removed along with the token's definitions
+    pub removable_lines: usize,
 }
 
 impl TokenUsage {
@@ -30,53 +50,413 @@ impl TokenUsage {
             file_type_counts,
             usage_likelihood,
             result: token_search_result,
+            suppression: None,
+            blame: None,
+            spans: vec![],
+            removable_lines: 0,
+        }
+    }
+
+    /// Directory of the token's primary definition with a trailing slash, or `./` at the root
+    pub fn directory(&self) -> String {
+        match self
+            .result
+            .token
+            .first_path()
+            .parent()
+            .filter(|p| p != &Path::new(""))
+        {
+            Some(parent) => format!("{}/", parent.display()),
+            None => String::from("./"),
         }
     }
 }
 
 #[derive(Serialize)]
-pub struct TokenUsageResults(Vec<TokenUsage>);
+pub struct TokenUsageResults {
+    usages: Vec<TokenUsage>,
+    dead_clusters: Vec<DeadCluster>,
+}
 
 impl TokenUsageResults {
+    /// Calculate usage for every search result
+    ///
+    /// When `transitive`, tokens only referenced from the definitions of unused tokens are
+    /// reported as high likelihood; dead clusters are detected regardless. Suppressed and
+    /// allowlisted tokens are treated as used when following references.
+    ///
+    /// Definition spans are only calculated for usages the filter reports.
     pub fn calculate(
         token_search_config: &TokenSearchConfig,
         results: TokenSearchResults,
         config: &ProjectConfiguration,
+        filter: &AnalysisFilter,
+        transitive: bool,
     ) -> Self {
         let unwrapped_results = results.value().to_vec();
-        let size = &unwrapped_results.len();
+        let progress = &token_search_config.progress;
+
+        progress.start(ProgressPhase::Analyzing, unwrapped_results.len());
 
-        let results = unwrapped_results
+        let mut results = unwrapped_results
             .into_par_iter()
-            .progress_with(token_search_config.toggleable_progress_bar("🧐 Analyzing...", *size))
-            .map(move |r| TokenUsage::new(config, r))
+            .map(move |r| {
+                let usage = TokenUsage::new(config, r);
+                progress.advance(ProgressPhase::Analyzing, 1);
+                usage
+            })
             .collect::<Vec<_>>();
-        TokenUsageResults(results)
+
+        progress.finish(ProgressPhase::Analyzing);
+
+        let files = SourceFiles::read(token_search_config);
+        let spanned_files = SpannedFiles::new(token_search_config, &files);
+        Suppression::detect(&files, &mut results);
+
+        let graph = ReferenceGraph::build(
+            token_search_config,
+            config,
+            filter.allowlist(),
+            &spanned_files,
+            &results,
+        );
+
+        if transitive {
+            let dead_tokens = graph.dead_tokens();
+
+            for usage in results.iter_mut() {
+                let token = &usage.result.token.token;
+                let referenced_by = graph.referenced_by(token);
+
+                if dead_tokens.contains(token) && !referenced_by.is_empty() {
+                    usage
+                        .usage_likelihood
+                        .only_used_by_unused(referenced_by, &config.scoring.thresholds);
+                }
+            }
+        }
+
+        DefinitionSpan::detect(
+            &spanned_files,
+            results.iter_mut().filter(|u| Self::is_reported(filter, u)),
+        );
+
+        TokenUsageResults {
+            usages: results,
+            dead_clusters: graph.dead_clusters(),
+        }
+    }
+
+    /// Every token usage, regardless of filter
+    pub fn usages(&self) -> &[TokenUsage] {
+        &self.usages
+    }
+
+    /// Groups of tokens only referenced by one another
+    pub fn dead_clusters(&self) -> &[DeadCluster] {
+        &self.dead_clusters
     }
 
     pub fn filter(&self, config: &AnalysisFilter) -> Vec<&TokenUsage> {
-        let final_result = (*self.0)
-            .into_iter()
-            .filter(|a| {
+        self.usages
+            .iter()
+            .filter(|a| Self::is_reported(config, a))
+            .filter(|a| config.includes_age(a))
+            .sorted_by(|a, b| {
                 config
-                    .usage_likelihood_filter
-                    .contains(&a.usage_likelihood.status)
+                    .sort_order
+                    .iter()
+                    .fold(Ordering::Equal, |ordering, sort_order| {
+                        ordering.then_with(|| match sort_order {
+                            SortOrder::Ascending(field) => Self::compare(*field, a, b),
+                            SortOrder::Descending(field) => Self::compare(*field, b, a),
+                        })
+                    })
+                    .then_with(|| a.result.token.token.cmp(&b.result.token.token))
             })
-            .filter(|a| config.ignores_path(&a.result))
-            .sorted_by_key(|a| match config.sort_order {
-                SortOrder::Ascending(OrderField::Token) => a.result.token.token.to_string(),
-                SortOrder::Descending(OrderField::Token) => a.result.token.token.to_string(),
-                SortOrder::Ascending(OrderField::File) => {
-                    a.result.token.first_path().to_string_lossy().into_owned()
-                }
-                SortOrder::Descending(OrderField::File) => {
-                    a.result.token.first_path().to_string_lossy().into_owned()
+            .collect()
+    }
+
+    /// Findings the filter would report without its baseline, e.g. to record a new baseline
+    pub fn filter_ignoring_baseline(&self, config: &AnalysisFilter) -> Vec<&TokenUsage> {
+        self.usages
+            .iter()
+            .filter(|a| Self::is_reported_ignoring_baseline(config, a))
+            .filter(|a| config.includes_age(a))
+            .collect()
+    }
+
+    /// Findings hidden by the project's allowlist
+    pub fn allowlisted(&self, config: &AnalysisFilter) -> Vec<&TokenUsage> {
+        self.matching(config)
+            .into_iter()
+            .filter(|a| config.excludes_baselined(a) && !config.excludes_allowlisted(&a.result))
+            .collect()
+    }
+
+    /// Findings hidden by a suppression comment at their definition
+    pub fn suppressed(&self, config: &AnalysisFilter) -> Vec<&TokenUsage> {
+        self.matching(config)
+            .into_iter()
+            .filter(|a| config.excludes_baselined(a) && a.suppression.is_some())
+            .collect()
+    }
+
+    /// Baseline entries which no longer match any finding, ignoring the baseline itself
+    pub fn stale_baseline_entries<'a>(&self, config: &'a AnalysisFilter) -> Vec<&'a BaselineEntry> {
+        match config.baseline() {
+            Some(baseline) => baseline.stale_entries(&self.matching(config)),
+            None => vec![],
+        }
+    }
+
+    /// Attach the most recent commit to modify a definition of each token the filter would report
+    ///
+    /// Lines are read with `git blame` from the repository containing the root, and cached within
+    /// it. Definitions in files git can't blame (e.g. untracked files) are skipped.
+    pub fn blame_definitions(&mut self, root: &Path, config: &AnalysisFilter) -> io::Result<()> {
+        let mut cache = BlameCache::within(root)?;
+        let mut contents: HashMap<PathBuf, Option<String>> = HashMap::new();
+
+        let definition_lines: Vec<(usize, Vec<(PathBuf, usize)>)> = self
+            .usages
+            .iter()
+            .enumerate()
+            .filter(|(_, usage)| Self::is_reported(config, usage))
+            .map(|(idx, usage)| {
+                let lines = usage
+                    .result
+                    .token
+                    .definitions
+                    .iter()
+                    .filter_map(|definition| {
+                        let path = definition.file_path.to_path_buf();
+                        let line = definition.line().or_else(|| {
+                            contents
+                                .entry(path.clone())
+                                .or_insert_with(|| fs::read_to_string(root.join(&path)).ok())
+                                .as_deref()
+                                .and_then(|c| definition.resolve_line(c))
+                        })?;
+
+                        Some((path, line))
+                    })
+                    .collect();
+
+                (idx, lines)
+            })
+            .collect();
+
+        let mut lines_by_file: BTreeMap<&Path, BTreeSet<usize>> = BTreeMap::new();
+        for (path, line) in definition_lines.iter().flat_map(|(_, lines)| lines) {
+            lines_by_file.entry(path).or_default().insert(*line);
+        }
+
+        let mut blames: HashMap<&Path, HashMap<usize, Blame>> = HashMap::new();
+        let mut uncached = vec![];
+        for (path, lines) in lines_by_file {
+            match cache.get(path, &lines) {
+                Some(cached) => {
+                    blames.insert(path, cached);
                 }
-            });
+                None => uncached.push((path, lines)),
+            }
+        }
+
+        let blamed: Vec<_> = uncached
+            .par_iter()
+            .filter_map(|(path, lines)| cache.blame(path, lines).ok().map(|b| (*path, b)))
+            .collect();
+        for (path, blamed_lines) in blamed {
+            cache.insert(path, blamed_lines.clone());
+            blames.insert(path, blamed_lines);
+        }
+
+        // Failing to write the cache only means the next run blames these lines again
+        let _ = cache.save();
+
+        for (idx, lines) in definition_lines.iter() {
+            self.usages[*idx].blame = lines
+                .iter()
+                .filter_map(|(path, line)| blames.get(path.as_path())?.get(line))
+                .max_by_key(|b| b.timestamp)
+                .cloned();
+        }
+
+        Ok(())
+    }
+
+    fn matching(&self, config: &AnalysisFilter) -> Vec<&TokenUsage> {
+        self.usages
+            .iter()
+            .filter(|a| Self::matches(config, a))
+            .collect()
+    }
 
-        match config.sort_order {
-            SortOrder::Ascending(_) => final_result.collect(),
-            SortOrder::Descending(_) => rev(final_result).collect(),
+    fn matches(config: &AnalysisFilter, usage: &TokenUsage) -> bool {
+        config
+            .usage_likelihood_filter
+            .contains(&usage.usage_likelihood.status)
+            && config.ignores_path(&usage.result)
+            && config.includes_token(&usage.result)
+            && config.includes_usage_scope(&usage.usage_likelihood)
+            && config.includes_kind(&usage.result)
+    }
+
+    /// Does the usage match the filter without being baselined, suppressed, or allowlisted?
+    ///
+    /// Age isn't considered, since it requires blame.
+    fn is_reported(config: &AnalysisFilter, usage: &TokenUsage) -> bool {
+        Self::is_reported_ignoring_baseline(config, usage) && config.excludes_baselined(usage)
+    }
+
+    fn is_reported_ignoring_baseline(config: &AnalysisFilter, usage: &TokenUsage) -> bool {
+        Self::matches(config, usage)
+            && config.includes_suppressed(usage)
+            && config.excludes_allowlisted(&usage.result)
+    }
+
+    fn compare(field: OrderField, a: &TokenUsage, b: &TokenUsage) -> Ordering {
+        let (a_token, b_token) = (&a.result.token, &b.result.token);
+
+        match field {
+            OrderField::Token => a_token.token.cmp(&b_token.token),
+            OrderField::File => Self::first_path(a).cmp(Self::first_path(b)),
+            OrderField::Directory => Self::first_path(a)
+                .parent()
+                .cmp(&Self::first_path(b).parent()),
+            OrderField::Occurrences => a
+                .file_type_counts
+                .total()
+                .occurrence_count
+                .cmp(&b.file_type_counts.total().occurrence_count),
+            OrderField::Likelihood => Self::likelihood_rank(a).cmp(&Self::likelihood_rank(b)),
+            OrderField::Kind => Self::min_definition_value(a, |d| Some(d.kind.to_string()))
+                .cmp(&Self::min_definition_value(b, |d| Some(d.kind.to_string()))),
+            OrderField::Language => {
+                Self::min_definition_value(a, |d| d.language.map(|l| l.to_string())).cmp(
+                    &Self::min_definition_value(b, |d| d.language.map(|l| l.to_string())),
+                )
+            }
+            OrderField::Definitions => a_token.definitions.len().cmp(&b_token.definitions.len()),
+            OrderField::Score => a.usage_likelihood.score.cmp(&b.usage_likelihood.score),
+            OrderField::Age => Self::age(a).cmp(&Self::age(b)),
+            OrderField::Size => a.removable_lines.cmp(&b.removable_lines),
         }
     }
+
+    fn first_path(usage: &TokenUsage) -> &Path {
+        usage.result.token.first_path()
+    }
+
+    /// Older definitions sort after newer ones, and unblamed definitions first
+    fn age(usage: &TokenUsage) -> Option<Reverse<u64>> {
+        usage.blame.as_ref().map(|b| Reverse(b.timestamp))
+    }
+
+    fn likelihood_rank(usage: &TokenUsage) -> usize {
+        UsageLikelihoodStatus::all()
+            .iter()
+            .position(|s| *s == usage.usage_likelihood.status)
+            .unwrap_or(0)
+    }
+
+    fn min_definition_value<F>(usage: &TokenUsage, value: F) -> Option<String>
+    where
+        F: Fn(&CtagItem) -> Option<String>,
+    {
+        usage
+            .result
+            .token
+            .definitions
+            .iter()
+            .filter_map(value)
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_helpers::build_usage;
+    use super::*;
+    use read_ctags::TokenKind;
+
+    fn sorted_tokens(results: &TokenUsageResults, sort_order: Vec<SortOrder>) -> Vec<&str> {
+        let mut filter = AnalysisFilter::default();
+        filter.set_sort_order(sort_order);
+
+        results
+            .filter(&filter)
+            .iter()
+            .map(|u| u.result.token.token.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn sorts_by_each_key_in_order_breaking_ties_by_token() {
+        let results = TokenUsageResults {
+            usages: vec![
+                build_usage("full_name", "app/models/person.rb", TokenKind::Method),
+                build_usage("Person", "app/models/person.rb", TokenKind::Class),
+                build_usage("age", "app/models/person.rb", TokenKind::Method),
+                build_usage("Widget", "lib/widget.rb", TokenKind::Class),
+            ],
+            dead_clusters: vec![],
+        };
+
+        assert_eq!(
+            sorted_tokens(
+                &results,
+                vec!["kind".parse().unwrap(), "file:desc".parse().unwrap()]
+            ),
+            vec!["Widget", "Person", "age", "full_name"]
+        );
+        assert_eq!(
+            sorted_tokens(&results, vec!["directory:desc".parse().unwrap()]),
+            vec!["Widget", "Person", "age", "full_name"]
+        );
+        assert_eq!(
+            sorted_tokens(&results, vec!["token:desc".parse().unwrap()]),
+            vec!["full_name", "age", "Widget", "Person"]
+        );
+    }
+
+    #[test]
+    fn sorts_and_filters_by_definition_age() {
+        let blamed = |token: &str, days_ago: Option<u64>| {
+            let mut usage = build_usage(token, "lib/widget.rb", TokenKind::Method);
+            usage.blame = days_ago.map(|days| Blame {
+                commit: "1b2c3d4e5f60718293a4b5c6d7e8f90112233445".to_string(),
+                author: "Jane Doe".to_string(),
+                timestamp: crate::age::now() - days * 86_400,
+            });
+            usage
+        };
+        let results = TokenUsageResults {
+            usages: vec![
+                blamed("recent", Some(3)),
+                blamed("ancient", Some(1_500)),
+                blamed("untracked", None),
+                blamed("old", Some(200)),
+            ],
+            dead_clusters: vec![],
+        };
+
+        assert_eq!(
+            sorted_tokens(&results, vec!["age:desc".parse().unwrap()]),
+            vec!["ancient", "old", "recent", "untracked"]
+        );
+
+        let mut filter = AnalysisFilter::default();
+        filter.older_than = Some("180d".parse().unwrap());
+        filter.set_sort_order(vec!["age".parse().unwrap()]);
+
+        let tokens: Vec<_> = results
+            .filter(&filter)
+            .iter()
+            .map(|u| u.result.token.token.as_str())
+            .collect();
+        assert!(filter.requires_blame());
+        assert_eq!(tokens, vec!["old", "ancient"]);
+    }
 }
@@ -0,0 +1,153 @@
+use codebase_files::write_atomically;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const VERSION: u32 = 1;
+
+/// Default triage file, relative to the project root
+pub const TRIAGE_FILE_NAME: &str = ".unused-triage.json";
+
+/// What to do with a finding, decided while triaging
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriageDecision {
+    /// Remove the token's definitions later
+    DeleteLater,
+    /// The token is used; it was added to the allowlist
+    Ignore,
+    /// Decide later
+    Skip,
+}
+
+impl Display for TriageDecision {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            TriageDecision::DeleteLater => write!(f, "delete later"),
+            TriageDecision::Ignore => write!(f, "ignore"),
+            TriageDecision::Skip => write!(f, "skip"),
+        }
+    }
+}
+
+/// Decisions made while triaging findings, keyed by token and the path of its primary definition
+///
+/// Progress is saved after every decision so triage can resume in a later session.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Triage {
+    version: u32,
+    decisions: BTreeMap<String, TriageDecision>,
+}
+
+#[derive(Debug)]
+pub enum TriageError {
+    Io(PathBuf, io::Error),
+    Json(PathBuf, serde_json::Error),
+}
+
+impl Default for Triage {
+    fn default() -> Self {
+        Triage {
+            version: VERSION,
+            decisions: BTreeMap::new(),
+        }
+    }
+}
+
+impl Triage {
+    /// Load decisions, treating a missing file as no decisions
+    pub fn load(path: &Path) -> Result<Self, TriageError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| TriageError::Json(path.to_path_buf(), e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Triage::default()),
+            Err(e) => Err(TriageError::Io(path.to_path_buf(), e)),
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), TriageError> {
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| TriageError::Json(path.to_path_buf(), e))?;
+        write_atomically(path, &(contents + "\n"))
+            .map_err(|e| TriageError::Io(path.to_path_buf(), e))
+    }
+
+    pub fn decision(&self, token: &str, path: &Path) -> Option<TriageDecision> {
+        self.decisions.get(&Self::key(token, path)).copied()
+    }
+
+    /// Record a decision, or clear it when `None`
+    pub fn decide(&mut self, token: &str, path: &Path, decision: Option<TriageDecision>) {
+        let key = Self::key(token, path);
+
+        match decision {
+            Some(decision) => self.decisions.insert(key, decision),
+            None => self.decisions.remove(&key),
+        };
+    }
+
+    fn key(token: &str, path: &Path) -> String {
+        format!("{}#{}", path.display(), token)
+    }
+}
+
+impl Display for TriageError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            TriageError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            TriageError::Json(path, err) => write!(f, "{}: {}", path.display(), err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_and_clears_decisions() {
+        let person = Path::new("app/models/person.rb");
+        let mut triage = Triage::default();
+
+        triage.decide("full_name", person, Some(TriageDecision::Skip));
+        triage.decide("full_name", person, Some(TriageDecision::DeleteLater));
+        triage.decide("age", person, Some(TriageDecision::Ignore));
+        triage.decide("email", person, Some(TriageDecision::Skip));
+        triage.decide("email", person, None);
+
+        assert_eq!(
+            triage.decision("full_name", person),
+            Some(TriageDecision::DeleteLater)
+        );
+        assert_eq!(
+            triage.decision("full_name", Path::new("lib/person.rb")),
+            None
+        );
+        assert_eq!(triage.decision("email", person), None);
+        assert_eq!(triage.decision("age", person), Some(TriageDecision::Ignore));
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let mut triage = Triage::default();
+        triage.decide(
+            "full_name",
+            Path::new("app/models/person.rb"),
+            Some(TriageDecision::DeleteLater),
+        );
+
+        let json = serde_json::to_string(&triage).unwrap();
+        assert_eq!(
+            json,
+            r#"{"version":1,"decisions":{"app/models/person.rb#full_name":"delete_later"}}"#
+        );
+        assert_eq!(serde_json::from_str::<Triage>(&json).unwrap(), triage);
+        assert_eq!(
+            Triage::load(Path::new("missing.json")).unwrap(),
+            Triage::default()
+        );
+    }
+}
@@ -1,30 +1,33 @@
-use super::occurrence_count::FileTypeCounts;
-use project_configuration::ProjectConfiguration;
+use super::occurrence_count::{FileType, FileTypeCounts};
+use super::usage_likelihood_reason::{UsageLikelihoodReason, UsageScope};
+use itertools::Itertools;
+use project_configuration::{ProjectConfiguration, ScoreThresholds};
+use read_ctags::Visibility;
 use serde::Serialize;
 use std::default::Default;
 use std::fmt::{Display, Formatter};
+use std::path::Path;
 use std::str::FromStr;
 use token_search::TokenSearchResult;
 
 #[derive(Debug, PartialEq, Serialize)]
 pub struct UsageLikelihood {
     pub status: UsageLikelihoodStatus,
-    pub reason: String,
+    pub reason: UsageLikelihoodReason,
+    pub score: u32,
+    /// Where the token is exclusively used, regardless of the reason reported
+    #[serde(skip)]
+    pub usage_scope: Option<UsageScope>,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Default)]
 pub enum UsageLikelihoodStatus {
+    #[default]
     High,
     Medium,
     Low,
 }
 
-impl Default for UsageLikelihoodStatus {
-    fn default() -> Self {
-        UsageLikelihoodStatus::High
-    }
-}
-
 impl FromStr for UsageLikelihoodStatus {
     type Err = String;
 
@@ -33,10 +36,7 @@ impl FromStr for UsageLikelihoodStatus {
             "high" => Ok(UsageLikelihoodStatus::High),
             "medium" => Ok(UsageLikelihoodStatus::Medium),
             "low" => Ok(UsageLikelihoodStatus::Low),
-            val => Err(String::from(format!(
-                "Unable to parse usage likelihood: {}",
-                val
-            ))),
+            val => Err(format!("Unable to parse usage likelihood: {}", val)),
         }
     }
 }
@@ -62,63 +62,276 @@ impl UsageLikelihoodStatus {
 }
 
 impl UsageLikelihood {
+    /// Score the token, then explain the status its score falls within
+    ///
+    /// The reason is the strongest piece of evidence: the one whose implied status is closest to
+    /// the status from the score, taking the first in order of precedence when several are
+    /// equally close.
     pub fn calculate(
         project_configuration: &ProjectConfiguration,
         token_search_result: &TokenSearchResult,
         all_counts: &FileTypeCounts,
     ) -> Self {
-        match project_configuration.low_likelihood_match(token_search_result) {
-            Some(low_likelihood_config) => UsageLikelihood {
-                status: UsageLikelihoodStatus::Low,
-                reason: format!(
-                    "Token is classified as low-likelihood: {}",
-                    low_likelihood_config.name
-                ),
-            },
+        let low_likelihood_config = project_configuration.low_likelihood_match(token_search_result);
+        let consumers_with_usage = token_search_result.consumers_with_usage();
+        let refs_with_usage = token_search_result.refs_with_usage();
+        let usage_scope = Self::usage_scope(project_configuration, token_search_result);
+        let score = Self::score(
+            project_configuration,
+            token_search_result,
+            low_likelihood_config.is_some(),
+            usage_scope,
+        );
+        let status = Self::status(&project_configuration.scoring.thresholds, score);
+        let mut evidence = vec![];
+
+        if let Some(low_likelihood_config) = low_likelihood_config {
+            evidence.push(UsageLikelihoodReason::LowLikelihoodRule {
+                name: low_likelihood_config.name.clone(),
+            });
+        }
+        if !consumers_with_usage.is_empty() {
+            evidence.push(UsageLikelihoodReason::UsedByConsumers {
+                consumers: consumers_with_usage.into_iter().cloned().collect(),
+            });
+        }
+        if all_counts.total().occurrence_count == 1 {
+            if !refs_with_usage.is_empty() {
+                evidence.push(UsageLikelihoodReason::UsedOnOtherRefs {
+                    refs: refs_with_usage.into_iter().map(String::from).collect(),
+                });
+            }
+            evidence.push(UsageLikelihoodReason::SingleOccurrence);
+        }
+        match usage_scope {
+            Some(UsageScope::Tests) => evidence.push(UsageLikelihoodReason::UsedOnlyInTests),
+            Some(UsageScope::Config) => evidence.push(UsageLikelihoodReason::UsedOnlyInConfig),
             None => {
-                if all_counts.total().occurrence_count == 1 {
-                    UsageLikelihood {
-                        status: UsageLikelihoodStatus::High,
-                        reason: String::from("Only one occurrence exists"),
-                    }
-                } else {
-                    if all_counts.total().occurrence_count == 2
-                        && all_counts.test.occurrence_count == 1
-                    {
-                        UsageLikelihood {
-                            status: UsageLikelihoodStatus::Medium,
-                            reason: String::from("Only a test and definition exists"),
-                        }
-                    } else {
-                        UsageLikelihood {
-                            status: UsageLikelihoodStatus::Low,
-                            reason: String::from("Token has wide usage"),
-                        }
-                    }
+                if Self::usage_count(token_search_result) == 0 {
+                    evidence.push(UsageLikelihoodReason::OnlyDefinitions);
+                }
+                evidence.push(UsageLikelihoodReason::WideUsage);
+            }
+        }
+
+        let reason = evidence
+            .into_iter()
+            .min_by_key(|reason| {
+                Self::rank(Self::implied_status(reason)).abs_diff(Self::rank(status))
+            })
+            .unwrap_or(UsageLikelihoodReason::WideUsage);
+
+        UsageLikelihood {
+            status,
+            reason,
+            score,
+            usage_scope,
+        }
+    }
+
+    /// Treat the token as unused because every token referencing it is itself unused
+    pub(crate) fn only_used_by_unused(
+        &mut self,
+        tokens: Vec<String>,
+        thresholds: &ScoreThresholds,
+    ) {
+        self.status = UsageLikelihoodStatus::High;
+        self.score = self.score.max(thresholds.high);
+        self.reason = UsageLikelihoodReason::OnlyUsedByUnusedTokens { tokens };
+    }
+
+    /// A score from 0 to 100, where 100 is most likely to be unused
+    ///
+    /// Each signal of usage subtracts its configured weight from 100. Tokens used only in tests
+    /// or only in config subtract that scope's weight once, regardless of occurrence count. Each
+    /// definition beyond the first counts as usage, e.g. an override called polymorphically.
+    fn score(
+        project_configuration: &ProjectConfiguration,
+        token_search_result: &TokenSearchResult,
+        matches_rule: bool,
+        usage_scope: Option<UsageScope>,
+    ) -> u32 {
+        let weights = &project_configuration.scoring.weights;
+        let token = &token_search_result.token;
+        let mut penalty: usize = match usage_scope {
+            Some(UsageScope::Tests) => weights.test_only as usize,
+            Some(UsageScope::Config) => weights.config_only as usize,
+            None => 0,
+        };
+
+        for (path, count) in &token_search_result.occurrences {
+            if token.defined_paths.contains(path) {
+                penalty += weights.same_file as usize
+                    * Self::same_file_usage(token_search_result, path, *count);
+            } else if usage_scope.is_none() {
+                let weight = match FileTypeCounts::file_type(project_configuration, path) {
+                    FileType::ApplicationFile => weights.application,
+                    FileType::ConfigFile => weights.config,
+                    FileType::TestFile => weights.test,
+                    FileType::UnknownFile => weights.unknown,
+                };
+                penalty += weight as usize * count;
+            }
+        }
+
+        if matches_rule {
+            penalty += weights.rule as usize;
+        }
+
+        penalty += weights.other_definitions as usize * token.definitions.len().saturating_sub(1);
+        penalty += weights.other_refs as usize * token_search_result.refs_with_usage().len();
+        penalty += weights.consumers as usize * token_search_result.consumers_with_usage().len();
+
+        let bonus = match Self::visibility(token_search_result) {
+            Some(Visibility::Public) => {
+                penalty += weights.public as usize;
+                0
+            }
+            Some(Visibility::Private) if Self::usage_count(token_search_result) == 0 => {
+                weights.private as usize
+            }
+            _ => 0,
+        };
+
+        penalty += token
+            .definitions
+            .iter()
+            .map(|d| d.kind.to_string())
+            .unique()
+            .filter_map(|kind| weights.kinds.get(&kind))
+            .max()
+            .copied()
+            .unwrap_or(0) as usize;
+
+        (100 + bonus).saturating_sub(penalty).min(100) as u32
+    }
+
+    /// Public if any definition is public; private only if every definition is private
+    fn visibility(token_search_result: &TokenSearchResult) -> Option<Visibility> {
+        let visibilities: Vec<Option<Visibility>> = token_search_result
+            .token
+            .definitions
+            .iter()
+            .map(|d| d.visibility())
+            .collect();
+
+        if visibilities.contains(&Some(Visibility::Public)) {
+            Some(Visibility::Public)
+        } else if !visibilities.is_empty()
+            && visibilities.iter().all(|v| *v == Some(Visibility::Private))
+        {
+            Some(Visibility::Private)
+        } else {
+            None
+        }
+    }
+
+    /// Whether every occurrence outside the token's definitions is in test files, or in config
+    /// files
+    fn usage_scope(
+        project_configuration: &ProjectConfiguration,
+        token_search_result: &TokenSearchResult,
+    ) -> Option<UsageScope> {
+        let defined_paths = &token_search_result.token.defined_paths;
+        let mut file_types = vec![];
+
+        for (path, count) in &token_search_result.occurrences {
+            if defined_paths.contains(path) {
+                if Self::same_file_usage(token_search_result, path, *count) > 0 {
+                    return None;
                 }
+            } else {
+                file_types.push(FileTypeCounts::file_type(project_configuration, path));
             }
         }
+
+        if file_types.is_empty() {
+            None
+        } else if file_types.iter().all(|t| *t == FileType::TestFile) {
+            Some(UsageScope::Tests)
+        } else if file_types.iter().all(|t| *t == FileType::ConfigFile) {
+            Some(UsageScope::Config)
+        } else {
+            None
+        }
+    }
+
+    fn same_file_usage(
+        token_search_result: &TokenSearchResult,
+        path: &Path,
+        count: usize,
+    ) -> usize {
+        let definitions = token_search_result
+            .token
+            .definitions
+            .iter()
+            .filter(|d| d.file_path == path)
+            .count();
+
+        count.saturating_sub(definitions)
+    }
+
+    /// The status a piece of evidence suggests on its own
+    fn implied_status(reason: &UsageLikelihoodReason) -> UsageLikelihoodStatus {
+        match reason {
+            UsageLikelihoodReason::SingleOccurrence
+            | UsageLikelihoodReason::OnlyDefinitions
+            | UsageLikelihoodReason::OnlyUsedByUnusedTokens { .. } => UsageLikelihoodStatus::High,
+            UsageLikelihoodReason::UsedOnOtherRefs { .. }
+            | UsageLikelihoodReason::UsedOnlyInTests => UsageLikelihoodStatus::Medium,
+            UsageLikelihoodReason::LowLikelihoodRule { .. }
+            | UsageLikelihoodReason::UsedByConsumers { .. }
+            | UsageLikelihoodReason::UsedOnlyInConfig
+            | UsageLikelihoodReason::WideUsage => UsageLikelihoodStatus::Low,
+        }
+    }
+
+    fn rank(status: UsageLikelihoodStatus) -> usize {
+        match status {
+            UsageLikelihoodStatus::High => 0,
+            UsageLikelihoodStatus::Medium => 1,
+            UsageLikelihoodStatus::Low => 2,
+        }
+    }
+
+    fn status(thresholds: &ScoreThresholds, score: u32) -> UsageLikelihoodStatus {
+        if score >= thresholds.high {
+            UsageLikelihoodStatus::High
+        } else if score >= thresholds.medium {
+            UsageLikelihoodStatus::Medium
+        } else {
+            UsageLikelihoodStatus::Low
+        }
+    }
+
+    fn usage_count(token_search_result: &TokenSearchResult) -> usize {
+        token_search_result
+            .occurrences
+            .values()
+            .sum::<usize>()
+            .saturating_sub(token_search_result.token.definitions.len())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use project_configuration::PathPrefix;
     use read_ctags::{CtagItem, Language, TokenKind};
     use std::collections::{BTreeMap, HashMap};
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
     use token_search::Token;
 
-    fn build_ruby_file(token: &str, path: &PathBuf, kind: TokenKind) -> Token {
+    fn build_ruby_file(token: &str, path: &Path, kind: TokenKind) -> Token {
         Token::new(
             token.to_string(),
-            vec![CtagItem {
+            [CtagItem {
                 name: token.to_string(),
                 file_path: path.to_path_buf(),
                 address: String::from("1"),
                 language: Some(Language::Ruby),
                 tags: BTreeMap::new(),
-                kind: kind,
+                kind,
             }]
             .iter()
             .cloned()
@@ -132,7 +345,13 @@ mod tests {
         let token = build_ruby_file("Person", &path, TokenKind::Class);
         let mut occurrences = HashMap::new();
         occurrences.insert(path, 1);
-        let result = TokenSearchResult { token, occurrences };
+        let result = TokenSearchResult {
+            token,
+            occurrences,
+            occurrence_lines: HashMap::new(),
+            ref_occurrences: BTreeMap::new(),
+            consumer_occurrences: BTreeMap::new(),
+        };
         let file_type_counts = FileTypeCounts::new(&ProjectConfiguration::default(), &result);
 
         assert_eq!(
@@ -143,7 +362,282 @@ mod tests {
             ),
             UsageLikelihood {
                 status: UsageLikelihoodStatus::High,
-                reason: String::from("Only one occurrence exists")
+                reason: UsageLikelihoodReason::SingleOccurrence,
+                score: 100,
+                usage_scope: None,
+            }
+        );
+    }
+
+    #[test]
+    fn usage_only_on_other_refs_is_medium_likelihood() {
+        let path = PathBuf::from("app/models/person.rb");
+        let token = build_ruby_file("Person", &path, TokenKind::Class);
+        let mut occurrences = HashMap::new();
+        occurrences.insert(path.clone(), 1);
+        let mut release_occurrences = HashMap::new();
+        release_occurrences.insert(path, 1);
+        release_occurrences.insert(PathBuf::from("app/models/team.rb"), 2);
+        let mut ref_occurrences = BTreeMap::new();
+        ref_occurrences.insert(String::from("release"), release_occurrences);
+        let result = TokenSearchResult {
+            token,
+            occurrences,
+            occurrence_lines: HashMap::new(),
+            ref_occurrences,
+            consumer_occurrences: BTreeMap::new(),
+        };
+        let file_type_counts = FileTypeCounts::new(&ProjectConfiguration::default(), &result);
+
+        assert_eq!(
+            UsageLikelihood::calculate(
+                &ProjectConfiguration::default(),
+                &result,
+                &file_type_counts
+            ),
+            UsageLikelihood {
+                status: UsageLikelihoodStatus::Medium,
+                reason: UsageLikelihoodReason::UsedOnOtherRefs {
+                    refs: vec![String::from("release")]
+                },
+                score: 70,
+                usage_scope: None,
+            }
+        );
+    }
+
+    #[test]
+    fn usage_by_consumers_is_low_likelihood() {
+        let path = PathBuf::from("lib/library/person.rb");
+        let token = build_ruby_file("Person", &path, TokenKind::Class);
+        let mut occurrences = HashMap::new();
+        occurrences.insert(path, 1);
+        let mut app_occurrences = HashMap::new();
+        app_occurrences.insert(PathBuf::from("../app/app/models/team.rb"), 1);
+        let mut consumer_occurrences = BTreeMap::new();
+        consumer_occurrences.insert(PathBuf::from("../app"), app_occurrences);
+        let result = TokenSearchResult {
+            token,
+            occurrences,
+            occurrence_lines: HashMap::new(),
+            ref_occurrences: BTreeMap::new(),
+            consumer_occurrences,
+        };
+        let file_type_counts = FileTypeCounts::new(&ProjectConfiguration::default(), &result);
+
+        assert_eq!(
+            UsageLikelihood::calculate(
+                &ProjectConfiguration::default(),
+                &result,
+                &file_type_counts
+            ),
+            UsageLikelihood {
+                status: UsageLikelihoodStatus::Low,
+                reason: UsageLikelihoodReason::UsedByConsumers {
+                    consumers: vec![PathBuf::from("../app")]
+                },
+                score: 0,
+                usage_scope: None,
+            }
+        );
+    }
+
+    #[test]
+    fn score_uses_configured_weights_and_thresholds() {
+        let path = PathBuf::from("app/models/person.rb");
+        let token = build_ruby_file("Person", &path, TokenKind::Class);
+        let mut occurrences = HashMap::new();
+        occurrences.insert(path, 1);
+        occurrences.insert(PathBuf::from("test/models/person_test.rb"), 1);
+        let result = TokenSearchResult {
+            token,
+            occurrences,
+            occurrence_lines: HashMap::new(),
+            ref_occurrences: BTreeMap::new(),
+            consumer_occurrences: BTreeMap::new(),
+        };
+        let mut config = ProjectConfiguration::default();
+        let file_type_counts = FileTypeCounts::new(&config, &result);
+
+        let default_likelihood = UsageLikelihood::calculate(&config, &result, &file_type_counts);
+        assert_eq!(default_likelihood.score, 70);
+        assert_eq!(default_likelihood.status, UsageLikelihoodStatus::Medium);
+
+        config.scoring.weights.test_only = 5;
+        config
+            .scoring
+            .weights
+            .kinds
+            .insert(String::from("class"), 10);
+        config.scoring.thresholds.high = 85;
+
+        let configured_likelihood = UsageLikelihood::calculate(&config, &result, &file_type_counts);
+        assert_eq!(configured_likelihood.score, 85);
+        assert_eq!(configured_likelihood.status, UsageLikelihoodStatus::High);
+        assert_eq!(
+            configured_likelihood.reason,
+            UsageLikelihoodReason::UsedOnlyInTests
+        );
+        assert_eq!(configured_likelihood.usage_scope, Some(UsageScope::Tests));
+    }
+
+    #[test]
+    fn usage_only_in_tests_or_config_is_classified_regardless_of_count() {
+        let path = PathBuf::from("app/models/person.rb");
+        let token = build_ruby_file("Person", &path, TokenKind::Class);
+        let mut test_occurrences = HashMap::new();
+        test_occurrences.insert(path.clone(), 1);
+        test_occurrences.insert(PathBuf::from("test/models/person_test.rb"), 4);
+        test_occurrences.insert(PathBuf::from("test/models/team_test.rb"), 1);
+        let mut config_occurrences = HashMap::new();
+        config_occurrences.insert(path.clone(), 1);
+        config_occurrences.insert(PathBuf::from("config/initializers/people.rb"), 3);
+        let mut same_file_occurrences = test_occurrences.clone();
+        same_file_occurrences.insert(path, 2);
+        let config = ProjectConfiguration {
+            config_file: vec![PathPrefix::new("config/")],
+            ..Default::default()
+        };
+
+        let likelihood = |occurrences: HashMap<PathBuf, usize>| {
+            let result = TokenSearchResult {
+                token: token.clone(),
+                occurrences,
+                occurrence_lines: HashMap::new(),
+                ref_occurrences: BTreeMap::new(),
+                consumer_occurrences: BTreeMap::new(),
+            };
+            let file_type_counts = FileTypeCounts::new(&config, &result);
+            UsageLikelihood::calculate(&config, &result, &file_type_counts)
+        };
+
+        let tests_only = likelihood(test_occurrences);
+        assert_eq!(tests_only.reason, UsageLikelihoodReason::UsedOnlyInTests);
+        assert_eq!(tests_only.status, UsageLikelihoodStatus::Medium);
+
+        let config_only = likelihood(config_occurrences);
+        assert_eq!(config_only.reason, UsageLikelihoodReason::UsedOnlyInConfig);
+        assert_eq!(config_only.status, UsageLikelihoodStatus::Low);
+
+        let same_file = likelihood(same_file_occurrences);
+        assert_eq!(same_file.reason, UsageLikelihoodReason::WideUsage);
+    }
+
+    #[test]
+    fn visibility_adjusts_score() {
+        let path = PathBuf::from("lib/library/person.rb");
+        let build_result = |access: &str, occurrences: &[(&str, usize)]| {
+            let mut token = build_ruby_file("name", &path, TokenKind::Method);
+            let mut definition = token.definitions.iter().next().unwrap().clone();
+            definition
+                .tags
+                .insert(String::from("access"), access.to_string());
+            token = Token::new(token.token, vec![definition].into_iter().collect());
+
+            let mut found = HashMap::new();
+            found.insert(path.clone(), 1);
+            for (p, count) in occurrences {
+                found.insert(PathBuf::from(p), *count);
+            }
+
+            TokenSearchResult {
+                token,
+                occurrences: found,
+                occurrence_lines: HashMap::new(),
+                ref_occurrences: BTreeMap::new(),
+                consumer_occurrences: BTreeMap::new(),
+            }
+        };
+        let mut library_config = ProjectConfiguration::default();
+        library_config.scoring.weights.public = 40;
+        let likelihood = |config: &ProjectConfiguration, result: &TokenSearchResult| {
+            let file_type_counts = FileTypeCounts::new(config, result);
+            UsageLikelihood::calculate(config, result, &file_type_counts)
+        };
+
+        let private_unused = build_result("private", &[]);
+        assert_eq!(
+            likelihood(&ProjectConfiguration::default(), &private_unused).score,
+            100
+        );
+
+        let private_tested = build_result("private", &[("test/person_test.rb", 2)]);
+        assert_eq!(
+            likelihood(&ProjectConfiguration::default(), &private_tested).score,
+            70
+        );
+
+        let private_called = build_result("private", &[("lib/library/team.rb", 1)]);
+        let protected_called = build_result("protected", &[("lib/library/team.rb", 1)]);
+        assert_eq!(
+            likelihood(&ProjectConfiguration::default(), &private_called),
+            likelihood(&ProjectConfiguration::default(), &protected_called)
+        );
+        assert_eq!(
+            likelihood(&ProjectConfiguration::default(), &private_called).status,
+            UsageLikelihoodStatus::Low
+        );
+
+        let public_unused = build_result("public", &[]);
+        assert_eq!(
+            likelihood(&ProjectConfiguration::default(), &public_unused).status,
+            UsageLikelihoodStatus::High
+        );
+        let public_library = likelihood(&library_config, &public_unused);
+        assert_eq!(public_library.score, 60);
+        assert_eq!(public_library.status, UsageLikelihoodStatus::Medium);
+        assert_eq!(
+            public_library.reason,
+            UsageLikelihoodReason::SingleOccurrence
+        );
+    }
+
+    #[test]
+    fn tokens_defined_several_times_are_low_likelihood_unless_configured() {
+        let path = PathBuf::from("app/models/person.rb");
+        let other_path = PathBuf::from("app/models/team.rb");
+        let definitions = [&path, &other_path]
+            .iter()
+            .map(|p| CtagItem {
+                name: String::from("display_name"),
+                file_path: p.to_path_buf(),
+                address: String::from("1"),
+                language: Some(Language::Ruby),
+                tags: BTreeMap::new(),
+                kind: TokenKind::Method,
+            })
+            .collect();
+        let mut occurrences = HashMap::new();
+        occurrences.insert(path, 1);
+        occurrences.insert(other_path, 1);
+        let result = TokenSearchResult {
+            token: Token::new(String::from("display_name"), definitions),
+            occurrences,
+            occurrence_lines: HashMap::new(),
+            ref_occurrences: BTreeMap::new(),
+            consumer_occurrences: BTreeMap::new(),
+        };
+        let mut config = ProjectConfiguration::default();
+        let file_type_counts = FileTypeCounts::new(&config, &result);
+
+        assert_eq!(
+            UsageLikelihood::calculate(&config, &result, &file_type_counts),
+            UsageLikelihood {
+                status: UsageLikelihoodStatus::Low,
+                reason: UsageLikelihoodReason::WideUsage,
+                score: 40,
+                usage_scope: None,
+            }
+        );
+
+        config.scoring.weights.other_definitions = 0;
+        assert_eq!(
+            UsageLikelihood::calculate(&config, &result, &file_type_counts),
+            UsageLikelihood {
+                status: UsageLikelihoodStatus::High,
+                reason: UsageLikelihoodReason::OnlyDefinitions,
+                score: 100,
+                usage_scope: None,
             }
         );
     }
@@ -0,0 +1,144 @@
+use itertools::Itertools;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Where a token is exclusively used outside its definitions
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UsageScope {
+    Tests,
+    Config,
+}
+
+impl FromStr for UsageScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_ref() {
+            "tests" | "test" => Ok(UsageScope::Tests),
+            "config" => Ok(UsageScope::Config),
+            val => Err(format!("Unable to parse usage scope: {}", val)),
+        }
+    }
+}
+
+impl Display for UsageScope {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            UsageScope::Tests => write!(f, "tests"),
+            UsageScope::Config => write!(f, "config"),
+        }
+    }
+}
+
+/// Why a token was assigned its usage likelihood
+#[derive(Clone, Debug, PartialEq)]
+pub enum UsageLikelihoodReason {
+    LowLikelihoodRule { name: String },
+    UsedByConsumers { consumers: Vec<PathBuf> },
+    UsedOnOtherRefs { refs: Vec<String> },
+    OnlyUsedByUnusedTokens { tokens: Vec<String> },
+    SingleOccurrence,
+    UsedOnlyInTests,
+    UsedOnlyInConfig,
+    OnlyDefinitions,
+    WideUsage,
+}
+
+impl UsageLikelihoodReason {
+    /// A stable identifier for the reason, suitable for aggregation
+    pub fn code(&self) -> &'static str {
+        match self {
+            UsageLikelihoodReason::LowLikelihoodRule { .. } => "low_likelihood_rule",
+            UsageLikelihoodReason::UsedByConsumers { .. } => "used_by_consumers",
+            UsageLikelihoodReason::UsedOnOtherRefs { .. } => "used_on_other_refs",
+            UsageLikelihoodReason::OnlyUsedByUnusedTokens { .. } => "only_used_by_unused_tokens",
+            UsageLikelihoodReason::SingleOccurrence => "single_occurrence",
+            UsageLikelihoodReason::UsedOnlyInTests => "test_only",
+            UsageLikelihoodReason::UsedOnlyInConfig => "config_only",
+            UsageLikelihoodReason::OnlyDefinitions => "only_definitions",
+            UsageLikelihoodReason::WideUsage => "wide_usage",
+        }
+    }
+}
+
+impl Display for UsageLikelihoodReason {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            UsageLikelihoodReason::LowLikelihoodRule { name } => {
+                write!(f, "Token is classified as low-likelihood: {}", name)
+            }
+            UsageLikelihoodReason::UsedByConsumers { consumers } => write!(
+                f,
+                "Used by consumers: {}",
+                consumers.iter().map(|c| c.to_string_lossy()).join(", ")
+            ),
+            UsageLikelihoodReason::UsedOnOtherRefs { refs } => {
+                write!(f, "Only used on other branches: {}", refs.join(", "))
+            }
+            UsageLikelihoodReason::OnlyUsedByUnusedTokens { tokens } => {
+                write!(f, "Only used by unused tokens: {}", tokens.join(", "))
+            }
+            UsageLikelihoodReason::SingleOccurrence => write!(f, "Only one occurrence exists"),
+            UsageLikelihoodReason::UsedOnlyInTests => write!(f, "Only used in tests"),
+            UsageLikelihoodReason::UsedOnlyInConfig => write!(f, "Only used in config"),
+            UsageLikelihoodReason::OnlyDefinitions => write!(f, "Only definitions exist"),
+            UsageLikelihoodReason::WideUsage => write!(f, "Token has wide usage"),
+        }
+    }
+}
+
+impl Serialize for UsageLikelihoodReason {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("code", self.code())?;
+        map.serialize_entry("message", &self.to_string())?;
+
+        match self {
+            UsageLikelihoodReason::LowLikelihoodRule { name } => {
+                map.serialize_entry("name", name)?;
+            }
+            UsageLikelihoodReason::UsedByConsumers { consumers } => {
+                map.serialize_entry("consumers", consumers)?;
+            }
+            UsageLikelihoodReason::UsedOnOtherRefs { refs } => {
+                map.serialize_entry("refs", refs)?;
+            }
+            UsageLikelihoodReason::OnlyUsedByUnusedTokens { tokens } => {
+                map.serialize_entry("tokens", tokens)?;
+            }
+            _ => {}
+        }
+
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_code_message_and_data() {
+        let reason = UsageLikelihoodReason::LowLikelihoodRule {
+            name: String::from("Pundit Policies"),
+        };
+
+        assert_eq!(
+            serde_json::to_string(&reason).unwrap(),
+            r#"{"code":"low_likelihood_rule","message":"Token is classified as low-likelihood: Pundit Policies","name":"Pundit Policies"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&UsageLikelihoodReason::SingleOccurrence).unwrap(),
+            r#"{"code":"single_occurrence","message":"Only one occurrence exists"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&UsageLikelihoodReason::UsedOnlyInTests).unwrap(),
+            r#"{"code":"test_only","message":"Only used in tests"}"#
+        );
+    }
+}
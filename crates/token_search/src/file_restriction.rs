@@ -0,0 +1,286 @@
+use super::token_search::LanguageRestriction;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use read_ctags::Language;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A FileCategory groups files which commonly mention tokens without using them
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum FileCategory {
+    /// READMEs, changelogs, licenses, and anything within `doc/` or `docs/`
+    Documentation,
+    /// Anything within `fixtures/`, `__fixtures__/`, or `testdata/`
+    Fixtures,
+}
+
+const DOCUMENTATION_EXTENSIONS: [&str; 6] = ["md", "markdown", "rst", "txt", "adoc", "rdoc"];
+const DOCUMENTATION_NAMES: [&str; 7] = [
+    "readme",
+    "changelog",
+    "history",
+    "news",
+    "license",
+    "contributing",
+    "authors",
+];
+const DOCUMENTATION_DIRECTORIES: [&str; 2] = ["doc", "docs"];
+const FIXTURE_DIRECTORIES: [&str; 3] = ["fixtures", "__fixtures__", "testdata"];
+
+impl FileCategory {
+    /// All categories
+    pub fn all() -> Vec<FileCategory> {
+        vec![FileCategory::Documentation, FileCategory::Fixtures]
+    }
+
+    /// Does the path fall into this category?
+    pub fn matches(&self, path: &Path) -> bool {
+        match self {
+            FileCategory::Documentation => {
+                Self::has_extension(path, &DOCUMENTATION_EXTENSIONS)
+                    || Self::has_name(path, &DOCUMENTATION_NAMES)
+                    || Self::within_directory(path, &DOCUMENTATION_DIRECTORIES)
+            }
+            FileCategory::Fixtures => Self::within_directory(path, &FIXTURE_DIRECTORIES),
+        }
+    }
+
+    fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .map(|e| extensions.contains(&e.to_lowercase().as_str()))
+            .unwrap_or(false)
+    }
+
+    fn has_name(path: &Path, names: &[&str]) -> bool {
+        path.file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.split('.').next())
+            .map(|n| names.contains(&n.to_lowercase().as_str()))
+            .unwrap_or(false)
+    }
+
+    fn within_directory(path: &Path, directories: &[&str]) -> bool {
+        path.parent()
+            .map(|parent| {
+                parent
+                    .components()
+                    .filter_map(|c| c.as_os_str().to_str())
+                    .any(|c| directories.contains(&c))
+            })
+            .unwrap_or(false)
+    }
+}
+
+impl std::fmt::Display for FileCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FileCategory::Documentation => write!(f, "documentation"),
+            FileCategory::Fixtures => write!(f, "fixtures"),
+        }
+    }
+}
+
+impl FromStr for FileCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_ref() {
+            "documentation" | "docs" => Ok(FileCategory::Documentation),
+            "fixtures" => Ok(FileCategory::Fixtures),
+            v => Err(format!("Unable to parse file category: {}", v)),
+        }
+    }
+}
+
+/// FileRestriction limits which files are searched for token occurrences
+///
+/// This is distinct from `LanguageRestriction` on `TokenSearchConfig`, which limits which tokens
+/// are searched for. Files where a token is defined are always searched.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileRestriction {
+    /// Restrict files searched based on their language (determined by file extension)
+    pub languages: LanguageRestriction,
+    /// Files matching any of these globs are not searched
+    pub excluded_globs: Vec<Glob>,
+    /// Files within any of these categories are not searched
+    pub excluded_categories: BTreeSet<FileCategory>,
+}
+
+impl Default for FileRestriction {
+    fn default() -> Self {
+        FileRestriction {
+            languages: LanguageRestriction::NoRestriction,
+            excluded_globs: vec![],
+            excluded_categories: BTreeSet::new(),
+        }
+    }
+}
+
+impl FileRestriction {
+    /// Combine two restrictions
+    ///
+    /// Excluded globs and categories from both are kept; the other restriction's languages take
+    /// precedence unless it has no language restriction.
+    pub fn combine(mut self, other: FileRestriction) -> Self {
+        if other.languages != LanguageRestriction::NoRestriction {
+            self.languages = other.languages;
+        }
+        for glob in other.excluded_globs {
+            if !self.excluded_globs.contains(&glob) {
+                self.excluded_globs.push(glob);
+            }
+        }
+        self.excluded_categories.extend(other.excluded_categories);
+        self
+    }
+
+    /// Does this restriction exclude anything?
+    pub fn is_unrestricted(&self) -> bool {
+        self.languages == LanguageRestriction::NoRestriction
+            && self.excluded_globs.is_empty()
+            && self.excluded_categories.is_empty()
+    }
+
+    pub(crate) fn matcher(&self) -> FileRestrictionMatcher<'_> {
+        let mut builder = GlobSetBuilder::new();
+        for glob in self.excluded_globs.iter() {
+            builder.add(glob.clone());
+        }
+
+        FileRestrictionMatcher {
+            restriction: self,
+            globs: builder.build().unwrap_or_else(|_| GlobSet::empty()),
+        }
+    }
+
+    fn allows_language(&self, path: &Path) -> bool {
+        let language = Language::from_path(path);
+
+        match (&self.languages, language) {
+            (LanguageRestriction::NoRestriction, _) => true,
+            (LanguageRestriction::Only(languages), Some(lang)) => languages.contains(&lang),
+            (LanguageRestriction::Only(_), None) => false,
+            (LanguageRestriction::Except(languages), Some(lang)) => !languages.contains(&lang),
+            (LanguageRestriction::Except(_), None) => true,
+        }
+    }
+}
+
+impl std::fmt::Display for FileRestriction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.is_unrestricted() {
+            return write!(f, "all files");
+        }
+
+        let mut parts = vec![];
+
+        if self.languages != LanguageRestriction::NoRestriction {
+            parts.push(self.languages.to_string());
+        }
+
+        let excluded: Vec<String> = self
+            .excluded_categories
+            .iter()
+            .map(|c| c.to_string())
+            .chain(self.excluded_globs.iter().map(|g| g.to_string()))
+            .collect();
+        if !excluded.is_empty() {
+            parts.push(format!("excluding {}", excluded.join(", ")));
+        }
+
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
+pub(crate) struct FileRestrictionMatcher<'a> {
+    restriction: &'a FileRestriction,
+    globs: GlobSet,
+}
+
+impl<'a> FileRestrictionMatcher<'a> {
+    pub(crate) fn allows(&self, path: &Path) -> bool {
+        let path = path.strip_prefix("./").unwrap_or(path);
+
+        self.restriction.allows_language(path)
+            && !self
+                .restriction
+                .excluded_categories
+                .iter()
+                .any(|c| c.matches(path))
+            && !self.globs.is_match(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn allows(restriction: &FileRestriction, path: &str) -> bool {
+        restriction.matcher().allows(Path::new(path))
+    }
+
+    #[test]
+    fn categorizes_documentation() {
+        let documentation = FileCategory::Documentation;
+
+        assert!(documentation.matches(Path::new("README.md")));
+        assert!(documentation.matches(Path::new("CHANGELOG")));
+        assert!(documentation.matches(Path::new("LICENSE.txt")));
+        assert!(documentation.matches(Path::new("docs/usage.html")));
+        assert!(!documentation.matches(Path::new("src/docs.rs")));
+        assert!(!documentation.matches(Path::new("app/models/readme_generator.rb")));
+    }
+
+    #[test]
+    fn categorizes_fixtures() {
+        let fixtures = FileCategory::Fixtures;
+
+        assert!(fixtures.matches(Path::new("spec/fixtures/people.json")));
+        assert!(fixtures.matches(Path::new("test/fixtures/files/upload.csv")));
+        assert!(!fixtures.matches(Path::new("lib/fixtures.rb")));
+    }
+
+    #[test]
+    fn restricts_by_language_glob_and_category() {
+        let restriction = FileRestriction {
+            languages: LanguageRestriction::Except(
+                [Language::JSON].iter().cloned().collect::<HashSet<_>>(),
+            ),
+            excluded_globs: vec![Glob::new("vendor/**").unwrap()],
+            excluded_categories: [FileCategory::Documentation].iter().cloned().collect(),
+        };
+
+        assert!(allows(&restriction, "app/models/person.rb"));
+        assert!(allows(&restriction, "./app/models/person.rb"));
+        assert!(!allows(&restriction, "package.json"));
+        assert!(!allows(&restriction, "vendor/gems/thing.rb"));
+        assert!(!allows(&restriction, "./README.md"));
+        assert_eq!(
+            restriction.to_string(),
+            "except JSON; excluding documentation, vendor/**"
+        );
+    }
+
+    #[test]
+    fn combines_restrictions() {
+        let project = FileRestriction {
+            excluded_categories: [FileCategory::Documentation].iter().cloned().collect(),
+            ..Default::default()
+        };
+        let cli = FileRestriction {
+            languages: LanguageRestriction::Only(
+                [Language::Ruby].iter().cloned().collect::<HashSet<_>>(),
+            ),
+            excluded_categories: [FileCategory::Fixtures].iter().cloned().collect(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            project.combine(cli).to_string(),
+            "only Ruby; excluding documentation, fixtures"
+        );
+        assert_eq!(FileRestriction::default().to_string(), "all files");
+    }
+}
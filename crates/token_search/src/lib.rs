@@ -4,8 +4,12 @@
 //!
 //! It does so relatively quickly by leveraging Aho-Corasick. It constructs the trie-like structure
 //! with the provided tokens and does a single pass over each file.
+mod file_restriction;
+mod progress;
 mod token;
 mod token_search;
 
+pub use self::file_restriction::*;
+pub use self::progress::*;
 pub use self::token::*;
 pub use self::token_search::*;
@@ -0,0 +1,206 @@
+use indicatif::{ProgressBar, ProgressStyle};
+use serde::Serialize;
+use std::convert::TryInto;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::RwLock;
+
+/// A ProgressPhase identifies the unit of work being reported
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressPhase {
+    /// Searching files for token occurrences
+    Searching,
+    /// Searching files within other git references for token occurrences
+    SearchingRefs,
+    /// Analyzing search results
+    Analyzing,
+}
+
+impl ProgressPhase {
+    fn label(&self) -> &'static str {
+        match self {
+            ProgressPhase::Searching => "🤔 Working...",
+            ProgressPhase::SearchingRefs => "🌿 Branches...",
+            ProgressPhase::Analyzing => "🧐 Analyzing...",
+        }
+    }
+}
+
+/// A ProgressSink receives phases and counts as work is performed
+///
+/// Implementations must be safe to call from multiple threads, as searching and analyzing both
+/// happen in parallel.
+pub trait ProgressSink: Send + Sync {
+    /// A phase has started, with `total` units of work to perform
+    fn start(&self, phase: ProgressPhase, total: usize);
+    /// `delta` units of work have completed within a phase
+    fn advance(&self, phase: ProgressPhase, delta: usize);
+    /// A phase has completed
+    fn finish(&self, phase: ProgressPhase);
+}
+
+/// SilentProgress discards all progress
+pub struct SilentProgress;
+
+impl ProgressSink for SilentProgress {
+    fn start(&self, _phase: ProgressPhase, _total: usize) {}
+    fn advance(&self, _phase: ProgressPhase, _delta: usize) {}
+    fn finish(&self, _phase: ProgressPhase) {}
+}
+
+/// TerminalProgress renders a progress bar per phase to STDERR
+pub struct TerminalProgress {
+    bar: RwLock<ProgressBar>,
+}
+
+impl Default for TerminalProgress {
+    fn default() -> Self {
+        TerminalProgress {
+            bar: RwLock::new(ProgressBar::hidden()),
+        }
+    }
+}
+
+impl TerminalProgress {
+    fn progress_bar(prefix: &str, size: usize) -> ProgressBar {
+        let pb = ProgressBar::new(size.try_into().unwrap());
+        pb.set_message(prefix);
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template("{msg:12} [{bar:40.cyan/blue}] {pos:>7}/{len:7}({eta})")
+                .progress_chars("#>-"),
+        );
+        pb
+    }
+}
+
+impl ProgressSink for TerminalProgress {
+    fn start(&self, phase: ProgressPhase, total: usize) {
+        if let Ok(mut bar) = self.bar.write() {
+            *bar = Self::progress_bar(phase.label(), total);
+        }
+    }
+
+    fn advance(&self, _phase: ProgressPhase, delta: usize) {
+        if let Ok(bar) = self.bar.read() {
+            bar.inc(delta.try_into().unwrap());
+        }
+    }
+
+    fn finish(&self, _phase: ProgressPhase) {
+        if let Ok(bar) = self.bar.read() {
+            bar.finish();
+        }
+    }
+}
+
+/// A single event written by `JsonProgress`
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    /// A phase has started
+    Start {
+        /// The phase started
+        phase: ProgressPhase,
+        /// Units of work within the phase
+        total: usize,
+    },
+    /// Work has completed within a phase
+    Progress {
+        /// The phase being worked on
+        phase: ProgressPhase,
+        /// Units of work completed so far
+        position: usize,
+        /// Units of work within the phase
+        total: usize,
+    },
+    /// A phase has completed
+    Finish {
+        /// The phase completed
+        phase: ProgressPhase,
+        /// Units of work completed
+        position: usize,
+        /// Units of work within the phase
+        total: usize,
+    },
+}
+
+/// JsonProgress writes one JSON object per line to STDERR for each event
+///
+/// Progress events are emitted at most once per percentage point to keep output manageable on
+/// large codebases.
+#[derive(Default)]
+pub struct JsonProgress {
+    total: AtomicUsize,
+    position: AtomicUsize,
+    last_percent: AtomicUsize,
+}
+
+impl JsonProgress {
+    fn emit(&self, event: &ProgressEvent) {
+        if let Ok(line) = serde_json::to_string(event) {
+            let _ = writeln!(io::stderr().lock(), "{}", line);
+        }
+    }
+
+    fn percent(position: usize, total: usize) -> usize {
+        (position * 100).checked_div(total).unwrap_or(100)
+    }
+}
+
+impl ProgressSink for JsonProgress {
+    fn start(&self, phase: ProgressPhase, total: usize) {
+        self.total.store(total, Ordering::SeqCst);
+        self.position.store(0, Ordering::SeqCst);
+        self.last_percent.store(0, Ordering::SeqCst);
+        self.emit(&ProgressEvent::Start { phase, total });
+    }
+
+    fn advance(&self, phase: ProgressPhase, delta: usize) {
+        let total = self.total.load(Ordering::SeqCst);
+        let position = self.position.fetch_add(delta, Ordering::SeqCst) + delta;
+        let percent = Self::percent(position, total);
+        let last_percent = self.last_percent.fetch_max(percent, Ordering::SeqCst);
+
+        if percent > last_percent {
+            self.emit(&ProgressEvent::Progress {
+                phase,
+                position,
+                total,
+            });
+        }
+    }
+
+    fn finish(&self, phase: ProgressPhase) {
+        self.emit(&ProgressEvent::Finish {
+            phase,
+            position: self.position.load(Ordering::SeqCst),
+            total: self.total.load(Ordering::SeqCst),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_events_as_tagged_json() {
+        assert_eq!(
+            serde_json::to_string(&ProgressEvent::Progress {
+                phase: ProgressPhase::Searching,
+                position: 5,
+                total: 10,
+            })
+            .unwrap(),
+            r#"{"event":"progress","phase":"searching","position":5,"total":10}"#
+        );
+    }
+
+    #[test]
+    fn percent_handles_empty_phases() {
+        assert_eq!(JsonProgress::percent(0, 0), 100);
+        assert_eq!(JsonProgress::percent(1, 3), 33);
+    }
+}
@@ -1,11 +1,12 @@
 use itertools::Itertools;
 use read_ctags::{CtagItem, Language, ReadCtagsError, Tags, TagsReader};
-use serde::Serialize;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::cmp::Ordering;
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// A token based on a set of `CtagItem`s
-#[derive(Clone, Serialize)]
+#[derive(Clone)]
 pub struct Token {
     /// The token value
     pub token: String,
@@ -13,6 +14,7 @@ pub struct Token {
     pub definitions: Tags,
     /// The paths where a token is defined
     pub defined_paths: HashSet<PathBuf>,
+    test_paths: HashSet<PathBuf>,
 }
 
 impl Token {
@@ -27,9 +29,26 @@ impl Token {
             token,
             definitions,
             defined_paths,
+            test_paths: HashSet::new(),
         }
     }
 
+    /// Record which defined paths are test files, according to the provided classifier
+    ///
+    /// Definitions in test files sort after those in application files; until classified, no
+    /// definition is considered to be in a test file.
+    pub fn classify_test_paths<F>(&mut self, is_test_file: F)
+    where
+        F: Fn(&Path) -> bool,
+    {
+        self.test_paths = self
+            .defined_paths
+            .iter()
+            .filter(|path| is_test_file(path))
+            .cloned()
+            .collect();
+    }
+
     /// Load tokens after reading tags
     pub fn all(tags_reader: &TagsReader) -> Result<(PathBuf, Vec<Token>), ReadCtagsError> {
         tags_reader.load().map(|tags_file| {
@@ -40,9 +59,39 @@ impl Token {
         })
     }
 
-    /// Provide the first path in the list of defined paths
-    pub fn first_path(&self) -> &PathBuf {
-        self.defined_paths.iter().nth(0).unwrap()
+    /// Provide the path of the primary definition, or an empty path without any definitions
+    pub fn first_path(&self) -> &Path {
+        self.primary_definition()
+            .map(|d| d.file_path.as_path())
+            .unwrap_or_else(|| Path::new(""))
+    }
+
+    /// The preferred definition of the token, shown when only one can be displayed
+    pub fn primary_definition(&self) -> Option<&CtagItem> {
+        self.definitions
+            .iter()
+            .min_by(|a, b| self.compare_definitions(a, b))
+    }
+
+    /// Every definition in a stable order
+    ///
+    /// Definitions in application files come before those in classified test files, more
+    /// specific kinds (e.g. a class or method) come before less specific ones (e.g. a variable),
+    /// and the rest are ordered by path and line.
+    pub fn sorted_definitions(&self) -> Vec<&CtagItem> {
+        self.definitions
+            .iter()
+            .sorted_by(|a, b| self.compare_definitions(a, b))
+            .collect()
+    }
+
+    /// The paths where the token is defined, following the order of `sorted_definitions`
+    pub fn sorted_defined_paths(&self) -> Vec<&PathBuf> {
+        self.sorted_definitions()
+            .into_iter()
+            .map(|d| &d.file_path)
+            .unique()
+            .collect()
     }
 
     /// All languages based on matched `CtagItem`s
@@ -53,9 +102,23 @@ impl Token {
     /// Do all `CtagItem`s meet a particular constraint?
     pub fn only_ctag<F>(&self, check: F) -> bool
     where
-        F: FnOnce(&CtagItem) -> bool + Copy,
+        F: Fn(&CtagItem) -> bool,
     {
-        self.definitions.iter().all(|ct| check(ct))
+        self.definitions.iter().all(check)
+    }
+
+    fn compare_definitions(&self, a: &CtagItem, b: &CtagItem) -> Ordering {
+        self.test_paths
+            .contains(&a.file_path)
+            .cmp(&self.test_paths.contains(&b.file_path))
+            .then_with(|| a.kind.specificity().cmp(&b.kind.specificity()))
+            .then_with(|| a.file_path.cmp(&b.file_path))
+            .then_with(|| {
+                a.line()
+                    .unwrap_or(usize::MAX)
+                    .cmp(&b.line().unwrap_or(usize::MAX))
+            })
+            .then_with(|| a.encode().cmp(&b.encode()))
     }
 
     fn build_tokens_from_outcome(outcome: Tags) -> Vec<Token> {
@@ -69,9 +132,21 @@ impl Token {
     }
 
     fn strip_prepended_punctuation(input: &str) -> String {
-        input
-            .trim_start_matches(|c| c == '#' || c == '.')
-            .to_string()
+        input.trim_start_matches(['#', '.']).to_string()
+    }
+}
+
+/// Definitions and defined paths serialize in the order of `sorted_definitions`
+impl Serialize for Token {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Token", 3)?;
+        state.serialize_field("token", &self.token)?;
+        state.serialize_field("definitions", &self.sorted_definitions())?;
+        state.serialize_field("defined_paths", &self.sorted_defined_paths())?;
+        state.end()
     }
 }
 
@@ -101,13 +176,71 @@ mod tests {
             kind: TokenKind::Class,
         };
         let tokens = Token::build_tokens_from_outcome(
-            vec![instance_method_spec, instance_method]
+            [instance_method_spec, instance_method]
                 .iter()
                 .cloned()
                 .collect::<Tags>(),
         );
 
         assert_eq!(tokens.len(), 1);
-        assert_eq!(tokens.iter().nth(0).unwrap().token, "name");
+        assert_eq!(tokens.first().unwrap().token, "name");
+    }
+
+    fn build_definition(path: &str, line: usize, kind: TokenKind) -> CtagItem {
+        CtagItem {
+            name: String::from("name"),
+            file_path: PathBuf::from(path),
+            address: line.to_string(),
+            language: Some(Language::Ruby),
+            tags: BTreeMap::new(),
+            kind,
+        }
+    }
+
+    #[test]
+    fn primary_definition_prefers_application_files_and_specific_kinds() {
+        let mut token = Token::new(
+            String::from("name"),
+            [
+                build_definition("spec/models/person_spec.rb", 1, TokenKind::Class),
+                build_definition("app/models/team.rb", 4, TokenKind::Variable),
+                build_definition("app/models/person.rb", 12, TokenKind::Method),
+                build_definition("app/models/person.rb", 3, TokenKind::Method),
+            ]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+
+        assert_eq!(
+            token.first_path(),
+            Path::new("spec/models/person_spec.rb"),
+            "classes are more specific than methods"
+        );
+
+        token.classify_test_paths(|path| path.starts_with("spec/"));
+
+        assert_eq!(token.first_path(), Path::new("app/models/person.rb"));
+        assert_eq!(
+            token
+                .sorted_definitions()
+                .iter()
+                .map(|d| (d.file_path.to_string_lossy().to_string(), d.line()))
+                .collect::<Vec<_>>(),
+            vec![
+                (String::from("app/models/person.rb"), Some(3)),
+                (String::from("app/models/person.rb"), Some(12)),
+                (String::from("app/models/team.rb"), Some(4)),
+                (String::from("spec/models/person_spec.rb"), Some(1)),
+            ]
+        );
+        assert_eq!(
+            token.sorted_defined_paths(),
+            vec![
+                &PathBuf::from("app/models/person.rb"),
+                &PathBuf::from("app/models/team.rb"),
+                &PathBuf::from("spec/models/person_spec.rb"),
+            ]
+        );
     }
 }
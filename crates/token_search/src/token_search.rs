@@ -1,19 +1,19 @@
+use super::file_restriction::FileRestriction;
+use super::progress::{ProgressPhase, ProgressSink, TerminalProgress};
 use super::token::Token;
-use aho_corasick::{AhoCorasickBuilder, MatchKind};
-use codebase_files::CodebaseFiles;
-use indicatif::ParallelProgressIterator;
-use indicatif::{ProgressBar, ProgressStyle};
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
+use codebase_files::GitTree;
 use itertools::Itertools;
-use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use rayon::prelude::*;
 use read_ctags::{Language, TokenKind};
 use serde::ser::SerializeMap;
 use serde::{Serialize, Serializer};
-use std::collections::{HashMap, HashSet};
-use std::convert::TryInto;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::io;
 use std::iter::FromIterator;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// A TokenSearchConfig is necessary to construct the list of tokens and files to search against
 /// when generating results.
@@ -25,15 +25,29 @@ pub struct TokenSearchConfig {
     pub filter_tokens: fn(&Token) -> bool,
     /// Tokens to be used when searching
     pub tokens: Vec<Token>,
-    /// Filenames to search against
+    /// Filenames to search against, relative to `root`
     pub files: Vec<PathBuf>,
-    /// Should a progress bar be displayed?
-    pub display_progress: bool,
-    /// Restrict languages searched (based on file extension)
+    /// Directory the codebase being searched lives in
+    pub root: PathBuf,
+    /// Where progress is reported while searching and analyzing
+    pub progress: Arc<dyn ProgressSink>,
+    /// Restrict tokens searched for based on the language they're defined in
     pub language_restriction: LanguageRestriction,
+    /// Restrict which files are searched for token occurrences
+    pub file_restriction: FileRestriction,
+    /// Additional git trees (e.g. other branches) whose file contents are also searched
+    ///
+    /// Occurrences within these are tracked separately from the working tree's occurrences.
+    pub git_trees: Vec<GitTree>,
+    /// Root directories of other codebases consuming the tokens being searched
+    ///
+    /// Files within these roots are expected to be present in `files`; occurrences within them
+    /// are tracked separately, keyed by root.
+    pub consumers: Vec<PathBuf>,
 }
 
 /// LanguageRestriction allows for filtering out what's searched
+#[derive(Clone, Debug, PartialEq)]
 pub enum LanguageRestriction {
     /// All lanugages are searched
     NoRestriction,
@@ -49,63 +63,110 @@ impl std::fmt::Display for LanguageRestriction {
             LanguageRestriction::NoRestriction => write!(f, "all file types"),
             LanguageRestriction::Only(languages) => write!(
                 f,
-                "{}",
-                format!(
-                    "only {}",
-                    languages.iter().map(|l| l.to_string()).join(", ")
-                )
+                "only {}",
+                languages.iter().map(|l| l.to_string()).join(", ")
             ),
             LanguageRestriction::Except(languages) => write!(
                 f,
-                "{}",
-                format!(
-                    "except {}",
-                    languages.iter().map(|l| l.to_string()).join(", ")
-                )
+                "except {}",
+                languages.iter().map(|l| l.to_string()).join(", ")
             ),
         }
     }
 }
 
+impl LanguageRestriction {
+    /// Search every language except data and documentation (JSON and Markdown)
+    pub fn except_data_and_docs() -> Self {
+        LanguageRestriction::Except(HashSet::from_iter(
+            [Language::JSON, Language::Markdown].iter().cloned(),
+        ))
+    }
+}
+
+/// No files are listed by default; callers provide the files to search
 impl Default for TokenSearchConfig {
     fn default() -> Self {
         TokenSearchConfig {
-            filter_tokens: |t| {
-                !t.token.contains(" ")
-                    && t.token.len() > 1
-                    && !t.only_ctag(|ct| ct.kind == TokenKind::RSpecDescribe)
-            },
+            filter_tokens: TokenSearchConfig::is_searchable,
             tokens: vec![],
-            files: CodebaseFiles::all().paths,
-            display_progress: true,
-            language_restriction: LanguageRestriction::Except(HashSet::from_iter(
-                vec![Language::JSON, Language::Markdown].iter().cloned(),
-            )),
+            files: vec![],
+            root: PathBuf::from("."),
+            progress: Arc::new(TerminalProgress::default()),
+            language_restriction: LanguageRestriction::except_data_and_docs(),
+            file_restriction: FileRestriction::default(),
+            git_trees: vec![],
+            consumers: vec![],
         }
     }
 }
 
 impl TokenSearchConfig {
-    fn progress_bar(prefix: &str, size: usize) -> ProgressBar {
-        let pb = ProgressBar::new(size.try_into().unwrap());
-        pb.set_message(prefix);
-        pb.set_style(
-            ProgressStyle::default_bar()
-                .template("{msg:12} [{bar:40.cyan/blue}] {pos:>7}/{len:7}({eta})")
-                .progress_chars("#>-"),
-        );
-        pb
+    /// Skip tokens containing spaces, single characters, and RSpec describe blocks
+    pub fn is_searchable(token: &Token) -> bool {
+        !token.token.contains(' ')
+            && token.token.len() > 1
+            && !token.only_ctag(|ct| ct.kind == TokenKind::RSpecDescribe)
+    }
+
+    /// Tokens which will be searched for, after filtering by token and language
+    pub fn searchable_tokens(&self) -> Vec<&Token> {
+        self.tokens
+            .iter()
+            .filter(|t| self.filter_token(t) && self.filter_language(t))
+            .collect()
     }
 
-    /// Generate a progress bar with configurable message
+    /// Files which will be searched, after applying the file restriction
     ///
-    /// This takes into account the `display_progress` flag
-    pub fn toggleable_progress_bar(&self, prefix: &str, size: usize) -> ProgressBar {
-        if self.display_progress {
-            Self::progress_bar(prefix, size)
-        } else {
-            ProgressBar::hidden()
+    /// Files where any searchable token is defined are always included.
+    pub fn searchable_files(&self) -> Vec<&PathBuf> {
+        self.filter_files(&self.files, &self.searchable_tokens())
+    }
+
+    fn filter_files<'a>(&self, files: &'a [PathBuf], tokens: &[&Token]) -> Vec<&'a PathBuf> {
+        if self.file_restriction.is_unrestricted() {
+            return files.iter().collect();
+        }
+
+        let defined_paths: HashSet<&Path> = tokens
+            .iter()
+            .flat_map(|t| t.defined_paths.iter().map(|p| p.as_path()))
+            .collect();
+        let matcher = self.file_restriction.matcher();
+
+        files
+            .iter()
+            .filter(|f| defined_paths.contains(f.as_path()) || matcher.allows(f))
+            .collect()
+    }
+
+    fn consumer_for(&self, path: &Path) -> Option<&PathBuf> {
+        self.consumers.iter().find(|root| path.starts_with(root))
+    }
+
+    fn partition_by_consumer<T>(
+        &self,
+        occurrences: HashMap<PathBuf, T>,
+    ) -> (HashMap<PathBuf, T>, BTreeMap<PathBuf, HashMap<PathBuf, T>>) {
+        let mut local = HashMap::new();
+        let mut consumers: BTreeMap<PathBuf, HashMap<PathBuf, T>> = BTreeMap::new();
+
+        for (path, value) in occurrences {
+            match self.consumer_for(&path) {
+                Some(root) => {
+                    consumers
+                        .entry(root.to_path_buf())
+                        .or_default()
+                        .insert(path, value);
+                }
+                None => {
+                    local.insert(path, value);
+                }
+            }
         }
+
+        (local, consumers)
     }
 
     fn filter_token(&self, token: &Token) -> bool {
@@ -129,12 +190,18 @@ impl TokenSearchConfig {
     }
 }
 
+/// Occurrence counts per file, keyed by token index
+type OccurrenceCounts = HashMap<usize, HashMap<PathBuf, usize>>;
+
+/// Occurrence counts per file and git reference, keyed by token index
+type RefOccurrenceCounts = HashMap<usize, BTreeMap<String, HashMap<PathBuf, usize>>>;
+
 /// Search results
 pub struct TokenSearchResults(Vec<TokenSearchResult>);
 
 impl TokenSearchResults {
     /// Convenience method for generating results with the default config
-    pub fn generate() -> Self {
+    pub fn generate() -> Result<Self, io::Error> {
         Self::generate_with_config(&TokenSearchConfig::default())
     }
 
@@ -144,40 +211,28 @@ impl TokenSearchResults {
     }
 
     /// Generate results based on provided search config
-    pub fn generate_with_config(config: &TokenSearchConfig) -> Self {
-        let filtered_results: Vec<_> = config
-            .tokens
-            .iter()
-            .filter(|t| config.filter_token(t) && config.filter_language(t))
-            .collect();
+    ///
+    /// This fails when any of the config's git trees can't be read.
+    pub fn generate_with_config(config: &TokenSearchConfig) -> Result<Self, io::Error> {
+        let filtered_results = config.searchable_tokens();
+        let files = config.filter_files(&config.files, &filtered_results);
 
         let tokens: Vec<_> = filtered_results.iter().map(|r| &r.token).collect();
         let ac = AhoCorasickBuilder::new()
             .match_kind(MatchKind::LeftmostLongest)
             .build(tokens);
 
-        let res = config
-            .files
+        config.progress.start(ProgressPhase::Searching, files.len());
+
+        let res = files
             .par_iter()
-            .progress_with(config.toggleable_progress_bar(&"🤔 Working...", config.files.len()))
             .fold(HashMap::new, |mut results, f| {
-                if let Ok(contents) = Self::read_file(&f) {
-                    for (key, res) in ac
-                        .find_iter(&contents)
-                        .map(|v| v.pattern())
-                        .into_iter()
-                        .sorted_by_key(|&v| v)
-                        .group_by(|&v| v)
-                        .into_iter()
-                        .map(|(idx, res)| (idx, res.count()))
-                        .collect::<Vec<(usize, usize)>>()
-                    {
-                        let file_with_occurrences = results.entry(key).or_insert(HashMap::new());
-
-                        file_with_occurrences.insert(f.clone(), res);
-                    }
+                if let Ok(contents) = Self::read_file(&config.root.join(f)) {
+                    Self::record_occurrence_lines(&ac, f, &contents, &mut results);
                 }
 
+                config.progress.advance(ProgressPhase::Searching, 1);
+
                 results
             })
             .reduce(HashMap::new, |m1, m2| {
@@ -188,20 +243,168 @@ impl TokenSearchResults {
                 })
             });
 
+        config.progress.finish(ProgressPhase::Searching);
+
+        let mut ref_results = Self::search_git_trees(config, &filtered_results, &ac)?;
+
         let final_results = res
             .into_iter()
-            .map(|(idx, occurrences)| TokenSearchResult {
-                token: filtered_results[idx].clone(),
-                occurrences,
+            .map(|(idx, occurrences)| {
+                let (occurrence_lines, consumer_lines) = config.partition_by_consumer(occurrences);
+
+                TokenSearchResult {
+                    token: filtered_results[idx].clone(),
+                    occurrences: Self::counts(&occurrence_lines),
+                    occurrence_lines,
+                    ref_occurrences: ref_results.remove(&idx).unwrap_or_default(),
+                    consumer_occurrences: consumer_lines
+                        .into_iter()
+                        .map(|(root, lines)| (root, Self::counts(&lines)))
+                        .collect(),
+                }
             })
             .collect();
 
         std::thread::spawn(move || drop(ac));
 
-        TokenSearchResults(final_results)
+        Ok(TokenSearchResults(final_results))
     }
 
-    fn read_file(filename: &PathBuf) -> Result<String, io::Error> {
+    fn search_git_trees(
+        config: &TokenSearchConfig,
+        tokens: &[&Token],
+        ac: &AhoCorasick,
+    ) -> Result<RefOccurrenceCounts, io::Error> {
+        let mut results: RefOccurrenceCounts = HashMap::new();
+
+        if config.git_trees.is_empty() {
+            return Ok(results);
+        }
+
+        let git_trees: Vec<GitTree> = config
+            .git_trees
+            .iter()
+            .map(|tree| GitTree {
+                reference: tree.reference.to_string(),
+                root: tree.root.to_path_buf(),
+                paths: config
+                    .filter_files(&tree.paths, tokens)
+                    .into_iter()
+                    .cloned()
+                    .collect(),
+            })
+            .collect();
+
+        config.progress.start(
+            ProgressPhase::SearchingRefs,
+            git_trees.iter().map(|t| t.paths.len()).sum(),
+        );
+
+        for tree in git_trees.iter() {
+            let tree_results = Self::search_git_tree(config, tree, ac)?;
+
+            for (idx, occurrences) in tree_results {
+                results
+                    .entry(idx)
+                    .or_default()
+                    .insert(tree.reference.to_string(), occurrences);
+            }
+        }
+
+        config.progress.finish(ProgressPhase::SearchingRefs);
+
+        Ok(results)
+    }
+
+    /// Count occurrences within a git tree, reading its files in parallel batches
+    fn search_git_tree(
+        config: &TokenSearchConfig,
+        tree: &GitTree,
+        ac: &AhoCorasick,
+    ) -> Result<OccurrenceCounts, io::Error> {
+        let batch_size = (tree.paths.len() / rayon::current_num_threads()).max(1);
+
+        tree.paths
+            .par_chunks(batch_size)
+            .map(|paths| {
+                let batch = GitTree {
+                    reference: tree.reference.to_string(),
+                    root: tree.root.to_path_buf(),
+                    paths: paths.to_vec(),
+                };
+                let mut batch_results = HashMap::new();
+
+                batch.each_file(|f, contents| {
+                    Self::count_occurrences(ac, f, contents, &mut batch_results);
+                    config.progress.advance(ProgressPhase::SearchingRefs, 1);
+                })?;
+
+                Ok(batch_results)
+            })
+            .try_reduce(HashMap::new, |mut m1, m2| {
+                for (idx, occurrences) in m2 {
+                    m1.entry(idx)
+                        .or_insert_with(HashMap::new)
+                        .extend(occurrences);
+                }
+                Ok(m1)
+            })
+    }
+
+    fn count_occurrences(
+        ac: &AhoCorasick,
+        file: &Path,
+        contents: &str,
+        results: &mut OccurrenceCounts,
+    ) {
+        for (key, res) in ac
+            .find_iter(contents)
+            .map(|v| v.pattern())
+            .sorted_by_key(|&v| v)
+            .group_by(|&v| v)
+            .into_iter()
+            .map(|(idx, res)| (idx, res.count()))
+            .collect::<Vec<(usize, usize)>>()
+        {
+            let file_with_occurrences = results.entry(key).or_default();
+
+            file_with_occurrences.insert(file.to_path_buf(), res);
+        }
+    }
+
+    fn record_occurrence_lines(
+        ac: &AhoCorasick,
+        file: &Path,
+        contents: &str,
+        results: &mut HashMap<usize, HashMap<PathBuf, Vec<usize>>>,
+    ) {
+        let line_starts: Vec<usize> = std::iter::once(0)
+            .chain(contents.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        for found in ac.find_iter(contents) {
+            let line = match line_starts.binary_search(&found.start()) {
+                Ok(idx) => idx + 1,
+                Err(idx) => idx,
+            };
+
+            results
+                .entry(found.pattern())
+                .or_default()
+                .entry(file.to_path_buf())
+                .or_default()
+                .push(line);
+        }
+    }
+
+    fn counts(occurrence_lines: &HashMap<PathBuf, Vec<usize>>) -> HashMap<PathBuf, usize> {
+        occurrence_lines
+            .iter()
+            .map(|(path, lines)| (path.to_path_buf(), lines.len()))
+            .collect()
+    }
+
+    fn read_file(filename: &Path) -> Result<String, io::Error> {
         let contents = fs::read_to_string(filename)?;
 
         Ok(contents)
@@ -228,6 +431,15 @@ pub struct TokenSearchResult {
     pub token: Token,
     /// A HashMap of paths and occurrence counts
     pub occurrences: HashMap<PathBuf, usize>,
+    /// The 1-based line number of each occurrence, keyed by path
+    #[serde(skip)]
+    pub occurrence_lines: HashMap<PathBuf, Vec<usize>>,
+    /// Paths and occurrence counts within additional git trees, keyed by git reference
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub ref_occurrences: BTreeMap<String, HashMap<PathBuf, usize>>,
+    /// Paths and occurrence counts within consuming codebases, keyed by consumer root
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub consumer_occurrences: BTreeMap<PathBuf, HashMap<PathBuf, usize>>,
 }
 
 impl TokenSearchResult {
@@ -244,6 +456,26 @@ impl TokenSearchResult {
             .collect()
     }
 
+    /// Git references where the token occurs more often than it's defined
+    pub fn refs_with_usage(&self) -> Vec<&str> {
+        self.ref_occurrences
+            .iter()
+            .filter(|(_, occurrences)| {
+                occurrences.values().sum::<usize>() > self.token.definitions.len()
+            })
+            .map(|(reference, _)| reference.as_str())
+            .collect()
+    }
+
+    /// Roots of consuming codebases where the token occurs
+    pub fn consumers_with_usage(&self) -> Vec<&PathBuf> {
+        self.consumer_occurrences
+            .iter()
+            .filter(|(_, occurrences)| occurrences.values().sum::<usize>() > 0)
+            .map(|(root, _)| root)
+            .collect()
+    }
+
     fn all_occurred_paths(&self) -> HashSet<PathBuf> {
         self.occurrences.keys().cloned().collect()
     }
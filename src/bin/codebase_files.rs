@@ -1,7 +1,19 @@
-use codebase_files::CodebaseFiles;
+use codebase_files::{CodebaseFiles, FileSource, WalkOptions};
 
 fn main() {
-    let files = CodebaseFiles::all();
+    let options = WalkOptions {
+        source: Some(FileSource::Git {
+            include_untracked: false,
+        }),
+        ..Default::default()
+    };
+    let files = CodebaseFiles::with_options(&options).unwrap_or_else(|e| {
+        eprintln!(
+            "Unable to list files tracked by git, walking the filesystem instead: {}",
+            e
+        );
+        CodebaseFiles::all()
+    });
     for f in files.paths.iter().filter_map(|v| v.to_str()) {
         println!("{}", f);
     }
@@ -1,3 +1,4 @@
+use codebase_files::CodebaseFiles;
 use read_ctags::TagsReader;
 use token_search::{Token, TokenSearchConfig, TokenSearchResults};
 
@@ -5,11 +6,15 @@ fn main() {
     let tags_reader = TagsReader::default();
     match Token::all(&tags_reader) {
         Ok((_, outcome)) => {
-            let mut config = TokenSearchConfig::default();
-            config.tokens = outcome;
-            let results = TokenSearchResults::generate_with_config(&config);
-
-            println!("{}", serde_json::to_string(&results).unwrap());
+            let config = TokenSearchConfig {
+                tokens: outcome,
+                files: CodebaseFiles::all().paths,
+                ..Default::default()
+            };
+            match TokenSearchResults::generate_with_config(&config) {
+                Ok(results) => println!("{}", serde_json::to_string(&results).unwrap()),
+                Err(e) => eprintln!("{}", e),
+            }
         }
         Err(e) => eprintln!("{}", e),
     }
@@ -2,8 +2,6 @@
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
-use cli;
-
 fn main() {
     cli::run();
 }
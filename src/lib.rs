@@ -0,0 +1,25 @@
+//! `unused` identifies unused code in Rails, Phoenix, and other types of applications.
+//!
+//! Alongside the `unused` binary, this library exposes the same analysis for use from other Rust
+//! tooling via `Analysis`, which returns a typed `AnalysisReport` without printing or exiting.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use unused::{Analysis, TagsReader, UsageLikelihoodStatus};
+//!
+//! let report = Analysis::default()
+//!     .tags_reader(TagsReader::default())
+//!     .run()
+//!     .expect("tags file should be readable");
+//!
+//! let high_likelihood = report
+//!     .findings()
+//!     .into_iter()
+//!     .filter(|usage| usage.usage_likelihood.status == UsageLikelihoodStatus::High)
+//!     .count();
+//!
+//! println!("{} tokens are likely unused", high_likelihood);
+//! ```
+
+pub use analysis::*;
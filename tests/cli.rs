@@ -44,11 +44,657 @@ fn token_search_successful() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn progress_reported_as_json_lines() -> Result<(), Box<dyn std::error::Error>> {
+    let (_file, mut cmd) = configure_command_with_tags_file_override()?;
+
+    cmd.arg("--progress=json");
+
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains(
+            r#"{"event":"start","phase":"searching""#,
+        ))
+        .stderr(predicate::str::contains(
+            r#"{"event":"finish","phase":"analyzing""#,
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn searched_files_reported_in_summary() -> Result<(), Box<dyn std::error::Error>> {
+    let (_file, mut cmd) = configure_command_with_tags_file_override()?;
+
+    cmd.arg("--search-exclude=vendor/**");
+    cmd.arg("--search-exclude-category=fixtures");
+
+    cmd.assert().success().stdout(predicate::str::contains(
+        "Searched files: excluding documentation, fixtures, vendor/**",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn invalid_glob_reports_failure() -> Result<(), Box<dyn std::error::Error>> {
+    let (_file, mut cmd) = configure_command_with_tags_file_override()?;
+
+    cmd.arg("-C").arg("crates/codebase_files");
+    cmd.arg("--glob=[");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Failed to find files"));
+
+    Ok(())
+}
+
+#[test]
+fn group_by_directory_prints_subtotals_and_hotspots() -> Result<(), Box<dyn std::error::Error>> {
+    let (_file, mut cmd) = configure_command_with_tags_file_override()?;
+
+    cmd.arg("-a");
+    cmd.arg("--group-by=directory");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("== crates/read_ctags/src/ =="))
+        .stdout(predicate::str::contains("(1 tokens across 1 files"))
+        .stdout(predicate::str::contains("== HOTSPOTS =="))
+        .stdout(predicate::str::contains("Grouped by: directory"));
+
+    Ok(())
+}
+
+#[test]
+fn group_by_included_in_json() -> Result<(), Box<dyn std::error::Error>> {
+    let (_file, mut cmd) = configure_command_with_tags_file_override()?;
+
+    cmd.arg("-a");
+    cmd.arg("--json");
+    cmd.arg("--group-by=file");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::starts_with(r#"{"version":1,"tokens":[{"#))
+        .stdout(predicate::str::contains(
+            r#""groups":[{"key":"crates/read_ctags/src/token_kind.rs","token_count":1,"file_count":1,"high":0,"medium":0,"low":1,"tokens":["Alias"]}]"#,
+        ))
+        .stdout(predicate::str::contains(
+            r#""hotspots":[{"directory":"crates/read_ctags/src/","#,
+        ));
+
+    let (_file, mut cmd) = configure_command_with_tags_file_override()?;
+    cmd.args(["-a", "--json"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::starts_with(r#"{"version":1,"tokens":[{"#))
+        .stdout(predicate::str::contains(r#""groups":[],"hotspots":[]"#));
+
+    Ok(())
+}
+
+#[test]
+fn hotspots_weight_density_by_finding_count() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("tags").write_str(
+        "One	lib/one/one.rb	/^class One$/;\"	c\n\
+         Unused1	lib/many/many.rb	/^class Unused1$/;\"	c\n\
+         Unused2	lib/many/many.rb	/^class Unused2$/;\"	c\n\
+         Unused3	lib/many/many.rb	/^class Unused3$/;\"	c\n\
+         Used	lib/many/many.rb	/^class Used$/;\"	c",
+    )?;
+    root.child("lib/one/one.rb").write_str("class One\nend\n")?;
+    root.child("lib/many/many.rb").write_str(
+        "class Unused1\nend\nclass Unused2\nend\nclass Unused3\nend\nclass Used\nend\nUsed.new\n",
+    )?;
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args(["--no-color", "-P", "--search-exclude", "tags"]);
+    cmd.args(["--group-by", "directory"]);
+    cmd.assert().success().stdout(predicate::str::contains(
+        "== HOTSPOTS ==\n   lib/many/ 75% (3 of 4 tokens across 1 files)\n   lib/one/ 100% (1 of 1 tokens across 1 files)\n",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn baseline_hides_known_findings() -> Result<(), Box<dyn std::error::Error>> {
+    let baseline = assert_fs::NamedTempFile::new("baseline.json")?;
+
+    let (_file, mut cmd) = configure_command_with_tags_file_override()?;
+    cmd.arg("-a");
+    cmd.arg("--write-baseline");
+    cmd.arg(baseline.path());
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("Wrote 1 findings to baseline"));
+
+    let (_file, mut cmd) = configure_command_with_tags_file_override()?;
+    cmd.arg("-a");
+    cmd.arg("--harsh");
+    cmd.arg("--baseline");
+    cmd.arg(baseline.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Tokens found: 0"));
+
+    let (_file, mut cmd) = configure_command_with_tags_file_override()?;
+    cmd.arg("-a");
+    cmd.arg("--baseline");
+    cmd.arg(baseline.path());
+    cmd.arg("--write-baseline");
+    cmd.arg(baseline.path());
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("Wrote 1 findings to baseline"));
+
+    Ok(())
+}
+
+#[test]
+fn baseline_lists_stale_entries() -> Result<(), Box<dyn std::error::Error>> {
+    let baseline = assert_fs::NamedTempFile::new("baseline.json")?;
+    baseline.write_str(
+        r#"{"version":1,"findings":[{"token":"Removed","path":"app/models/removed.rb"}]}"#,
+    )?;
+
+    let (_file, mut cmd) = configure_command_with_tags_file_override()?;
+    cmd.arg("-a");
+    cmd.arg("--harsh");
+    cmd.arg("--baseline");
+    cmd.arg(baseline.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Baseline entries no longer reported: (1)",
+        ))
+        .stderr(predicate::str::contains("Removed app/models/removed.rb"));
+
+    Ok(())
+}
+
+#[test]
+fn ignore_adds_to_and_prunes_allowlist() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("tags")
+        .write_str("Alias	src/token_kind.rs	/^    Alias,$/;\"	e	enum:TokenKind")?;
+    root.child("src/token_kind.rs")
+        .write_str("enum TokenKind {\n    Alias,\n}\n")?;
+    root.child(".unused-allowlist")
+        .write_str("# kept by hand\n- token: Kept\n  reason: dynamic dispatch\n")?;
+
+    for token in ["Alias", "Removed"] {
+        let mut cmd = Command::cargo_bin("unused")?;
+        cmd.arg("-C").arg(root.path());
+        cmd.args([
+            "ignore",
+            token,
+            "--reason",
+            "used by macros",
+            "--author",
+            "Jane",
+        ]);
+        cmd.assert().success();
+    }
+
+    root.child(".unused-allowlist")
+        .assert(predicate::str::starts_with(
+            "# kept by hand\n- token: Kept\n  reason: dynamic dispatch\n- token: Alias\n",
+        ));
+    root.child(".unused-allowlist")
+        .assert(predicate::str::contains("- token: Removed"));
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args(["ignore", "--prune"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Removed 2 entries"));
+
+    root.child(".unused-allowlist").assert(
+        predicate::str::contains("- token: Alias\n  reason: used by macros\n  author: Jane")
+            .and(predicate::str::contains("Removed").not()),
+    );
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.arg("-a");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Tokens found: 0"))
+        .stdout(predicate::str::contains("Allowlisted tokens: 1"));
+
+    Ok(())
+}
+
+#[test]
+fn snapshots_are_compared_in_trend() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("tags")
+        .write_str("Alias	src/token_kind.rs	/^    Alias,$/;\"	e	enum:TokenKind")?;
+    root.child("src/token_kind.rs")
+        .write_str("enum TokenKind {\n    Alias,\n}\n")?;
+
+    for label in ["before", "after"] {
+        if label == "after" {
+            root.child(".unused-allowlist")
+                .write_str("- token: Alias\n  reason: used by macros\n")?;
+        }
+
+        let mut cmd = Command::cargo_bin("unused")?;
+        cmd.arg("-C").arg(root.path());
+        cmd.args(["-a", "snapshot", "--label", label]);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Recorded"));
+    }
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args(["--no-color", "trend"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("== CHANGES (1 -> 2) =="))
+        .stdout(predicate::str::contains("findings: 1 -> 0 (-1)"))
+        .stdout(predicate::str::contains("* src/token_kind.rs#Alias"));
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args(["--json", "trend"]);
+    cmd.assert().success().stdout(predicate::str::contains(
+        r#""removed":["src/token_kind.rs#Alias"]"#,
+    ));
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args(["--no-color", "trend", "--to", "1"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("== TREND =="))
+        .stdout(predicate::str::contains("== CHANGES").not());
+
+    Ok(())
+}
+
+#[test]
+fn trend_rejects_snapshots_which_do_not_exist() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("tags")
+        .write_str("Alias	src/token_kind.rs	/^    Alias,$/;\"	e	enum:TokenKind")?;
+    root.child("src/token_kind.rs")
+        .write_str("enum TokenKind {\n    Alias,\n}\n")?;
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args(["-a", "snapshot"]);
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args(["--no-color", "trend", "--to", "7"]);
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Snapshot 7 does not exist; there are 1",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn older_than_limits_findings_by_blame() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("tags").write_str(
+        "Alias	src/token_kind.rs	/^    Alias,$/;\"	e	enum:TokenKind\n\
+         Recent	src/token_kind.rs	/^    Recent,$/;\"	e	enum:TokenKind",
+    )?;
+    root.child("src/token_kind.rs")
+        .write_str("enum TokenKind {\n    Alias,\n}\n")?;
+
+    let git = |args: &[&str], date: &str| {
+        Command::new("git")
+            .current_dir(root.path())
+            .args([
+                "-c",
+                "user.name=Jane Doe",
+                "-c",
+                "user.email=jane@example.com",
+            ])
+            .args(args)
+            .env("GIT_AUTHOR_DATE", date)
+            .env("GIT_COMMITTER_DATE", date)
+            .output()
+    };
+    git(&["init", "-q"], "")?;
+    git(&["add", "src"], "")?;
+    git(&["commit", "-qm", "Add TokenKind"], "2020-01-01T00:00:00")?;
+    root.child("src/token_kind.rs")
+        .write_str("enum TokenKind {\n    Alias,\n    Recent,\n}\n")?;
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args(["--no-color", "-P", "-a", "--blame"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Last modified: 2020-01-01T00:00:00Z by Jane Doe",
+        ))
+        .stdout(predicate::str::contains(
+            "Last modified: uncommitted changes",
+        ));
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args(["--no-color", "-P", "-a", "--older-than", "180d"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Alias"))
+        .stdout(predicate::str::contains("Recent").not())
+        .stdout(predicate::str::contains("Older than: 180d"));
+
+    Ok(())
+}
+
+#[test]
+fn ignore_defaults_author_to_the_project_git_user() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("tags")
+        .write_str("Alias	src/token_kind.rs	/^    Alias,$/;\"	e	enum:TokenKind")?;
+    Command::new("git")
+        .current_dir(root.path())
+        .args(["init", "-q"])
+        .output()?;
+    Command::new("git")
+        .current_dir(root.path())
+        .args(["config", "user.name", "Project Maintainer"])
+        .output()?;
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args(["ignore", "Alias", "--reason", "used by macros"]);
+    cmd.assert().success();
+
+    root.child(".unused-allowlist")
+        .assert(predicate::str::contains("author: Project Maintainer"));
+
+    Ok(())
+}
+
+#[test]
+fn since_includes_untracked_files() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("tags").write_str(
+        "Alias	src/token_kind.rs	/^    Alias,$/;\"	e	enum:TokenKind\n\
+         Untracked	src/untracked.rs	/^pub struct Untracked;$/;\"	s",
+    )?;
+    root.child("src/token_kind.rs")
+        .write_str("enum TokenKind {\n    Alias,\n}\n")?;
+
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .current_dir(root.path())
+            .args([
+                "-c",
+                "user.name=Jane Doe",
+                "-c",
+                "user.email=jane@example.com",
+            ])
+            .args(args)
+            .output()
+    };
+    git(&["init", "-q"])?;
+    git(&["add", "src"])?;
+    git(&["commit", "-qm", "Add TokenKind"])?;
+    root.child("src/untracked.rs")
+        .write_str("pub struct Untracked;\n")?;
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args(["--no-color", "-P", "-a", "--search-exclude", "tags"]);
+    cmd.args(["--since", "HEAD"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Untracked"))
+        .stdout(predicate::str::contains("Alias").not());
+
+    Ok(())
+}
+
+#[test]
+fn used_only_in_respects_explicit_likelihoods() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("tags")
+        .write_str("Thing	lib/thing.rb	/^class Thing$/;\"	c")?;
+    root.child("lib/thing.rb").write_str("class Thing\nend\n")?;
+    root.child("test/thing_test.rb").write_str("Thing.new\n")?;
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args(["--no-color", "-P", "--search-exclude", "tags"]);
+    cmd.args(["--format", "compact", "--used-only-in", "tests"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Only used in tests"));
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args(["--no-color", "-P", "--search-exclude", "tags"]);
+    cmd.args(["--format", "compact", "--used-only-in", "tests"]);
+    cmd.args(["--likelihood", "high"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Thing").not());
+
+    Ok(())
+}
+
+#[test]
+fn dead_clusters_reported_in_every_format() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("tags").write_str(
+        "helper_a	lib/thing.rb	/^def helper_a$/;\"	f	line:1	end:3\n\
+         helper_b	lib/thing.rb	/^def helper_b$/;\"	f	line:5	end:6",
+    )?;
+    root.child("lib/thing.rb")
+        .write_str("def helper_a\n  helper_b\nend\n\ndef helper_b\nend\n")?;
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args([
+        "--no-color",
+        "-P",
+        "-a",
+        "--search-exclude",
+        "tags",
+        "--json",
+    ]);
+    cmd.assert().success().stdout(predicate::str::contains(
+        r#""dead_clusters":[{"tokens":["helper_a","helper_b"],"chains":[["helper_a","helper_b"]]}]}"#,
+    ));
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args(["--no-color", "-P", "-a", "--search-exclude", "tags"]);
+    cmd.args(["--format", "compact"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("dead cluster: helper_a, helper_b"))
+        .stdout(predicate::str::contains("Only used by unused tokens").not());
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args(["--no-color", "-P", "-a", "--search-exclude", "tags"]);
+    cmd.args(["--format", "compact", "--transitive"]);
+    cmd.assert().success().stdout(predicate::str::contains(
+        "Only used by unused tokens: helper_a",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn removable_lines_reported_per_finding_and_in_summary() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("tags").write_str(
+        "TokenKind	src/token_kind.rs	/^enum TokenKind {$/;\"	g	line:1	end:4\n\
+         Alias	src/token_kind.rs	/^    Alias,$/;\"	e	line:2	enum:TokenKind",
+    )?;
+    root.child("src/token_kind.rs")
+        .write_str("enum TokenKind {\n    Alias,\n    Other,\n}\n")?;
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args(["--no-color", "-P", "-a", "--sort-order", "size:desc"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::is_match(
+            "TokenKind\n.*\n.*\n   Removable lines: ~4\n",
+        )?)
+        .stdout(predicate::str::contains("Removable lines: ~1"))
+        .stdout(predicate::str::contains("Removable lines (estimated): 4"))
+        .stdout(predicate::str::contains("Sort order: size (desc)"));
+
+    Ok(())
+}
+
+#[test]
+fn fix_prints_and_applies_removals() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("tags").write_str(
+        "Widget	src/widget.rs	/^pub struct Widget;$/;\"	s	line:1	end:1\n\
+         unused_helper	src/widget.rs	/^    pub fn unused_helper() {}$/;\"	P	line:5	end:5	implementation:Widget\n\
+         unended	src/widget.rs	/^pub fn unended() {}$/;\"	f	line:8",
+    )?;
+    root.child("src/widget.rs").write_str(
+        "pub struct Widget;\n\nimpl Widget {\n    /// Never called\n    pub fn unused_helper() {}\n}\n\npub fn unended() {}\n",
+    )?;
+    root.child("src/main.rs")
+        .write_str("fn main() {\n    let _ = widget::Widget;\n}\n")?;
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args(["--no-color", "-P", "--search-exclude", "tags"]);
+    cmd.args(["fix", "--likelihood", "high", "--dry-run"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "--- a/src/widget.rs\n+++ b/src/widget.rs\n@@ -1,8 +1,3 @@\n pub struct Widget;\n-\n-impl Widget {\n",
+        ))
+        .stderr(predicate::str::contains(
+            "* unended src/widget.rs (no end line; generate tags with --fields=+ne)",
+        ));
+    root.child("src/widget.rs")
+        .assert(predicate::str::contains("unused_helper"));
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args([
+        "--no-color",
+        "-P",
+        "--search-exclude",
+        "tags",
+        "fix",
+        "--apply",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Removed 5 lines from 1 files"));
+    root.child("src/widget.rs")
+        .assert("pub struct Widget;\n\npub fn unended() {}\n");
+
+    Ok(())
+}
+
+#[test]
+fn fix_skips_ambiguous_tokens_unless_included() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("tags").write_str(
+        "Widget	src/widget.rs	/^pub struct Widget;$/;\"	s	line:1	end:1\n\
+         shared	src/widget.rs	/^pub fn shared() {}$/;\"	f	line:3	end:3\n\
+         shared	src/gadget.rs	/^pub fn shared() {}$/;\"	f	line:1	end:1",
+    )?;
+    root.child("src/widget.rs")
+        .write_str("pub struct Widget;\n\npub fn shared() {}\n")?;
+    root.child("src/gadget.rs")
+        .write_str("pub fn shared() {}\n")?;
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args(["--no-color", "-P", "--search-exclude", "tags"]);
+    cmd.args(["fix", "-a", "--kinds", "function", "--apply"]);
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "* shared src/gadget.rs (2 definitions share its name)",
+        ))
+        .stderr(predicate::str::contains("Widget").not())
+        .stderr(predicate::str::contains("No definitions to remove"));
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args(["--no-color", "-P", "--search-exclude", "tags"]);
+    cmd.args([
+        "fix",
+        "-a",
+        "--kinds",
+        "function",
+        "--include-ambiguous",
+        "--apply",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Removed 3 lines from 2 files"));
+    root.child("src/widget.rs").assert("pub struct Widget;\n");
+    root.child("src/gadget.rs").assert("");
+
+    Ok(())
+}
+
+#[test]
+fn triage_requires_a_terminal_and_its_file_is_not_searched(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("tags").write_str(
+        "triaged_helper	src/widget.rs	/^pub fn triaged_helper() {}$/;\"	f	line:1	end:1",
+    )?;
+    root.child("src/widget.rs")
+        .write_str("pub fn triaged_helper() {}\n")?;
+    // Outside its definition, the token only occurs within the triage file
+    root.child(".unused-triage.json")
+        .write_str("{\"version\":1,\"decisions\":{\"src/widget.rs#triaged_helper\":\"skip\"}}\n")?;
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args(["--no-color", "--search-exclude", "tags", "triage"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Failed to run interactive triage"));
+
+    let mut cmd = Command::cargo_bin("unused")?;
+    cmd.arg("-C").arg(root.path());
+    cmd.args([
+        "--no-color",
+        "--search-exclude",
+        "tags",
+        "--format",
+        "compact",
+    ]);
+    cmd.assert().success().stdout(predicate::str::contains(
+        "triaged_helper    src/widget.rs    Only one occurrence exists",
+    ));
+
+    Ok(())
+}
+
 fn configure_command_with_tags_file_override(
 ) -> Result<(NamedTempFile, Command), Box<dyn std::error::Error>> {
     let mut cmd = Command::cargo_bin("unused")?;
     let file = assert_fs::NamedTempFile::new("tags")?;
-    file.write_str("Alias	../crates/read_ctags/src/token_kind.rs	/^    Alias,$/;\"	e	enum:TokenKind")?;
+    file.write_str(
+        "Alias	../crates/read_ctags/src/token_kind.rs	/^    Alias,$/;\"	e	enum:TokenKind",
+    )?;
 
     let path = file.path().display().to_string();
     cmd.arg("-t");